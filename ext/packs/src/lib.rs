@@ -0,0 +1,152 @@
+// Entry point for the `packs` Ruby native extension. Every function takes the
+// project root as its first argument and returns plain hashes and arrays with
+// symbol keys, shaped like the corresponding `--json` CLI output.
+use std::path::PathBuf;
+
+use magnus::{function, prelude::*, Error, RHash, RModule, Ruby, Value};
+use packs_lib::packs::{
+    self, check_all, pack::Pack, update_package_todos, validate_structured,
+    Configuration, UpdateOptions, ViolationIdentifier,
+};
+use serde_magnus::serialize;
+
+#[magnus::init(name = "packs")]
+fn init(ruby: &Ruby) -> Result<(), Error> {
+    let module: RModule = ruby.define_module("Packs")?.define_module("Rust")?;
+    module.define_module_function("check", function!(check, 2))?;
+    module.define_module_function("update", function!(update, 1))?;
+    module.define_module_function("validate", function!(validate, 1))?;
+    module.define_module_function("list_packs", function!(list_packs, 1))?;
+    module.define_module_function("for_file", function!(for_file, 2))?;
+    Ok(())
+}
+
+fn check(
+    ruby: &Ruby,
+    root: String,
+    files: Vec<String>,
+) -> Result<Value, Error> {
+    let configuration = configuration(ruby, root, files.len())?;
+    let result = check_all(&configuration, files)
+        .map_err(|err| runtime_error(ruby, err))?;
+    serialize(ruby, &result)
+}
+
+fn update(ruby: &Ruby, root: String) -> Result<Value, Error> {
+    let configuration = configuration(ruby, root, 0)?;
+    let options = UpdateOptions {
+        files: vec![],
+        expand_to_pack: false,
+        constant_name: None,
+        violation_type: None,
+        defining_pack_name: None,
+    };
+    let result = update_package_todos(&configuration, &options)
+        .map_err(|err| runtime_error(ruby, err))?;
+
+    let output: RHash = serialize(ruby, &result.stats)?;
+    let strict_mode_violations: Vec<&ViolationIdentifier> = result
+        .strict_mode_violations
+        .iter()
+        .map(|violation| &violation.identifier)
+        .collect();
+    output.aset(
+        ruby.to_symbol("strict_mode_violations"),
+        serialize::<_, Value>(ruby, &strict_mode_violations)?,
+    )?;
+    Ok(output.as_value())
+}
+
+fn validate(ruby: &Ruby, root: String) -> Result<Value, Error> {
+    let configuration = configuration(ruby, root, 0)?;
+    serialize(ruby, &validate_structured(&configuration))
+}
+
+fn list_packs(ruby: &Ruby, root: String) -> Result<Value, Error> {
+    let configuration = configuration(ruby, root, 0)?;
+    let packs = ruby.ary_new_capa(configuration.pack_set.packs.len());
+    for pack in &configuration.pack_set.packs {
+        packs.push(pack_to_ruby(ruby, pack)?)?;
+    }
+    Ok(packs.as_value())
+}
+
+fn for_file(ruby: &Ruby, root: String, file: String) -> Result<Value, Error> {
+    let configuration = configuration(ruby, root, 1)?;
+    let absolute_path = configuration.absolute_root.join(&file);
+    match configuration
+        .pack_set
+        .for_file(&absolute_path)
+        .map_err(|err| runtime_error(ruby, err))?
+    {
+        Some(pack) => pack_to_ruby(ruby, pack),
+        None => Ok(ruby.qnil().as_value()),
+    }
+}
+
+fn configuration(
+    ruby: &Ruby,
+    root: String,
+    input_files_count: usize,
+) -> Result<Configuration, Error> {
+    packs::configuration(PathBuf::from(root), &input_files_count)
+        .map_err(|err| runtime_error(ruby, err))
+}
+
+// The package.yml keys, plus the identifying fields serde skips when a pack
+// is written back to disk.
+fn pack_to_ruby(ruby: &Ruby, pack: &Pack) -> Result<Value, Error> {
+    let output: RHash = serialize(ruby, pack)?;
+    output.aset(ruby.to_symbol("name"), pack.name.as_str())?;
+    output.aset(ruby.to_symbol("yml"), pack.yml.to_string_lossy().as_ref())?;
+    output.aset(
+        ruby.to_symbol("relative_path"),
+        pack.relative_path.to_string_lossy().as_ref(),
+    )?;
+    Ok(output.as_value())
+}
+
+fn runtime_error(ruby: &Ruby, err: impl std::fmt::Display) -> Error {
+    Error::new(ruby.exception_runtime_error(), format!("{:#}", err))
+}
+
+// Run with `cargo test --features ruby-tests`, which needs a Ruby to link
+// against. A Ruby VM can only be started once per process, and only used
+// from the thread that started it, hence a single test.
+#[cfg(all(test, feature = "ruby-tests"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension() -> Result<(), Error> {
+        let _cleanup = unsafe { magnus::embed::init() };
+        let ruby = Ruby::get().unwrap();
+        let root = "../../tests/fixtures/simple_app".to_owned();
+
+        let result = check(&ruby, root.clone(), vec![])?;
+        let checked: bool = magnus::eval!(
+            ruby,
+            "result[:status] == 'failure' &&
+                result[:violations].map { |v| v[:violation_type] }.sort ==
+                    ['dependency', 'privacy'] &&
+                result[:violations].all? { |v| v[:source_location][:line] == 3 }",
+            result
+        )?;
+        assert!(checked);
+
+        let errors = validate(&ruby, root.clone())?;
+        let validated: bool = magnus::eval!(ruby, "errors == []", errors)?;
+        assert!(validated);
+
+        let pack =
+            for_file(&ruby, root, "packs/foo/app/services/foo.rb".to_owned())?;
+        let found: bool = magnus::eval!(
+            ruby,
+            "pack[:name] == 'packs/foo' &&
+                pack[:dependencies].include?('packs/baz')",
+            pack
+        )?;
+        assert!(found);
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+# frozen_string_literal: true
+
+# Loads the native extension, which defines Packs::Rust.check, update,
+# validate, list_packs and for_file. See ADVANCED_USAGE.md.
+require_relative "pks/packs"
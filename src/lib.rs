@@ -57,6 +57,7 @@ mod test_util {
                 enforce_visibility: Default::default(),
                 enforce_folder_privacy: Default::default(),
                 enforce_folder_visibility: None,
+                enforce_rules: Default::default(),
                 enforce_layers: Default::default(),
                 client_keys: Default::default(),
                 owner: Default::default(),
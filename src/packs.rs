@@ -1,5 +1,12 @@
-// Currently there are no supported library APIs for packs. The public API is the CLI.
-// This may change in the future! Please file an issue if you have a use case for a library API.
+// The public API is primarily the CLI, but `packs` is also usable as a library.
+// The library API is versioned with the crate, and consists of the items
+// re-exported below:
+// - `configuration` to load a `Configuration` for a project root
+// - `check_all` and `validate_structured` to get typed results
+// - `update_package_todos` to rewrite package_todo.yml files
+// - `PackSet` and `Pack` to query packs
+// - `constant_resolver` to resolve constants to their definitions
+// Everything else is an implementation detail and may change without notice.
 pub mod cli;
 
 // Module declarations
@@ -9,17 +16,22 @@ pub(crate) mod checker;
 pub(crate) mod configuration;
 pub(crate) mod constant_resolver;
 pub(crate) mod dependencies;
+pub(crate) mod git;
+pub(crate) mod graph;
 pub(crate) mod ignored;
+pub(crate) mod lsp;
+pub(crate) mod metrics;
 pub(crate) mod monkey_patch_detection;
 pub mod pack;
+pub(crate) mod pack_set;
 pub(crate) mod parsing;
 pub(crate) mod raw_configuration;
+pub(crate) mod server;
 pub(crate) mod walk_directory;
 
 mod constant_dependencies;
 mod file_utils;
 mod logger;
-mod pack_set;
 mod package_todo;
 mod reference_extractor;
 
@@ -27,16 +39,26 @@ use crate::packs;
 use crate::packs::pack::write_pack_to_disk;
 use crate::packs::pack::Pack;
 
+// Public API
+pub use self::checker::{
+    check_all, update_package_todos, validate_structured, CheckAllResult,
+    FileParseError, SuppressionReport, SuppressionStatus, UpdateOptions,
+    UpdateResult, ValidationError, Violation, ViolationIdentifier,
+};
+pub use self::configuration::Configuration;
+pub use self::constant_resolver::{ConstantDefinition, ConstantResolver};
+pub use self::pack_set::PackSet;
+pub use self::package_todo::UpdateStats;
+
 // Internal imports
-pub(crate) use self::checker::Violation;
-pub(crate) use self::pack_set::PackSet;
 pub(crate) use self::parsing::process_files_with_cache;
+use self::parsing::process_files_with_cache_and_stats;
 pub(crate) use self::parsing::ruby::experimental::get_experimental_constant_resolver;
 pub(crate) use self::parsing::ruby::zeitwerk::get_zeitwerk_constant_resolver;
+pub(crate) use self::parsing::ParseDiagnostic;
 pub(crate) use self::parsing::ParsedDefinition;
 pub(crate) use self::parsing::UnresolvedReference;
 use anyhow::bail;
-pub(crate) use configuration::Configuration;
 pub(crate) use package_todo::PackageTodo;
 
 // External imports
@@ -164,24 +186,87 @@ See https://github.com/rubyatscale/packs#readme for more info!",
     Ok(())
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+    Junit,
+    Checkstyle,
+    Github,
+}
+
+// `validate` has no per-file results, so it only supports these formats
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidateFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
 pub fn check(
     configuration: &Configuration,
     files: Vec<String>,
-    json: bool,
+    format: OutputFormat,
 ) -> anyhow::Result<()> {
     let result = checker::check_all(configuration, files)
         .context("Failed to check files")?;
-    if json {
-        println!("{}", result.to_json().context("Failed to serialize JSON")?);
-        if result.has_violations() {
-            std::process::exit(1);
-        }
+    report_check_result(&result, format)
+}
+
+/// Checks the files changed since `rev`, along with the files those changes
+/// may introduce violations in (see `checker::files_affected_by`).
+pub fn check_since(
+    mut configuration: Configuration,
+    rev: &str,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let changed_files = git::changed_files(&configuration.absolute_root, rev)?;
+    let files: Vec<String> =
+        checker::files_affected_by(&configuration, rev, &changed_files)?
+            .iter()
+            .map(|file| file.to_string_lossy().to_string())
+            .collect();
+
+    // An empty file list means "check everything" to `check_all`
+    let result = if files.is_empty() {
+        checker::CheckAllResult::default()
     } else {
-        println!("{}", result);
-        if result.has_violations() {
-            let count = result.violation_count();
-            bail!("{} violation(s) found!", count)
+        configuration.input_files_count = files.len();
+        checker::check_all(&configuration, files)
+            .context("Failed to check files")?
+    };
+    report_check_result(&result, format)
+}
+
+fn report_check_result(
+    result: &checker::CheckAllResult,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let output = match format {
+        OutputFormat::Text => {
+            println!("{}", result);
+            if result.has_violations() {
+                let count = result.violation_count();
+                bail!("{} violation(s) found!", count)
+            }
+            return Ok(());
+        }
+        OutputFormat::Json => {
+            result.to_json().context("Failed to serialize JSON")?
         }
+        OutputFormat::Sarif => {
+            result.to_sarif().context("Failed to serialize SARIF")?
+        }
+        OutputFormat::Junit => result.to_junit(),
+        OutputFormat::Checkstyle => result.to_checkstyle(),
+        OutputFormat::Github => result.to_github_annotations(),
+    };
+    println!("{}", output);
+    if result.has_violations() {
+        std::process::exit(1);
     }
     Ok(())
 }
@@ -234,7 +319,8 @@ pub fn add_dependency(
         &configuration.absolute_root,
         &configuration.input_files_count,
     )?;
-    let validation_result = packs::validate(&new_configuration, false);
+    let validation_result =
+        packs::validate(&new_configuration, ValidateFormat::Text);
     if validation_result.is_err() {
         println!("Added `{}` as a dependency to `{}`!", to, from);
         println!("Warning: This creates a cycle!");
@@ -255,12 +341,12 @@ pub fn list_included_files(configuration: Configuration) -> anyhow::Result<()> {
 
 pub fn validate(
     configuration: &Configuration,
-    json: bool,
+    format: ValidateFormat,
 ) -> anyhow::Result<()> {
-    if json {
-        checker::validate_all_json(configuration)
-    } else {
-        checker::validate_all(configuration)
+    match format {
+        ValidateFormat::Text => checker::validate_all(configuration),
+        ValidateFormat::Json => checker::validate_all_json(configuration),
+        ValidateFormat::Sarif => checker::validate_all_sarif(configuration),
     }
 }
 
@@ -384,6 +470,99 @@ pub fn delete_cache(configuration: Configuration) {
     }
 }
 
+pub fn cache_stats(configuration: &Configuration) -> anyhow::Result<()> {
+    let stats = caching::maintenance::stats(configuration);
+    for directory in &stats.directories {
+        println!(
+            "{}: {} entries, {}",
+            directory.name,
+            directory.entries,
+            caching::maintenance::format_size(directory.bytes)
+        );
+    }
+    match stats.last_run {
+        Some(last_run) if last_run.hits + last_run.misses > 0 => println!(
+            "Last run: {} hits, {} misses ({:.1}% hit rate)",
+            last_run.hits,
+            last_run.misses,
+            100.0 * last_run.hits as f64
+                / (last_run.hits + last_run.misses) as f64
+        ),
+        _ => println!("Last run: no files processed"),
+    }
+    Ok(())
+}
+
+pub fn cache_verify(
+    configuration: &Configuration,
+    sample_size: usize,
+) -> anyhow::Result<()> {
+    let result = caching::maintenance::verify(configuration, sample_size)?;
+    for path in &result.mismatches {
+        println!(
+            "Cache entry of {} does not match the file",
+            path.strip_prefix(&configuration.absolute_root)
+                .unwrap_or(path)
+                .display()
+        );
+    }
+    println!(
+        "Verified {} cache entries ({} sampled files were not cached)",
+        result.verified, result.not_cached
+    );
+    if !result.mismatches.is_empty() {
+        bail!(
+            "{} cache entries are out of date, run `{} delete-cache`",
+            result.mismatches.len(),
+            bin_locater::packs_bin_name()
+        )
+    }
+    Ok(())
+}
+
+pub fn cache_warm(configuration: &Configuration) -> anyhow::Result<()> {
+    if !configuration.cache_enabled {
+        bail!("The cache is disabled")
+    }
+    let (processed_files, run_stats) = process_files_with_cache_and_stats(
+        &configuration.included_files,
+        configuration.get_cache(),
+        configuration,
+    )?;
+    caching::maintenance::record_run(configuration, &run_stats);
+    println!("Cached {} files", processed_files.len());
+    Ok(())
+}
+
+pub fn cache_prune(
+    configuration: &Configuration,
+    max_size: Option<u64>,
+    max_age: Option<std::time::Duration>,
+) -> anyhow::Result<()> {
+    if max_size.is_none() && max_age.is_none() {
+        let removed = caching::maintenance::prune_excluded(configuration)?;
+        println!(
+            "Removed {} cache entries of files no longer included",
+            removed
+        );
+        return Ok(());
+    }
+
+    let summary = caching::content_addressed_cache::prune(
+        &configuration.cache_directory,
+        max_size,
+        max_age,
+    )?;
+    println!(
+        "Removed {} cache entries ({} bytes), {} entries ({} bytes) remain",
+        summary.removed_entries,
+        summary.removed_bytes,
+        summary.remaining_entries,
+        summary.remaining_bytes
+    );
+    Ok(())
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct ProcessedFile {
     pub absolute_path: PathBuf,
@@ -392,6 +571,12 @@ pub struct ProcessedFile {
 
     #[serde(default)] // Default to an empty Vec if not present
     pub sigils: Vec<Sigil>,
+
+    #[serde(default)]
+    pub parse_errors: Vec<ParseDiagnostic>,
+
+    #[serde(default)]
+    pub suppressions: Vec<Suppression>,
 }
 
 // A sigil is a way to specify some packs specific behavior at the top of a file, like
@@ -403,6 +588,18 @@ pub struct Sigil {
     pub value: bool,
 }
 
+// An inline suppression comment, like
+// `# packs:disable-next-line dependency,privacy -- reason: legacy billing shim`.
+// It suppresses violations of the listed types on the line after the comment,
+// as long as it gives a reason.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    // The line of the comment, 1-based
+    pub line: usize,
+    pub violation_types: Vec<String>,
+    pub reason: Option<String>,
+}
+
 #[derive(
     Debug, PartialEq, Serialize, Deserialize, Default, Eq, Clone, Hash,
 )]
@@ -411,31 +608,40 @@ pub struct SourceLocation {
     pub column: usize,
 }
 
-pub(crate) fn list_definitions(
+// Builds the constant resolver for the configured parser.
+// The experimental parser needs to process every included file to find definitions,
+// while the zeitwerk resolver infers definitions from the file system.
+pub fn constant_resolver(
     configuration: &Configuration,
-    ambiguous: bool,
-) -> anyhow::Result<()> {
-    let constant_resolver = if configuration.experimental_parser {
+) -> anyhow::Result<Box<dyn ConstantResolver + Send + Sync>> {
+    if configuration.experimental_parser {
         let processed_files: Vec<ProcessedFile> = process_files_with_cache(
             &configuration.included_files,
             configuration.get_cache(),
             configuration,
         )?;
 
-        get_experimental_constant_resolver(
+        Ok(get_experimental_constant_resolver(
             &configuration.absolute_root,
             &processed_files,
             &configuration.ignored_definitions,
-        )
+        ))
     } else {
-        if ambiguous {
-            bail!("Ambiguous mode is not supported for the Zeitwerk parser");
-        }
-        get_zeitwerk_constant_resolver(
+        Ok(get_zeitwerk_constant_resolver(
             &configuration.pack_set,
             &configuration.constant_resolver_configuration(),
-        )
-    };
+        ))
+    }
+}
+
+pub(crate) fn list_definitions(
+    configuration: &Configuration,
+    ambiguous: bool,
+) -> anyhow::Result<()> {
+    if ambiguous && !configuration.experimental_parser {
+        bail!("Ambiguous mode is not supported for the Zeitwerk parser");
+    }
+    let constant_resolver = constant_resolver(configuration)?;
 
     let constant_definition_map = constant_resolver
         .fully_qualified_constant_name_to_constant_definition_map();
@@ -556,6 +762,26 @@ pub(crate) fn list_references(
     Ok(())
 }
 
+pub(crate) fn list_suppressions(
+    configuration: &Configuration,
+    format: &str,
+) -> anyhow::Result<()> {
+    let suppressions = checker::list_suppressions(configuration)?;
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&suppressions)?),
+        "text" => {
+            if suppressions.is_empty() {
+                println!("No suppressions found");
+            }
+            for suppression in &suppressions {
+                println!("{}\n", suppression);
+            }
+        }
+        _ => bail!("Unsupported format: {}. Use 'json' or 'text'", format),
+    }
+    Ok(())
+}
+
 fn expose_monkey_patches(
     configuration: &Configuration,
     rubydir: &PathBuf,
@@ -604,6 +830,25 @@ fn list_dependencies(
     Ok(())
 }
 
+fn graph(
+    configuration: &Configuration,
+    options: &graph::GraphOptions,
+) -> anyhow::Result<()> {
+    println!("{}", graph::render(configuration, options)?);
+    Ok(())
+}
+
+fn metrics(
+    configuration: &Configuration,
+    format: metrics::MetricsFormat,
+) -> anyhow::Result<()> {
+    let constant_resolver = constant_resolver(configuration)?;
+    let pack_metrics =
+        metrics::compute(configuration, constant_resolver.as_ref())?;
+    println!("{}", metrics::render(&pack_metrics, format)?);
+    Ok(())
+}
+
 fn move_to_pack(
     configuration: &Configuration,
     destination: &str,
@@ -820,6 +1065,17 @@ fn for_file(configuration: &Configuration, file: String) -> anyhow::Result<()> {
     }
 }
 
+fn lsp(configuration: Configuration) -> anyhow::Result<()> {
+    lsp::run(configuration)
+}
+
+fn server(
+    configuration: Configuration,
+    socket: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    server::serve(configuration, socket)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -12,4 +12,10 @@ pub trait Cache {
         empty_cache_entry: &EmptyCacheEntry,
         processed_file: &ProcessedFile,
     ) -> anyhow::Result<()>;
+
+    /// Persists writes that were buffered in memory, called once all files
+    /// have been processed
+    fn flush(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,518 @@
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use tracing::warn;
+
+use crate::packs::file_utils::{file_content_digest, get_file_type};
+use crate::packs::parsing::ruby::experimental::parser::is_spec_file;
+use crate::packs::parsing::ruby::RubyParser;
+use crate::packs::parsing::StringReferenceCalls;
+use crate::packs::ProcessedFile;
+
+use super::cache::Cache;
+use super::per_file_cache::{read_json_file, CacheEntry, ENTRY_VERSION};
+use super::{CacheResult, EmptyCacheEntry};
+
+const CONTENT_DIRECTORY_NAME: &str = "content";
+// Part of every key, bump it whenever the parsers or `ProcessedFile` change in
+// a way that makes existing entries wrong
+const KEY_VERSION: u32 = 3;
+
+static TMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Keys entries by what determines the result of parsing a file (the parser,
+/// the configuration the parsers read, the file type, whether it is a spec
+/// file and the file contents) rather than by its path. Moved or renamed files still hit, and several checkouts can
+/// share one cache directory, e.g. a mounted volume on CI runners.
+///
+/// Entries are never invalidated, only garbage collected by `prune`.
+pub struct ContentAddressedCache {
+    pub cache_dir: PathBuf,
+    /// Digested together with the file contents into the key
+    pub parse_settings: String,
+}
+
+impl ContentAddressedCache {
+    pub fn new(
+        cache_directory: &Path,
+        experimental_parser: bool,
+        ruby_parser: RubyParser,
+        custom_associations: &[String],
+        string_reference_calls: &StringReferenceCalls,
+        inflections_digest: Option<&str>,
+    ) -> ContentAddressedCache {
+        let mode = if experimental_parser {
+            "experimental"
+        } else {
+            "zeitwerk"
+        };
+        let parser = format!("{:?}-{}", ruby_parser, mode);
+        let mut custom_associations = custom_associations.to_vec();
+        custom_associations.sort();
+
+        ContentAddressedCache {
+            cache_dir: content_directory(cache_directory),
+            parse_settings: format!(
+                "{}\0{}\0{}\0{}\0{}\0{}",
+                KEY_VERSION,
+                parser,
+                custom_associations.join(","),
+                string_reference_calls.methods.join(","),
+                string_reference_calls.keywords.join(","),
+                inflections_digest.unwrap_or_default()
+            ),
+        }
+    }
+
+    fn entry_path(&self, path: &Path, file_contents_digest: &str) -> PathBuf {
+        // The same contents parse differently as Ruby or ERB
+        let file_type = get_file_type(path)
+            .map_or("none".to_owned(), |file_type| format!("{:?}", file_type));
+        // ... and the experimental parser treats spec files differently
+        let is_spec_file = is_spec_file(path);
+        let key = format!(
+            "{:x}",
+            md5::compute(format!(
+                "{}\0{}\0{}\0{}",
+                self.parse_settings,
+                file_type,
+                is_spec_file,
+                file_contents_digest
+            ))
+        );
+        // Shard by prefix to keep directories small
+        self.cache_dir.join(&key[..2]).join(format!("{}.json", key))
+    }
+}
+
+impl Cache for ContentAddressedCache {
+    fn get(&self, path: &Path) -> anyhow::Result<CacheResult> {
+        let file_contents_digest = file_content_digest(path)?;
+        let cache_file_path = self.entry_path(path, &file_contents_digest);
+
+        if cache_file_path.exists() {
+            match read_json_file(&cache_file_path) {
+                Ok(cache_entry) => {
+                    // Entries are shared between paths, so the stored path
+                    // may be another copy of the same file
+                    let mut processed_file = cache_entry.processed_file;
+                    processed_file.absolute_path = path.to_path_buf();
+                    touch(&cache_file_path);
+                    return Ok(CacheResult::Processed(processed_file));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to read cache file {:?}: {}",
+                        cache_file_path, e
+                    );
+                }
+            }
+        }
+
+        Ok(CacheResult::Miss(EmptyCacheEntry {
+            file_contents_digest,
+            cache_file_path,
+        }))
+    }
+
+    fn write(
+        &self,
+        empty_cache_entry: &EmptyCacheEntry,
+        processed_file: &ProcessedFile,
+    ) -> anyhow::Result<()> {
+        let cache_entry = CacheEntry {
+            version: ENTRY_VERSION,
+            file_contents_digest: empty_cache_entry
+                .file_contents_digest
+                .to_owned(),
+            processed_file: processed_file.clone(),
+        };
+        // The directory may be shared and read-only (e.g. a restored CI
+        // artifact), failing to add an entry only costs a re-parse next time
+        if let Err(e) =
+            write_atomically(&empty_cache_entry.cache_file_path, &cache_entry)
+        {
+            warn!(
+                "Failed to write cache file {:?}: {}",
+                empty_cache_entry.cache_file_path, e
+            );
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn content_directory(cache_directory: &Path) -> PathBuf {
+    cache_directory.join(CONTENT_DIRECTORY_NAME)
+}
+
+// Concurrent runs may write the same entry, so each writes its own temporary
+// file and renames it into place
+fn write_atomically(
+    cache_file_path: &Path,
+    cache_entry: &CacheEntry,
+) -> anyhow::Result<()> {
+    let directory = cache_file_path.parent().unwrap();
+    std::fs::create_dir_all(directory)
+        .context(format!("Failed to create {:?}", directory))?;
+    let tmp_path = cache_file_path.with_extension(format!(
+        "json.{}.{}.tmp",
+        std::process::id(),
+        TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = File::create(&tmp_path)
+        .context(format!("Failed to create {:?}", tmp_path))?;
+    file.write_all(serde_json::to_string(cache_entry)?.as_bytes())
+        .context("Failed to write cache file")?;
+    drop(file);
+    std::fs::rename(&tmp_path, cache_file_path).context(format!(
+        "Failed to rename {:?} to {:?}",
+        tmp_path, cache_file_path
+    ))
+}
+
+// The modification time of an entry records when it was last used, which is
+// what `prune` goes by
+fn touch(cache_file_path: &Path) {
+    let _ = File::options()
+        .append(true)
+        .open(cache_file_path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub removed_entries: usize,
+    pub removed_bytes: u64,
+    pub remaining_entries: usize,
+    pub remaining_bytes: u64,
+}
+
+/// Removes entries that were not used for longer than `max_age`, then the
+/// least recently used entries until the cache takes at most `max_size` bytes
+pub(crate) fn prune(
+    cache_directory: &Path,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+) -> anyhow::Result<PruneSummary> {
+    let mut entries: Vec<(PathBuf, SystemTime, u64)> = vec![];
+    let content_directory = content_directory(cache_directory);
+    if content_directory.exists() {
+        for shard in std::fs::read_dir(&content_directory)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&shard)? {
+                let entry = entry?;
+                // Another process may be pruning the cache concurrently
+                let metadata = match entry.metadata() {
+                    Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                    metadata => metadata?,
+                };
+                if metadata.is_file() {
+                    entries.push((
+                        entry.path(),
+                        metadata.modified()?,
+                        metadata.len(),
+                    ));
+                }
+            }
+        }
+    }
+    // Most recently used first
+    entries.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+    let now = SystemTime::now();
+    let mut summary = PruneSummary::default();
+    for (path, modified, size) in entries {
+        let too_old = max_age.is_some_and(|max_age| {
+            now.duration_since(modified).unwrap_or_default() > max_age
+        });
+        let too_large = max_size
+            .is_some_and(|max_size| summary.remaining_bytes + size > max_size);
+        if too_old || too_large {
+            if remove_entry(&path)? {
+                summary.removed_entries += 1;
+                summary.removed_bytes += size;
+            }
+        } else {
+            summary.remaining_entries += 1;
+            summary.remaining_bytes += size;
+        }
+    }
+    Ok(summary)
+}
+
+// Returns false if the entry was already removed, e.g. by a concurrent prune
+fn remove_entry(path: &Path) -> anyhow::Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e).context(format!("Failed to remove {:?}", path)),
+    }
+}
+
+/// Parses sizes like `1024`, `500K`, `200M` or `2G` (powers of 1024)
+pub(crate) fn parse_size(size: &str) -> Result<u64, String> {
+    let invalid =
+        || format!("invalid size `{}`, expected e.g. `500M` or `2G`", size);
+    let number = size.trim().trim_end_matches(['B', 'b']);
+    let (number, multiplier) =
+        match number.chars().last().map(|unit| unit.to_ascii_uppercase()) {
+            Some('K') => (&number[..number.len() - 1], 1 << 10),
+            Some('M') => (&number[..number.len() - 1], 1 << 20),
+            Some('G') => (&number[..number.len() - 1], 1 << 30),
+            Some('T') => (&number[..number.len() - 1], 1 << 40),
+            _ => (number, 1),
+        };
+    number
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| invalid())?
+        .checked_mul(multiplier)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn processed_file(absolute_path: &Path) -> ProcessedFile {
+        ProcessedFile {
+            absolute_path: absolute_path.to_path_buf(),
+            unresolved_references: vec![],
+            definitions: vec![],
+            sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
+        }
+    }
+
+    fn fill(cache: &ContentAddressedCache, path: &Path) {
+        let CacheResult::Miss(empty_cache_entry) = cache.get(path).unwrap()
+        else {
+            panic!("expected a cache miss for {:?}", path);
+        };
+        cache
+            .write(&empty_cache_entry, &processed_file(path))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hits_for_moved_files() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache = ContentAddressedCache::new(
+            tmp_dir.path(),
+            false,
+            RubyParser::LibRubyParser,
+            &[],
+            &StringReferenceCalls::default(),
+            None,
+        );
+        let original = tmp_dir.path().join("foo.rb");
+        let moved = tmp_dir.path().join("bar.rb");
+        std::fs::write(&original, "module Foo\nend\n")?;
+        std::fs::write(&moved, "module Foo\nend\n")?;
+
+        fill(&cache, &original);
+        match cache.get(&moved)? {
+            CacheResult::Processed(cached) => {
+                assert_eq!(cached, processed_file(&moved))
+            }
+            CacheResult::Miss(_) => panic!("expected a cache hit"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_misses_for_other_parse_settings() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let source = tmp_dir.path().join("foo.rb");
+        std::fs::write(&source, "module Foo\nend\n")?;
+        fill(
+            &ContentAddressedCache::new(
+                tmp_dir.path(),
+                false,
+                RubyParser::LibRubyParser,
+                &[],
+                &StringReferenceCalls::default(),
+                None,
+            ),
+            &source,
+        );
+
+        for cache in [
+            ContentAddressedCache::new(
+                tmp_dir.path(),
+                true,
+                RubyParser::LibRubyParser,
+                &[],
+                &StringReferenceCalls::default(),
+                None,
+            ),
+            ContentAddressedCache::new(
+                tmp_dir.path(),
+                false,
+                RubyParser::Prism,
+                &[],
+                &StringReferenceCalls::default(),
+                None,
+            ),
+            ContentAddressedCache::new(
+                tmp_dir.path(),
+                false,
+                RubyParser::LibRubyParser,
+                &["has_one_thing".to_owned()],
+                &StringReferenceCalls::default(),
+                None,
+            ),
+            ContentAddressedCache::new(
+                tmp_dir.path(),
+                false,
+                RubyParser::LibRubyParser,
+                &[],
+                &StringReferenceCalls {
+                    methods: vec!["constantize".to_owned()],
+                    keywords: vec![],
+                },
+                None,
+            ),
+            ContentAddressedCache::new(
+                tmp_dir.path(),
+                false,
+                RubyParser::LibRubyParser,
+                &[],
+                &StringReferenceCalls::default(),
+                Some("inflections"),
+            ),
+        ] {
+            assert!(matches!(cache.get(&source)?, CacheResult::Miss(_)));
+        }
+
+        let erb_source = tmp_dir.path().join("foo.erb");
+        std::fs::write(&erb_source, "module Foo\nend\n")?;
+        let cache = ContentAddressedCache::new(
+            tmp_dir.path(),
+            false,
+            RubyParser::LibRubyParser,
+            &[],
+            &StringReferenceCalls::default(),
+            None,
+        );
+        assert!(matches!(cache.get(&erb_source)?, CacheResult::Miss(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_misses_for_spec_files() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache = ContentAddressedCache::new(
+            tmp_dir.path(),
+            true,
+            RubyParser::LibRubyParser,
+            &[],
+            &StringReferenceCalls::default(),
+            None,
+        );
+        let source = tmp_dir.path().join("app/foo.rb");
+        let spec = tmp_dir.path().join("spec/foo.rb");
+        for path in [&source, &spec] {
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(
+                path,
+                "class Foo
+  def foo; end
+end
+",
+            )?;
+        }
+
+        fill(&cache, &source);
+        assert!(matches!(cache.get(&spec)?, CacheResult::Miss(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache = ContentAddressedCache::new(
+            tmp_dir.path(),
+            false,
+            RubyParser::LibRubyParser,
+            &[],
+            &StringReferenceCalls::default(),
+            None,
+        );
+        let mut entry_paths = vec![];
+        for i in 0..3 {
+            let source = tmp_dir.path().join(format!("file_{}.rb", i));
+            std::fs::write(&source, format!("module File{}\nend\n", i))?;
+            fill(&cache, &source);
+            let CacheResult::Processed(_) = cache.get(&source)? else {
+                panic!("expected a cache hit");
+            };
+            let entry_path =
+                cache.entry_path(&source, &file_content_digest(&source)?);
+            // file_0 was used least recently
+            File::options()
+                .append(true)
+                .open(&entry_path)?
+                .set_modified(
+                    SystemTime::now() - Duration::from_secs(3600 * (3 - i)),
+                )?;
+            entry_paths.push(entry_path);
+        }
+        let entry_size = std::fs::metadata(&entry_paths[0])?.len();
+
+        let summary =
+            prune(tmp_dir.path(), None, Some(Duration::from_secs(4 * 3600)))?;
+        assert_eq!(summary.removed_entries, 0);
+
+        let summary = prune(
+            tmp_dir.path(),
+            None,
+            Some(Duration::from_secs(2 * 3600 + 60)),
+        )?;
+        assert_eq!(summary.removed_entries, 1);
+        assert!(!entry_paths[0].exists());
+
+        let summary = prune(tmp_dir.path(), Some(entry_size + 1), None)?;
+        assert_eq!(
+            summary,
+            PruneSummary {
+                removed_entries: 1,
+                removed_bytes: entry_size,
+                remaining_entries: 1,
+                remaining_bytes: entry_size,
+            }
+        );
+        assert!(!entry_paths[1].exists());
+        assert!(entry_paths[2].exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_entry_already_removed() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("entry");
+        std::fs::write(&path, "{}")?;
+        assert!(remove_entry(&path)?);
+        assert!(!remove_entry(&path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("500K"), Ok(500 * 1024));
+        assert_eq!(parse_size("2g"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("10MB"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("64B"), Ok(64));
+        assert!(parse_size("ten").is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use crate::packs::parsing::process_file;
+use crate::packs::Configuration;
+
+use super::content_addressed_cache::content_directory;
+use super::packed_cache::{self, LOCK_FILE_NAME, STORE_FILE_NAME};
+use super::{cache_file_name, CacheBackend, CacheResult};
+
+const LAST_RUN_FILE_NAME: &str = "last_run.json";
+const PARSER_DIRECTORY_NAMES: [&str; 2] = ["zeitwerk", "experimental"];
+
+/// Cache hits and misses of one full `check`, `update` or `cache warm` run
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DirectoryStats {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub directories: Vec<DirectoryStats>,
+    pub last_run: Option<RunStats>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct VerifyResult {
+    pub verified: usize,
+    pub not_cached: usize,
+    /// Files whose cached entry differs from what parsing them returns now
+    pub mismatches: Vec<PathBuf>,
+}
+
+// Stats are informational, so failing to record them is not an error.
+// Only runs over all included files are recorded, the hit rate of runs over
+// a few files says little about the cache.
+pub(crate) fn record_run(configuration: &Configuration, run_stats: &RunStats) {
+    if !configuration.cache_enabled {
+        return;
+    }
+    let cache_directory = &configuration.cache_directory;
+    let _ = std::fs::create_dir_all(cache_directory).and_then(|_| {
+        std::fs::write(
+            cache_directory.join(LAST_RUN_FILE_NAME),
+            serde_json::to_string(run_stats)?,
+        )
+    });
+}
+
+pub(crate) fn stats(configuration: &Configuration) -> CacheStats {
+    let mut directories: Vec<DirectoryStats> = PARSER_DIRECTORY_NAMES
+        .iter()
+        .map(|name| DirectoryStats {
+            name: name.to_string(),
+            ..directory_stats(&configuration.cache_directory.join(name))
+        })
+        .collect();
+    let content_directory = content_directory(&configuration.cache_directory);
+    if content_directory.exists() {
+        directories.push(DirectoryStats {
+            name: "content".to_owned(),
+            ..directory_stats(&content_directory)
+        });
+    }
+
+    let last_run = std::fs::read_to_string(
+        configuration.cache_directory.join(LAST_RUN_FILE_NAME),
+    )
+    .ok()
+    .and_then(|contents| serde_json::from_str(&contents).ok());
+
+    CacheStats {
+        directories,
+        last_run,
+    }
+}
+
+// Every file is an entry, except for the packed store which holds many
+fn directory_stats(directory: &Path) -> DirectoryStats {
+    let mut stats = DirectoryStats::default();
+    for entry in jwalk::WalkDir::new(directory)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type.is_file())
+    {
+        stats.bytes += entry.metadata().map_or(0, |metadata| metadata.len());
+        let file_name = entry.file_name().to_string_lossy();
+        if file_name == STORE_FILE_NAME {
+            stats.entries += packed_cache::entry_count(&entry.path());
+        } else if file_name != LOCK_FILE_NAME && !file_name.ends_with(".tmp") {
+            stats.entries += 1;
+        }
+    }
+    stats
+}
+
+/// Parses up to `sample_size` included files, spread evenly over the sorted
+/// file list, and compares the result with their cache entries
+pub(crate) fn verify(
+    configuration: &Configuration,
+    sample_size: usize,
+) -> anyhow::Result<VerifyResult> {
+    let mut files: Vec<&PathBuf> =
+        configuration.included_files.iter().collect();
+    files.sort();
+    let step = files.len().div_ceil(sample_size.max(1)).max(1);
+
+    let cache = configuration.get_cache();
+    let mut result = VerifyResult::default();
+    for path in files.into_iter().step_by(step) {
+        match cache.get(path)? {
+            CacheResult::Processed(cached) => {
+                result.verified += 1;
+                if cached != process_file(path, configuration)? {
+                    result.mismatches.push(path.clone());
+                }
+            }
+            CacheResult::Miss(_) => result.not_cached += 1,
+        }
+    }
+    Ok(result)
+}
+
+/// Removes the entries of files that are no longer included, returning how
+/// many were removed
+pub(crate) fn prune_excluded(
+    configuration: &Configuration,
+) -> anyhow::Result<usize> {
+    if configuration.cache_backend == CacheBackend::ContentAddressed {
+        bail!(
+            "Entries of the content-addressed cache are shared between files and checkouts, use `--max-size` or `--max-age` to prune it"
+        )
+    }
+    let cache_dir = configuration.cache_dir();
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+
+    match configuration.cache_backend {
+        CacheBackend::ContentAddressed => unreachable!(),
+        CacheBackend::Packed => packed_cache::retain(&cache_dir, |path| {
+            configuration.included_files.contains(path)
+        }),
+        CacheBackend::PerFile => {
+            let expected: HashSet<String> = configuration
+                .included_files
+                .iter()
+                .map(|path| cache_file_name(path))
+                .collect();
+            let mut removed = 0;
+            for entry in std::fs::read_dir(&cache_dir)? {
+                let entry = entry?;
+                let file_name = entry.file_name();
+                let file_name = file_name.to_string_lossy();
+                // Leave anything that isn't an entry alone, e.g. files
+                // someone put next to the entries
+                if !entry.file_type()?.is_file()
+                    || !is_cache_file_name(&file_name)
+                {
+                    continue;
+                }
+                if !expected.contains(file_name.as_ref()) {
+                    std::fs::remove_file(entry.path())?;
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        }
+    }
+}
+
+// Whether this could be a name returned by `cache_file_name`
+fn is_cache_file_name(file_name: &str) -> bool {
+    file_name.len() == 32
+        && file_name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::caching::EmptyCacheEntry;
+    use crate::packs::{configuration, ProcessedFile};
+    use tempfile::TempDir;
+
+    fn app(backend: CacheBackend) -> (TempDir, Configuration) {
+        let tmp_dir = TempDir::new().unwrap();
+        let root = tmp_dir.path();
+        std::fs::write(root.join("packwerk.yml"), "").unwrap();
+        std::fs::write(root.join("package.yml"), "").unwrap();
+        std::fs::write(root.join("foo.rb"), "module Foo\n  Bar\nend\n")
+            .unwrap();
+        std::fs::write(root.join("bar.rb"), "module Bar\nend\n").unwrap();
+        let mut configuration =
+            configuration::get(&root.canonicalize().unwrap(), &0).unwrap();
+        configuration.cache_backend = backend;
+        (tmp_dir, configuration)
+    }
+
+    fn warm(configuration: &Configuration) {
+        crate::packs::cache_warm(configuration).unwrap();
+    }
+
+    #[test]
+    fn test_stats() {
+        let (_tmp_dir, configuration) = app(CacheBackend::Packed);
+        warm(&configuration);
+        warm(&configuration);
+
+        let stats = stats(&configuration);
+        assert_eq!(stats.directories[0].name, "zeitwerk");
+        assert_eq!(stats.directories[0].entries, 2);
+        assert!(stats.directories[0].bytes > 0);
+        assert_eq!(stats.directories[1].entries, 0);
+        assert_eq!(stats.last_run, Some(RunStats { hits: 2, misses: 0 }));
+    }
+
+    #[test]
+    fn test_verify() -> anyhow::Result<()> {
+        let (_tmp_dir, configuration) = app(CacheBackend::PerFile);
+        assert_eq!(
+            verify(&configuration, 100)?,
+            VerifyResult {
+                verified: 0,
+                not_cached: 2,
+                mismatches: vec![],
+            }
+        );
+
+        warm(&configuration);
+        let foo = configuration.absolute_root.join("foo.rb");
+        // Overwrite the entry of foo.rb with a wrong one
+        let empty_cache_entry =
+            EmptyCacheEntry::new(&configuration.cache_dir(), &foo)?;
+        configuration.get_cache().write(
+            &empty_cache_entry,
+            &ProcessedFile {
+                absolute_path: foo.clone(),
+                unresolved_references: vec![],
+                definitions: vec![],
+                sigils: vec![],
+                suppressions: vec![],
+                parse_errors: vec![],
+            },
+        )?;
+
+        assert_eq!(
+            verify(&configuration, 100)?,
+            VerifyResult {
+                verified: 2,
+                not_cached: 0,
+                mismatches: vec![foo],
+            }
+        );
+        assert_eq!(verify(&configuration, 1)?.verified, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_excluded() -> anyhow::Result<()> {
+        for backend in [CacheBackend::PerFile, CacheBackend::Packed] {
+            let (_tmp_dir, mut configuration) = app(backend);
+            warm(&configuration);
+
+            assert_eq!(prune_excluded(&configuration)?, 0);
+            configuration
+                .included_files
+                .remove(&configuration.absolute_root.join("bar.rb"));
+            assert_eq!(prune_excluded(&configuration)?, 1);
+            assert_eq!(stats(&configuration).directories[0].entries, 1);
+        }
+
+        let (_tmp_dir, configuration) = app(CacheBackend::ContentAddressed);
+        warm(&configuration);
+        assert!(prune_excluded(&configuration).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_excluded_keeps_other_files() -> anyhow::Result<()> {
+        let (_tmp_dir, configuration) = app(CacheBackend::PerFile);
+        warm(&configuration);
+        let cache_dir = configuration.cache_dir();
+        std::fs::write(cache_dir.join("README"), "")?;
+        std::fs::create_dir(
+            cache_dir.join("0123456789abcdef0123456789abcdef"),
+        )?;
+
+        assert_eq!(prune_excluded(&configuration)?, 0);
+        assert!(cache_dir.join("README").exists());
+        assert!(cache_dir.join("0123456789abcdef0123456789abcdef").is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
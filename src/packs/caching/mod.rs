@@ -1,10 +1,29 @@
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 use super::{file_utils::file_content_digest, ProcessedFile};
 pub(crate) mod cache;
+pub(crate) mod content_addressed_cache;
+pub(crate) mod maintenance;
 pub(crate) mod noop_cache;
+pub(crate) mod packed_cache;
 pub(crate) mod per_file_cache;
 
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackend {
+    /// One JSON file per source file
+    #[default]
+    PerFile,
+    /// All entries in a single binary file, see `PackedCache`
+    Packed,
+    /// Entries keyed by file contents rather than path, see
+    /// `ContentAddressedCache`
+    ContentAddressed,
+}
+
 pub enum CacheResult {
     Processed(ProcessedFile),
     Miss(EmptyCacheEntry),
@@ -21,9 +40,7 @@ impl EmptyCacheEntry {
         cache_directory: &Path,
         filepath: &Path,
     ) -> anyhow::Result<EmptyCacheEntry> {
-        let file_digest = md5::compute(filepath.to_str().unwrap());
-        let file_name_digest = format!("{:x}", file_digest);
-        let cache_file_path = cache_directory.join(file_name_digest);
+        let cache_file_path = cache_directory.join(cache_file_name(filepath));
 
         let file_contents_digest = file_content_digest(filepath)?;
 
@@ -34,6 +51,11 @@ impl EmptyCacheEntry {
     }
 }
 
+// The per file cache names entries after a digest of the file path
+pub(crate) fn cache_file_name(filepath: &Path) -> String {
+    format!("{:x}", md5::compute(filepath.to_str().unwrap()))
+}
+
 pub fn create_cache_dir_idempotently(cache_dir: &Path) {
     std::fs::create_dir_all(cache_dir)
         .expect("Failed to create cache directory");
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::packs::file_utils::file_content_digest;
+use crate::packs::ProcessedFile;
+
+use super::cache::Cache;
+use super::{CacheResult, EmptyCacheEntry};
+
+pub(crate) const STORE_FILE_NAME: &str = "packed_cache.bin";
+pub(crate) const LOCK_FILE_NAME: &str = "packed_cache.lock";
+// Bump this whenever `PackedEntry` or `ProcessedFile` change shape, so old
+// stores are discarded instead of misread.
+const STORE_VERSION: u32 = 4;
+
+/// Keeps every cache entry in a single binary file under the cache directory.
+/// The store is read once when the cache is built and written back once by
+/// `flush`, so a warm run opens one file rather than one per source file.
+/// Entries whose mtime and size still match are trusted without hashing the
+/// file; otherwise the content digest decides.
+pub struct PackedCache {
+    cache_dir: PathBuf,
+    entries: HashMap<PathBuf, PackedEntry>,
+    updated_entries: Mutex<HashMap<PathBuf, PackedEntry>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+struct PackedEntry {
+    modified_nanos: Option<u128>,
+    size: u64,
+    file_contents_digest: String,
+    processed_file: ProcessedFile,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PackedStore {
+    version: u32,
+    entries: HashMap<PathBuf, PackedEntry>,
+}
+
+impl PackedCache {
+    pub fn new(cache_dir: PathBuf) -> PackedCache {
+        let entries =
+            with_lock(&cache_dir, |store_path| Ok(read_store(store_path)))
+                .unwrap_or_else(|e| {
+                    warn!("Failed to read the packed cache: {}", e);
+                    HashMap::new()
+                });
+
+        PackedCache {
+            cache_dir,
+            entries,
+            updated_entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn record(&self, path: &Path, entry: PackedEntry) {
+        self.updated_entries
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), entry);
+    }
+}
+
+impl Cache for PackedCache {
+    fn get(&self, path: &Path) -> anyhow::Result<CacheResult> {
+        let (modified_nanos, size) = file_stamp(path)?;
+        let cached_entry = self.entries.get(path);
+
+        if let Some(entry) = cached_entry {
+            if modified_nanos.is_some()
+                && entry.modified_nanos == modified_nanos
+                && entry.size == size
+            {
+                return Ok(CacheResult::Processed(
+                    entry.processed_file.clone(),
+                ));
+            }
+        }
+
+        let file_contents_digest = file_content_digest(path)?;
+        if let Some(entry) = cached_entry {
+            if entry.file_contents_digest == file_contents_digest {
+                // e.g. a fresh checkout, remember the new mtime for next time
+                self.record(
+                    path,
+                    PackedEntry {
+                        modified_nanos,
+                        size,
+                        ..entry.clone()
+                    },
+                );
+                return Ok(CacheResult::Processed(
+                    entry.processed_file.clone(),
+                ));
+            }
+        }
+
+        Ok(CacheResult::Miss(EmptyCacheEntry {
+            file_contents_digest,
+            cache_file_path: self.cache_dir.join(STORE_FILE_NAME),
+        }))
+    }
+
+    fn write(
+        &self,
+        empty_cache_entry: &EmptyCacheEntry,
+        processed_file: &ProcessedFile,
+    ) -> anyhow::Result<()> {
+        let (modified_nanos, size) = file_stamp(&processed_file.absolute_path)?;
+        self.record(
+            &processed_file.absolute_path,
+            PackedEntry {
+                modified_nanos,
+                size,
+                file_contents_digest: empty_cache_entry
+                    .file_contents_digest
+                    .clone(),
+                processed_file: processed_file.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    fn flush(&self) -> anyhow::Result<()> {
+        let updated_entries =
+            std::mem::take(&mut *self.updated_entries.lock().unwrap());
+        if updated_entries.is_empty() {
+            return Ok(());
+        }
+
+        with_lock(&self.cache_dir, |store_path| {
+            // Re-read the store so entries written by concurrent runs since
+            // this one started are kept
+            let mut entries = read_store(store_path);
+            entries.extend(updated_entries);
+
+            write_store(store_path, entries)
+        })
+    }
+}
+
+/// Removes the entries of files for which `keep` returns false, returning
+/// how many were removed
+pub(crate) fn retain(
+    cache_dir: &Path,
+    keep: impl Fn(&Path) -> bool,
+) -> anyhow::Result<usize> {
+    with_lock(cache_dir, |store_path| {
+        let mut entries = read_store(store_path);
+        let count = entries.len();
+        entries.retain(|path, _| keep(path));
+        let removed = count - entries.len();
+        if removed > 0 {
+            write_store(store_path, entries)?;
+        }
+        Ok(removed)
+    })
+}
+
+pub(crate) fn entry_count(store_path: &Path) -> usize {
+    read_store(store_path).len()
+}
+
+// Runs `f` with the path of the store while holding an exclusive lock on the
+// sibling lock file, which serializes concurrent `pks` runs
+fn with_lock<T>(
+    cache_dir: &Path,
+    f: impl FnOnce(&Path) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(cache_dir.join(LOCK_FILE_NAME))
+        .context("Failed to open the packed cache lock file")?;
+    lock_file
+        .lock_exclusive()
+        .context("Failed to lock the packed cache")?;
+    let result = f(&cache_dir.join(STORE_FILE_NAME));
+    // Unlocks automatically when `lock_file` goes out of scope
+    result
+}
+
+// Writes a temporary file and renames it over the store, so that readers
+// never see a partially written store. Callers must hold the lock.
+fn write_store(
+    store_path: &Path,
+    entries: HashMap<PathBuf, PackedEntry>,
+) -> anyhow::Result<()> {
+    let tmp_path =
+        store_path.with_extension(format!("bin.{}.tmp", std::process::id()));
+    let mut writer = BufWriter::new(
+        File::create(&tmp_path)
+            .context(format!("Failed to create {:?}", tmp_path))?,
+    );
+    bincode::serialize_into(
+        &mut writer,
+        &PackedStore {
+            version: STORE_VERSION,
+            entries,
+        },
+    )
+    .context("Failed to serialize the packed cache")?;
+    writer.flush().context("Failed to write the packed cache")?;
+    drop(writer);
+    std::fs::rename(&tmp_path, store_path)
+        .context(format!("Failed to replace {:?}", store_path))
+}
+
+fn read_store(store_path: &Path) -> HashMap<PathBuf, PackedEntry> {
+    let Ok(file) = File::open(store_path) else {
+        return HashMap::new();
+    };
+    match bincode::deserialize_from::<_, PackedStore>(BufReader::new(file)) {
+        Ok(store) if store.version == STORE_VERSION => store.entries,
+        Ok(_) => HashMap::new(),
+        Err(e) => {
+            warn!("Failed to read packed cache {:?}: {}", store_path, e);
+            HashMap::new()
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> anyhow::Result<(Option<u128>, u64)> {
+    let metadata = std::fs::metadata(path)
+        .context(format!("Failed to read metadata of {:?}", path))?;
+    let modified_nanos = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos());
+    Ok((modified_nanos, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::caching::create_cache_dir_idempotently;
+    use tempfile::TempDir;
+
+    fn processed_file(absolute_path: &Path) -> ProcessedFile {
+        ProcessedFile {
+            absolute_path: absolute_path.to_path_buf(),
+            unresolved_references: vec![],
+            definitions: vec![],
+            sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
+        }
+    }
+
+    fn get(cache: &PackedCache, path: &Path) -> Option<ProcessedFile> {
+        match cache.get(path).unwrap() {
+            CacheResult::Processed(processed_file) => Some(processed_file),
+            CacheResult::Miss(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache_dir = tmp_dir.path().join("cache");
+        create_cache_dir_idempotently(&cache_dir);
+        let source = tmp_dir.path().join("foo.rb");
+        std::fs::write(&source, "module Foo\nend\n")?;
+
+        let cache = PackedCache::new(cache_dir.clone());
+        let CacheResult::Miss(empty_cache_entry) = cache.get(&source)? else {
+            panic!("expected a cache miss");
+        };
+        cache.write(&empty_cache_entry, &processed_file(&source))?;
+        cache.flush()?;
+
+        let cache = PackedCache::new(cache_dir.clone());
+        assert_eq!(get(&cache, &source), Some(processed_file(&source)));
+
+        std::fs::write(&source, "module Bar\nend\n")?;
+        assert_eq!(get(&cache, &source), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_contents_with_new_mtime_is_a_hit() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache_dir = tmp_dir.path().join("cache");
+        create_cache_dir_idempotently(&cache_dir);
+        let source = tmp_dir.path().join("foo.rb");
+        std::fs::write(&source, "module Foo\nend\n")?;
+
+        let cache = PackedCache::new(cache_dir.clone());
+        let CacheResult::Miss(empty_cache_entry) = cache.get(&source)? else {
+            panic!("expected a cache miss");
+        };
+        cache.write(&empty_cache_entry, &processed_file(&source))?;
+        cache.flush()?;
+
+        let file = File::options().write(true).open(&source)?;
+        file.set_modified(UNIX_EPOCH)?;
+        let cache = PackedCache::new(cache_dir);
+        assert_eq!(get(&cache, &source), Some(processed_file(&source)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_store_is_ignored() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        std::fs::write(tmp_dir.path().join(STORE_FILE_NAME), "not bincode")?;
+        let source = tmp_dir.path().join("foo.rb");
+        std::fs::write(&source, "module Foo\nend\n")?;
+
+        let cache = PackedCache::new(tmp_dir.path().to_path_buf());
+        assert_eq!(get(&cache, &source), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_of_older_version_is_ignored() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let source = tmp_dir.path().join("foo.rb");
+        std::fs::write(&source, "module Foo\nend\n")?;
+        let (modified_nanos, size) = file_stamp(&source)?;
+        let entry = PackedEntry {
+            modified_nanos,
+            size,
+            file_contents_digest: file_content_digest(&source)?,
+            processed_file: processed_file(&source),
+        };
+        bincode::serialize_into(
+            File::create(tmp_dir.path().join(STORE_FILE_NAME))?,
+            &PackedStore {
+                version: STORE_VERSION - 1,
+                entries: HashMap::from([(source.clone(), entry)]),
+            },
+        )?;
+
+        let cache = PackedCache::new(tmp_dir.path().to_path_buf());
+        assert_eq!(get(&cache, &source), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_flushes_keep_all_entries() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let cache_dir = tmp_dir.path().join("cache");
+        create_cache_dir_idempotently(&cache_dir);
+        let sources: Vec<PathBuf> = (0..8)
+            .map(|i| {
+                let source = tmp_dir.path().join(format!("file_{}.rb", i));
+                std::fs::write(&source, format!("module File{}\nend\n", i))
+                    .unwrap();
+                source
+            })
+            .collect();
+
+        std::thread::scope(|scope| {
+            for source in &sources {
+                let cache_dir = cache_dir.clone();
+                scope.spawn(move || {
+                    let cache = PackedCache::new(cache_dir);
+                    let CacheResult::Miss(empty_cache_entry) =
+                        cache.get(source).unwrap()
+                    else {
+                        panic!("expected a cache miss");
+                    };
+                    cache
+                        .write(&empty_cache_entry, &processed_file(source))
+                        .unwrap();
+                    cache.flush().unwrap();
+                });
+            }
+        });
+
+        let cache = PackedCache::new(cache_dir);
+        for source in &sources {
+            assert_eq!(get(&cache, source), Some(processed_file(source)));
+        }
+        Ok(())
+    }
+}
@@ -12,6 +12,11 @@ use super::cache::Cache;
 use super::CacheResult;
 use super::EmptyCacheEntry;
 
+// Bump this whenever the parsers or `ProcessedFile` change in a way that makes
+// existing entries wrong, e.g. when a field is added with a default that would
+// hide what older entries are missing. Entries without a version are 0.
+pub(crate) const ENTRY_VERSION: u32 = 3;
+
 pub struct PerFileCache {
     pub cache_dir: PathBuf,
 }
@@ -25,7 +30,7 @@ impl Cache for PerFileCache {
             let file_digests_match = cache_entry.file_contents_digest
                 == empty_cache_entry.file_contents_digest;
 
-            if !file_digests_match {
+            if !file_digests_match || cache_entry.version != ENTRY_VERSION {
                 Ok(CacheResult::Miss(empty_cache_entry))
             } else {
                 let processed_file = cache_entry.processed_file;
@@ -45,6 +50,7 @@ impl Cache for PerFileCache {
             empty_cache_entry.file_contents_digest.to_owned();
 
         let cache_entry = &CacheEntry {
+            version: ENTRY_VERSION,
             file_contents_digest,
             // Ideally we could pass by reference here, but in practice this cost should be paid on few files
             // that have changed and need to be reprocessed.
@@ -69,6 +75,8 @@ impl Cache for PerFileCache {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CacheEntry {
+    #[serde(default)]
+    pub version: u32,
     pub file_contents_digest: String,
     pub processed_file: ProcessedFile,
 }
@@ -112,7 +120,7 @@ mod tests {
     use crate::packs::{
         self, configuration,
         file_utils::file_content_digest,
-        parsing::{Range, UnresolvedReference},
+        parsing::{Range, ReferenceKind, UnresolvedReference},
     };
 
     use super::*;
@@ -157,6 +165,7 @@ mod tests {
         );
 
         let expected_serialized = CacheEntry {
+            version: 0,
             file_contents_digest: "8f9efdcf2caa22fb7b1b4a8274e68d11".to_owned(),
             processed_file: ProcessedFile {
                 absolute_path: PathBuf::from("/tests/fixtures/simple_app/packs/foo/app/services/bar/foo.rb"),
@@ -168,10 +177,13 @@ mod tests {
                         start_col: 22,
                         end_row: 8,
                         end_col: 25,
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 }],
                 definitions: vec![],
                 sigils: vec![],
+                suppressions: vec![],
+                parse_errors: vec![],
             }
         };
 
@@ -212,4 +224,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_entries_of_older_versions_miss() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::TempDir::new()?;
+        let source = tmp_dir.path().join("foo.rb");
+        fs::write(&source, "module Foo\nend\n")?;
+        let cache = PerFileCache {
+            cache_dir: tmp_dir.path().join("cache"),
+        };
+        fs::create_dir_all(&cache.cache_dir)?;
+        let CacheResult::Miss(empty_cache_entry) = cache.get(&source)? else {
+            panic!("expected a miss for an uncached file");
+        };
+
+        // Written before entries had a version, e.g. without `parse_errors`
+        fs::write(
+            &empty_cache_entry.cache_file_path,
+            format!(
+                r#"{{"file_contents_digest":"{}","processed_file":{{"absolute_path":{:?},"unresolved_references":[],"definitions":[]}}}}"#,
+                empty_cache_entry.file_contents_digest, source
+            ),
+        )?;
+        assert!(matches!(cache.get(&source)?, CacheResult::Miss(_)));
+
+        let processed_file = ProcessedFile {
+            absolute_path: source.clone(),
+            unresolved_references: vec![],
+            definitions: vec![],
+            sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
+        };
+        cache.write(&empty_cache_entry, &processed_file)?;
+        assert!(matches!(
+            cache.get(&source)?,
+            CacheResult::Processed(cached) if cached == processed_file
+        ));
+        Ok(())
+    }
 }
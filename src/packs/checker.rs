@@ -1,13 +1,20 @@
 // Module declarations
 mod dependency;
+pub(crate) mod external;
 pub(crate) mod layer;
 
+mod checkstyle;
 mod common_test;
 mod folder_privacy;
+mod github;
+mod junit;
 mod output_helper;
 pub(crate) mod pack_checker;
 mod privacy;
 pub(crate) mod reference;
+pub(crate) mod rules;
+mod sarif;
+mod suppression;
 mod visibility;
 
 // Internal imports
@@ -23,7 +30,7 @@ use rayon::prelude::IntoParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
 use rayon::prelude::ParallelIterator;
 use reference::Reference;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
@@ -32,8 +39,24 @@ use std::{collections::HashSet, path::PathBuf};
 use tracing::debug;
 
 use super::bin_locater;
-use super::reference_extractor::get_all_references_and_sigils;
+use super::caching::maintenance::{record_run, RunStats};
+use super::constant_resolver::{ConstantDefinition, ConstantResolver};
+use super::file_utils::{get_file_type, SupportedFileType};
+use super::git;
+use super::parsing::process_from_ruby_contents;
+use super::parsing::ruby::zeitwerk::{
+    constant_resolver_from_constants, inferred_constants_from_files,
+};
+use super::process_files_with_cache;
+use super::reference_extractor::{
+    get_all_references_and_sigils, get_constant_resolver_and_processed_files,
+    get_constant_resolver_processed_files_and_stats,
+    get_references_and_sigils_from_processed_files,
+};
+use super::ProcessedFile;
 use super::Sigil;
+use suppression::Suppressions;
+pub use suppression::{SuppressionReport, SuppressionStatus};
 
 pub struct UpdateOptions {
     pub files: Vec<String>,
@@ -52,7 +75,7 @@ impl UpdateOptions {
     }
 }
 
-#[derive(PartialEq, Clone, Eq, Hash, Debug, Serialize)]
+#[derive(PartialEq, Clone, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct ViolationIdentifier {
     pub violation_type: String,
     pub strict: bool,
@@ -68,12 +91,35 @@ pub struct Violation {
     pub source_location: crate::packs::SourceLocation,
 }
 
+/// A syntax error in a checked file. The file's references, and so its
+/// violations, may be incomplete.
+#[derive(PartialEq, Clone, Eq, Hash, Debug, Serialize)]
+pub struct FileParseError {
+    pub file: String,
+    pub message: String,
+    pub source_location: crate::packs::SourceLocation,
+}
+
+impl Display for FileParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\x1b[36m{}\x1b[0m:{}:{}\nParse error: {}",
+            self.file,
+            self.source_location.line,
+            self.source_location.column,
+            self.message
+        )
+    }
+}
+
 pub(crate) trait CheckerInterface {
     fn check(
         &self,
         reference: &Reference,
         configuration: &Configuration,
         sigils: &HashMap<PathBuf, Vec<Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>>;
 
     fn violation_type(&self) -> String;
@@ -83,11 +129,16 @@ pub(crate) trait ValidatorInterface {
     fn validate(&self, configuration: &Configuration) -> Option<Vec<String>>;
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct CheckAllResult {
     reportable_violations: HashSet<Violation>,
     stale_violations: Vec<ViolationIdentifier>,
     strict_mode_violations: Vec<ViolationIdentifier>,
+    parse_errors: Vec<FileParseError>,
+    // Whether parse errors count as violations, see `fail_on_parse_errors`
+    fail_on_parse_errors: bool,
+    // Stale suppressions and suppressions without a reason
+    suppression_errors: Vec<SuppressionReport>,
 }
 
 impl CheckAllResult {
@@ -95,20 +146,72 @@ impl CheckAllResult {
         !self.reportable_violations.is_empty()
             || !self.stale_violations.is_empty()
             || !self.strict_mode_violations.is_empty()
+            || self.failing_parse_error_count() > 0
+            || !self.suppression_errors.is_empty()
+    }
+
+    pub fn reportable_violations(&self) -> &HashSet<Violation> {
+        &self.reportable_violations
+    }
+
+    pub fn stale_violations(&self) -> &Vec<ViolationIdentifier> {
+        &self.stale_violations
+    }
+
+    pub fn strict_mode_violations(&self) -> &Vec<ViolationIdentifier> {
+        &self.strict_mode_violations
+    }
+
+    pub fn parse_errors(&self) -> &Vec<FileParseError> {
+        &self.parse_errors
+    }
+
+    pub fn suppression_errors(&self) -> &Vec<SuppressionReport> {
+        &self.suppression_errors
     }
 
     pub fn violation_count(&self) -> usize {
         self.reportable_violations.len()
             + self.stale_violations.len()
             + self.strict_mode_violations.len()
+            + self.failing_parse_error_count()
+            + self.suppression_errors.len()
+    }
+
+    fn failing_parse_error_count(&self) -> usize {
+        if self.fail_on_parse_errors {
+            self.parse_errors.len()
+        } else {
+            0
+        }
+    }
+
+    pub fn to_github_annotations(&self) -> String {
+        github::check_result_to_github(self)
+    }
+
+    pub fn to_junit(&self) -> String {
+        junit::check_result_to_junit(self)
+    }
+
+    pub fn to_checkstyle(&self) -> String {
+        checkstyle::check_result_to_checkstyle(self)
+    }
+
+    pub fn to_sarif(&self) -> serde_json::Result<String> {
+        sarif::check_result_to_sarif(self)
     }
 
     pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    fn json_output(&self) -> CheckAllJsonOutput<'_> {
         let mut sorted_violations: Vec<&Violation> =
             self.reportable_violations.iter().collect();
         sorted_violations.sort_by(|a, b| a.message.cmp(&b.message));
 
-        let output = CheckAllJsonOutput {
+        CheckAllJsonOutput {
             status: if self.has_violations() {
                 "failure"
             } else {
@@ -120,8 +223,27 @@ impl CheckAllResult {
                 .collect(),
             stale_violations: &self.stale_violations,
             strict_mode_violations: &self.strict_mode_violations,
-        };
-        serde_json::to_string(&output)
+            parse_errors: self
+                .parse_errors
+                .iter()
+                .map(JsonParseError::from)
+                .collect(),
+            suppression_errors: &self.suppression_errors,
+        }
+    }
+
+    fn write_parse_errors(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.parse_errors.is_empty() {
+            writeln!(
+                f,
+                "{} parse error(s) detected, violations in these files may be missing:",
+                self.parse_errors.len()
+            )?;
+            for parse_error in &self.parse_errors {
+                writeln!(f, "{}\n", parse_error)?;
+            }
+        }
+        Ok(())
     }
 
     fn write_violations(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -151,12 +273,24 @@ impl CheckAllResult {
                 writeln!(f, "{}", error_message)?;
             }
         }
+
+        if !self.suppression_errors.is_empty() {
+            writeln!(
+                f,
+                "{} suppression error(s) detected:",
+                self.suppression_errors.len()
+            )?;
+            for suppression_error in &self.suppression_errors {
+                writeln!(f, "{}\n", suppression_error)?;
+            }
+        }
         Ok(())
     }
 }
 
 impl Display for CheckAllResult {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_parse_errors(f)?;
         if self.has_violations() {
             self.write_violations(f)
         } else {
@@ -165,12 +299,45 @@ impl Display for CheckAllResult {
     }
 }
 
+// Serialized like the `--json` output of `pks check`
+impl Serialize for CheckAllResult {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.json_output().serialize(serializer)
+    }
+}
+
 #[derive(Serialize)]
 struct CheckAllJsonOutput<'a> {
     status: &'a str,
     violations: Vec<JsonViolation>,
     stale_violations: &'a Vec<ViolationIdentifier>,
     strict_mode_violations: &'a Vec<ViolationIdentifier>,
+    parse_errors: Vec<JsonParseError>,
+    suppression_errors: &'a Vec<SuppressionReport>,
+}
+
+#[derive(Serialize)]
+struct JsonParseError {
+    message: String,
+    file: String,
+    line: usize,
+    column: usize,
+    violation_type: &'static str,
+}
+
+impl From<&FileParseError> for JsonParseError {
+    fn from(parse_error: &FileParseError) -> Self {
+        JsonParseError {
+            message: parse_error.message.clone(),
+            file: parse_error.file.clone(),
+            line: parse_error.source_location.line,
+            column: parse_error.source_location.column,
+            violation_type: "parse_error",
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -214,6 +381,8 @@ struct CheckAllBuilder<'a> {
 struct FoundViolations {
     absolute_paths: HashSet<PathBuf>,
     violations: HashSet<Violation>,
+    parse_errors: Vec<FileParseError>,
+    suppressions: Vec<SuppressionReport>,
 }
 
 impl<'a> CheckAllBuilder<'a> {
@@ -246,6 +415,17 @@ impl<'a> CheckAllBuilder<'a> {
                 .into_iter()
                 .cloned()
                 .collect(),
+            parse_errors: self.found_violations.parse_errors.clone(),
+            fail_on_parse_errors: self.configuration.fail_on_parse_errors,
+            suppression_errors: self
+                .found_violations
+                .suppressions
+                .iter()
+                .filter(|suppression| {
+                    suppression.status != SuppressionStatus::Used
+                })
+                .cloned()
+                .collect(),
         })
     }
 
@@ -339,25 +519,258 @@ impl<'a> CheckAllBuilder<'a> {
     }
 }
 
-pub(crate) fn check_all(
+pub fn check_all(
     configuration: &Configuration,
     files: Vec<String>,
 ) -> anyhow::Result<CheckAllResult> {
-    let checkers = get_checkers(configuration);
-
     debug!("Intersecting input files with configuration included files");
     let absolute_paths: HashSet<PathBuf> =
         configuration.intersect_files(files.clone());
 
-    let violations: HashSet<Violation> =
-        get_all_violations(configuration, &absolute_paths, &checkers)?;
-    let found_violations = FoundViolations {
+    let (constant_resolver, processed_files, run_stats) =
+        get_constant_resolver_processed_files_and_stats(
+            configuration,
+            &absolute_paths,
+        )?;
+    if files.is_empty() {
+        record_run(configuration, &run_stats);
+    }
+    let found_violations = find_violations(
+        configuration,
+        constant_resolver.as_ref(),
+        &processed_files.iter().collect::<Vec<&ProcessedFile>>(),
         absolute_paths,
-        violations,
-    };
+    )?;
     CheckAllBuilder::new(configuration, &found_violations).build()
 }
 
+/// The files whose violations may change along with `changed_files`: the
+/// changed files themselves, every file of a pack whose package.yml or
+/// package_todo.yml changed, since e.g. a removed dependency affects files
+/// that were not touched, and the files referencing constants the changed
+/// files define now or defined at `rev`, e.g. the constants of deleted files.
+pub(crate) fn files_affected_by(
+    configuration: &Configuration,
+    rev: &str,
+    changed_files: &[PathBuf],
+) -> anyhow::Result<HashSet<PathBuf>> {
+    let changed_files_set: HashSet<&PathBuf> = changed_files.iter().collect();
+    let mut affected_files: HashSet<PathBuf> = changed_files
+        .iter()
+        .filter(|file| configuration.included_files.contains(*file))
+        .cloned()
+        .collect();
+
+    let changed_packs: HashSet<&str> = configuration
+        .pack_set
+        .packs
+        .iter()
+        .filter(|pack| {
+            changed_files_set.contains(&pack.yml)
+                || changed_files_set
+                    .contains(&pack.yml.with_file_name("package_todo.yml"))
+        })
+        .map(|pack| pack.name.as_str())
+        .collect();
+    for file in &configuration.included_files {
+        if let Some(pack) = configuration.pack_set.for_file(file)? {
+            if changed_packs.contains(pack.name.as_str()) {
+                affected_files.insert(file.clone());
+            }
+        }
+    }
+
+    let changed_constants =
+        constants_defined_in(configuration, rev, changed_files)?;
+    if changed_constants.is_empty() {
+        return Ok(affected_files);
+    }
+
+    // References are only resolved against the changed constants: those
+    // that resolve to one of them may resolve differently since `rev`
+    let changed_constants_resolver =
+        constant_resolver_from_constants(changed_constants);
+    let processed_files = process_files_with_cache(
+        &configuration.included_files,
+        configuration.get_cache(),
+        configuration,
+    )?;
+    let referencing_files: Vec<PathBuf> = processed_files
+        .into_par_iter()
+        .filter(|processed_file| {
+            processed_file
+                .unresolved_references
+                .iter()
+                .any(|reference| {
+                    let namespace_path: Vec<&str> = reference
+                        .namespace_path
+                        .iter()
+                        .map(String::as_str)
+                        .collect();
+                    changed_constants_resolver
+                        .resolve(&reference.name, &namespace_path)
+                        .is_some()
+                })
+        })
+        .map(|processed_file| processed_file.absolute_path)
+        .collect();
+    affected_files.extend(referencing_files);
+
+    Ok(affected_files)
+}
+
+// The constants `changed_files` define now or defined at `rev`, once per name
+fn constants_defined_in(
+    configuration: &Configuration,
+    rev: &str,
+    changed_files: &[PathBuf],
+) -> anyhow::Result<Vec<ConstantDefinition>> {
+    let mut constants: Vec<ConstantDefinition> = Vec::new();
+    if configuration.experimental_parser {
+        // Constants are defined by the contents of files
+        for file in changed_files {
+            if get_file_type(file) != Some(SupportedFileType::Ruby) {
+                continue;
+            }
+            let relative_path =
+                file.strip_prefix(&configuration.absolute_root)?;
+            let contents_at_rev = git::file_at_rev(
+                &configuration.absolute_root,
+                rev,
+                relative_path,
+            )?;
+            let contents = std::fs::read_to_string(file).ok();
+            for contents in contents_at_rev.into_iter().chain(contents) {
+                let processed_file =
+                    process_from_ruby_contents(contents, file, configuration);
+                constants.extend(processed_file.definitions.into_iter().map(
+                    |definition| ConstantDefinition {
+                        fully_qualified_name: definition.fully_qualified_name,
+                        absolute_path_of_definition: file.clone(),
+                    },
+                ));
+            }
+        }
+    } else {
+        // Constants are inferred from the paths of files, which are the same
+        // at `rev`
+        constants = inferred_constants_from_files(
+            &configuration.pack_set,
+            &configuration.constant_resolver_configuration(),
+            changed_files,
+        );
+    }
+
+    let mut names: HashSet<String> = HashSet::new();
+    constants
+        .retain(|constant| names.insert(constant.fully_qualified_name.clone()));
+    Ok(constants)
+}
+
+/// Like `check_all`, but for files that were already processed and a
+/// constant resolver that was already built, as kept in memory by
+/// `pks server`.
+pub(crate) fn check_processed_files(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_files: &[&ProcessedFile],
+) -> anyhow::Result<CheckAllResult> {
+    let found_violations = find_violations(
+        configuration,
+        constant_resolver,
+        processed_files,
+        processed_files
+            .iter()
+            .map(|processed_file| processed_file.absolute_path.clone())
+            .collect(),
+    )?;
+    CheckAllBuilder::new(configuration, &found_violations).build()
+}
+
+fn find_violations(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_files: &[&ProcessedFile],
+    absolute_paths: HashSet<PathBuf>,
+) -> anyhow::Result<FoundViolations> {
+    let checkers = get_checkers(configuration);
+    let (references, sigils) = get_references_and_sigils_from_processed_files(
+        configuration,
+        constant_resolver,
+        processed_files,
+    )?;
+    let suppressions =
+        Suppressions::from_processed_files(configuration, processed_files);
+    let violations = run_checkers(
+        configuration,
+        &references,
+        &sigils,
+        &suppressions,
+        &checkers,
+    )?;
+    Ok(FoundViolations {
+        absolute_paths,
+        violations,
+        parse_errors: collect_parse_errors(configuration, processed_files),
+        suppressions: suppressions.reports(),
+    })
+}
+
+/// The inline suppressions of all included files, and whether they
+/// suppress anything
+pub(crate) fn list_suppressions(
+    configuration: &Configuration,
+) -> anyhow::Result<Vec<SuppressionReport>> {
+    let (constant_resolver, processed_files) =
+        get_constant_resolver_and_processed_files(
+            configuration,
+            &configuration.included_files,
+        )?;
+    let found_violations = find_violations(
+        configuration,
+        constant_resolver.as_ref(),
+        &processed_files.iter().collect::<Vec<&ProcessedFile>>(),
+        configuration.included_files.clone(),
+    )?;
+    Ok(found_violations.suppressions)
+}
+
+// Sorted by file and location
+fn collect_parse_errors(
+    configuration: &Configuration,
+    processed_files: &[&ProcessedFile],
+) -> Vec<FileParseError> {
+    let mut parse_errors: Vec<FileParseError> = processed_files
+        .iter()
+        .flat_map(|processed_file| {
+            let file = processed_file
+                .absolute_path
+                .strip_prefix(&configuration.absolute_root)
+                .unwrap_or(&processed_file.absolute_path)
+                .to_string_lossy()
+                .to_string();
+            processed_file.parse_errors.iter().map(move |parse_error| {
+                FileParseError {
+                    file: file.clone(),
+                    message: parse_error.message.clone(),
+                    source_location: crate::packs::SourceLocation {
+                        line: parse_error.location.start_row,
+                        column: parse_error.location.start_col,
+                    },
+                }
+            })
+        })
+        .collect();
+    parse_errors.sort_by(|a, b| {
+        (&a.file, a.source_location.line, a.source_location.column).cmp(&(
+            &b.file,
+            b.source_location.line,
+            b.source_location.column,
+        ))
+    });
+    parse_errors
+}
+
 fn validate(configuration: &Configuration) -> Vec<String> {
     debug!("Running validators against packages");
     let validators: Vec<Box<dyn ValidatorInterface + Send + Sync>> = vec![
@@ -388,6 +801,18 @@ pub(crate) fn build_strict_violation_message(
     violation_identifier.violation_type,)
 }
 
+pub(crate) fn build_stale_violation_message(
+    violation_identifier: &ViolationIdentifier,
+) -> String {
+    format!(
+        "Stale {} violation of `{}` in {} is no longer found, please run `{} update`",
+        violation_identifier.violation_type,
+        violation_identifier.constant_name,
+        violation_identifier.file,
+        bin_locater::packs_bin_name(),
+    )
+}
+
 pub(crate) fn validate_all(
     configuration: &Configuration,
 ) -> anyhow::Result<()> {
@@ -428,7 +853,7 @@ struct ValidateJsonOutput {
     validation_errors: Vec<ValidationError>,
 }
 
-pub(crate) fn validate_structured(
+pub fn validate_structured(
     configuration: &Configuration,
 ) -> Vec<ValidationError> {
     let mut errors = dependency::validate_structured(configuration);
@@ -480,10 +905,35 @@ pub(crate) fn validate_all_json(
     Ok(())
 }
 
-pub(crate) fn update(
+pub(crate) fn validate_all_sarif(
     configuration: &Configuration,
-    options: &UpdateOptions,
 ) -> anyhow::Result<()> {
+    let validation_errors = validate_structured(configuration);
+
+    println!(
+        "{}",
+        sarif::validation_errors_to_sarif(&validation_errors)
+            .context("Failed to serialize validation SARIF")?
+    );
+
+    if !validation_errors.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+pub struct UpdateResult {
+    pub stats: package_todo::UpdateStats,
+    pub strict_mode_violations: Vec<Violation>,
+}
+
+/// Rewrites package_todo.yml files without printing anything, so callers
+/// embedding packs can report the outcome however they like.
+pub fn update_package_todos(
+    configuration: &Configuration,
+    options: &UpdateOptions,
+) -> anyhow::Result<UpdateResult> {
     if options.expand_to_pack && options.files.is_empty() {
         bail!("--pack requires at least one file argument");
     }
@@ -496,8 +946,11 @@ pub(crate) fn update(
         configuration.included_files.clone()
     };
 
-    let violations =
+    let (violations, run_stats) =
         get_all_violations(configuration, &absolute_paths, &checkers)?;
+    if !options.is_scoped() {
+        record_run(configuration, &run_stats);
+    }
 
     let violations = if options.is_scoped() {
         filter_violations(violations, options)
@@ -505,12 +958,35 @@ pub(crate) fn update(
         violations
     };
 
-    let strict_violations = &violations
+    let strict_mode_violations = violations
         .iter()
         .filter(|v| v.identifier.strict)
-        .collect::<Vec<&Violation>>();
+        .cloned()
+        .collect::<Vec<Violation>>();
+
+    let stats = if options.is_scoped() {
+        package_todo::merge_violations_to_disk(configuration, violations)
+    } else {
+        package_todo::write_violations_to_disk(configuration, violations)
+    };
+
+    Ok(UpdateResult {
+        stats,
+        strict_mode_violations,
+    })
+}
+
+pub(crate) fn update(
+    configuration: &Configuration,
+    options: &UpdateOptions,
+) -> anyhow::Result<()> {
+    let UpdateResult {
+        stats,
+        strict_mode_violations: strict_violations,
+    } = update_package_todos(configuration, options)?;
+
     if !strict_violations.is_empty() {
-        for violation in strict_violations {
+        for violation in &strict_violations {
             let strict_message =
                 build_strict_violation_message(&violation.identifier);
             println!("{}", strict_message);
@@ -521,12 +997,6 @@ pub(crate) fn update(
         );
     }
 
-    let stats = if options.is_scoped() {
-        package_todo::merge_violations_to_disk(configuration, violations)
-    } else {
-        package_todo::write_violations_to_disk(configuration, violations)
-    };
-
     if stats.is_empty() {
         println!("No changes to package_todo.yml files.");
     } else {
@@ -733,17 +1203,45 @@ fn get_all_violations(
     configuration: &Configuration,
     absolute_paths: &HashSet<PathBuf>,
     checkers: &Vec<Box<dyn CheckerInterface + Send + Sync>>,
+) -> anyhow::Result<(HashSet<Violation>, RunStats)> {
+    let (constant_resolver, processed_files, run_stats) =
+        get_constant_resolver_processed_files_and_stats(
+            configuration,
+            absolute_paths,
+        )?;
+    let processed_files: Vec<&ProcessedFile> = processed_files.iter().collect();
+    let (references, sigils) = get_references_and_sigils_from_processed_files(
+        configuration,
+        constant_resolver.as_ref(),
+        &processed_files,
+    )?;
+    let suppressions =
+        Suppressions::from_processed_files(configuration, &processed_files);
+    let violations = run_checkers(
+        configuration,
+        &references,
+        &sigils,
+        &suppressions,
+        checkers,
+    )?;
+    Ok((violations, run_stats))
+}
+
+fn run_checkers(
+    configuration: &Configuration,
+    references: &Vec<Reference>,
+    sigils: &HashMap<PathBuf, Vec<Sigil>>,
+    suppressions: &Suppressions,
+    checkers: &Vec<Box<dyn CheckerInterface + Send + Sync>>,
 ) -> anyhow::Result<HashSet<Violation>> {
-    let (references, sigils) =
-        get_all_references_and_sigils(configuration, absolute_paths)?;
     debug!("Running checkers on resolved references");
 
-    let violations = checkers
+    let violations: anyhow::Result<HashSet<Violation>> = checkers
         .into_par_iter()
         .try_fold(HashSet::new, |mut acc, c| {
-            for reference in &references {
+            for reference in references {
                 if let Some(violation) =
-                    c.check(reference, configuration, &sigils)?
+                    c.check(reference, configuration, sigils, suppressions)?
                 {
                     acc.insert(violation);
                 }
@@ -754,10 +1252,20 @@ fn get_all_violations(
             acc.extend(v);
             Ok(acc)
         });
+    let mut violations = violations?;
+
+    for external_checker in &configuration.external_checkers {
+        debug!("Running external checker {}", external_checker.name);
+        violations.extend(external_checker.check_all(
+            references,
+            configuration,
+            suppressions,
+        )?);
+    }
 
     debug!("Finished running checkers");
 
-    violations
+    Ok(violations)
 }
 
 fn get_checkers(
@@ -771,6 +1279,7 @@ fn get_checkers(
             layers: configuration.layers.clone(),
         }),
         Box::new(folder_privacy::Checker {}),
+        Box::new(rules::Checker::new(&configuration.rules)),
     ]
 }
 
@@ -827,6 +1336,9 @@ mod tests {
             ].iter().cloned().collect(),
             stale_violations: Vec::new(),
             strict_mode_violations: Vec::new(),
+            parse_errors: vec![],
+            fail_on_parse_errors: false,
+            suppression_errors: vec![],
         };
 
         let expected_output = "2 violation(s) detected:
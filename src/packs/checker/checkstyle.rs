@@ -0,0 +1,216 @@
+// Renders check results as Checkstyle XML, grouping violations by file.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::output_helper::xml_escape;
+use super::{
+    build_stale_violation_message, build_strict_violation_message,
+    CheckAllResult, Violation,
+};
+use crate::packs::package_todo::package_todo_relative_path;
+
+struct Error {
+    // Strict mode and stale violations have no position within the file,
+    // suppression comments have no column
+    line: Option<usize>,
+    column: Option<usize>,
+    severity: &'static str,
+    message: String,
+    source: String,
+}
+
+pub(crate) fn check_result_to_checkstyle(result: &CheckAllResult) -> String {
+    let mut sorted_violations: Vec<&Violation> =
+        result.reportable_violations.iter().collect();
+    sorted_violations.sort_by(|a, b| a.message.cmp(&b.message));
+
+    let mut errors_by_file: BTreeMap<String, Vec<Error>> = BTreeMap::new();
+    for violation in sorted_violations {
+        let identifier = &violation.identifier;
+        errors_by_file
+            .entry(identifier.file.clone())
+            .or_default()
+            .push(Error {
+                line: Some(violation.source_location.line),
+                // Checkstyle columns are 1-based
+                column: Some(violation.source_location.column + 1),
+                severity: if identifier.strict {
+                    "error"
+                } else {
+                    "warning"
+                },
+                message: String::from_utf8_lossy(&strip_ansi_escapes::strip(
+                    &violation.message,
+                ))
+                .to_string(),
+                source: format!("pks.{}", identifier.violation_type),
+            });
+    }
+    for identifier in &result.strict_mode_violations {
+        errors_by_file
+            .entry(identifier.file.clone())
+            .or_default()
+            .push(Error {
+                line: None,
+                column: None,
+                severity: "error",
+                message: build_strict_violation_message(identifier),
+                source: format!("pks.{}", identifier.violation_type),
+            });
+    }
+    for identifier in &result.stale_violations {
+        errors_by_file
+            .entry(package_todo_relative_path(
+                &identifier.referencing_pack_name,
+            ))
+            .or_default()
+            .push(Error {
+                line: None,
+                column: None,
+                severity: "warning",
+                message: build_stale_violation_message(identifier),
+                source: "pks.stale_violation".to_owned(),
+            });
+    }
+
+    for parse_error in &result.parse_errors {
+        errors_by_file
+            .entry(parse_error.file.clone())
+            .or_default()
+            .push(Error {
+                line: Some(parse_error.source_location.line),
+                column: Some(parse_error.source_location.column + 1),
+                // Parse errors only fail `check` with `fail_on_parse_errors`
+                severity: if result.fail_on_parse_errors {
+                    "error"
+                } else {
+                    "warning"
+                },
+                message: format!("Parse error: {}", parse_error.message),
+                source: "pks.parse_error".to_owned(),
+            });
+    }
+    for suppression in &result.suppression_errors {
+        errors_by_file
+            .entry(suppression.file.clone())
+            .or_default()
+            .push(Error {
+                line: Some(suppression.line),
+                column: None,
+                severity: "error",
+                message: suppression.message(),
+                source: "pks.suppression".to_owned(),
+            });
+    }
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n",
+    );
+    for (file, errors) in errors_by_file {
+        // Writing to a String cannot fail
+        let _ = writeln!(xml, "  <file name=\"{}\">", xml_escape(&file));
+        for error in errors {
+            xml.push_str("    <error");
+            if let Some(line) = error.line {
+                let _ = write!(xml, " line=\"{}\"", line);
+            }
+            if let Some(column) = error.column {
+                let _ = write!(xml, " column=\"{}\"", column);
+            }
+            let _ = writeln!(
+                xml,
+                " severity=\"{}\" message=\"{}\" source=\"{}\"/>",
+                error.severity,
+                xml_escape(&error.message),
+                xml_escape(&error.source)
+            );
+        }
+        xml.push_str("  </file>\n");
+    }
+    xml.push_str("</checkstyle>");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::checker::common_test::tests::{
+        build_check_all_result, build_violation_identifier,
+    };
+    use crate::packs::checker::{
+        FileParseError, SuppressionReport, SuppressionStatus,
+    };
+    use crate::packs::SourceLocation;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_check_result_to_checkstyle() {
+        let result = build_check_all_result(
+            "\u{1b}[36mpacks/foo/app/services/foo.rb\u{1b}[0m:3:4\nDependency violation: `::Bar` belongs to `packs/bar`",
+            build_violation_identifier("dependency", false),
+            vec![build_violation_identifier("privacy", false)],
+            vec![],
+        );
+
+        assert_eq!(
+            check_result_to_checkstyle(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="packs/foo/app/services/foo.rb">
+    <error line="3" column="5" severity="warning" message="packs/foo/app/services/foo.rb:3:4&#10;Dependency violation: `::Bar` belongs to `packs/bar`" source="pks.dependency"/>
+  </file>
+  <file name="packs/foo/package_todo.yml">
+    <error severity="warning" message="Stale privacy violation of `::Bar` in packs/foo/app/services/foo.rb is no longer found, please run `packs update`" source="pks.stale_violation"/>
+  </file>
+</checkstyle>"#
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_to_checkstyle() {
+        let result = CheckAllResult {
+            parse_errors: vec![FileParseError {
+                file: "packs/foo/app/services/baz.rb".to_owned(),
+                message: "unexpected END_OF_INPUT".to_owned(),
+                source_location: SourceLocation { line: 7, column: 0 },
+            }],
+            fail_on_parse_errors: true,
+            ..CheckAllResult::default()
+        };
+
+        assert_eq!(
+            check_result_to_checkstyle(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="packs/foo/app/services/baz.rb">
+    <error line="7" column="1" severity="error" message="Parse error: unexpected END_OF_INPUT" source="pks.parse_error"/>
+  </file>
+</checkstyle>"#
+        );
+    }
+
+    #[test]
+    fn test_suppression_errors_to_checkstyle() {
+        let result = CheckAllResult {
+            suppression_errors: vec![SuppressionReport {
+                file: "packs/foo/app/services/foo.rb".to_owned(),
+                line: 2,
+                violation_types: vec!["privacy".to_owned()],
+                reason: None,
+                status: SuppressionStatus::MissingReason,
+                unused_violation_types: vec![],
+            }],
+            ..CheckAllResult::default()
+        };
+
+        assert_eq!(
+            check_result_to_checkstyle(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="packs/foo/app/services/foo.rb">
+    <error line="2" severity="error" message="Suppression of privacy violations is missing a reason, e.g. `# packs:disable-next-line privacy -- reason: ...`" source="pks.suppression"/>
+  </file>
+</checkstyle>"#
+        );
+    }
+}
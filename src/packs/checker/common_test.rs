@@ -8,9 +8,11 @@ pub mod tests {
 
     use crate::packs::{
         checker::{
-            reference::Reference, CheckerInterface, ViolationIdentifier,
+            reference::Reference, suppression::Suppressions, CheckAllResult,
+            CheckerInterface, ViolationIdentifier,
         },
         pack::Pack,
+        parsing::ReferenceKind,
         Configuration, PackSet, Sigil, SourceLocation, Violation,
     };
 
@@ -61,6 +63,40 @@ pub mod tests {
         }
     }
 
+    pub fn build_violation_identifier(
+        violation_type: &str,
+        strict: bool,
+    ) -> ViolationIdentifier {
+        ViolationIdentifier {
+            violation_type: violation_type.to_owned(),
+            strict,
+            file: "packs/foo/app/services/foo.rb".to_owned(),
+            constant_name: "::Bar".to_owned(),
+            referencing_pack_name: "packs/foo".to_owned(),
+            defining_pack_name: "packs/bar".to_owned(),
+        }
+    }
+
+    /// A result reporting one violation, at packs/foo/app/services/foo.rb:3:4,
+    /// for the output format tests
+    pub fn build_check_all_result(
+        message: &str,
+        identifier: ViolationIdentifier,
+        stale_violations: Vec<ViolationIdentifier>,
+        strict_mode_violations: Vec<ViolationIdentifier>,
+    ) -> CheckAllResult {
+        CheckAllResult {
+            reportable_violations: HashSet::from([Violation {
+                message: message.to_owned(),
+                identifier,
+                source_location: SourceLocation { line: 3, column: 4 },
+            }]),
+            stale_violations,
+            strict_mode_violations,
+            ..CheckAllResult::default()
+        }
+    }
+
     impl Default for TestChecker {
         fn default() -> Self {
             TestChecker {
@@ -115,6 +151,7 @@ pub mod tests {
                 "packs/bar/app/services/public/bar.rb",
             )),
             source_location: SourceLocation { line: 3, column: 1 },
+            kind: ReferenceKind::Constant,
         });
 
         let root_pack = Pack {
@@ -141,7 +178,12 @@ pub mod tests {
 
         let sigils: HashMap<PathBuf, Vec<Sigil>> = HashMap::new();
 
-        let result = checker.check(&reference, &configuration, &sigils)?;
+        let result = checker.check(
+            &reference,
+            &configuration,
+            &sigils,
+            &Suppressions::default(),
+        )?;
 
         let stripped_result = match result {
             Some(violation) => {
@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::output_helper::print_reference_location;
 use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
 use super::{CheckerInterface, CycleEdge, ValidationError, ValidatorInterface};
 use crate::packs::checker::Reference;
 use crate::packs::pack::Pack;
@@ -423,6 +424,7 @@ impl CheckerInterface for Checker {
         reference: &Reference,
         configuration: &Configuration,
         _sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>> {
         let pack_checker =
             PackChecker::new(configuration, reference, &self.violation_type())?;
@@ -468,6 +470,10 @@ impl CheckerInterface for Checker {
         // To receive help interpreting or resolving this error message, see: https://github.com/Shopify/packwerk/blob/main/TROUBLESHOOT.md#Troubleshooting-violations
         // END: Original packwerk message
 
+        if suppressions.suppresses(reference, &self.violation_type()) {
+            return Ok(None);
+        }
+
         let loc = print_reference_location(reference);
         let message = format!(
                 "{}Dependency violation: `{}` belongs to `{}`, but `{}` does not specify a dependency on `{}`.",
@@ -500,6 +506,7 @@ mod tests {
         pack::{CheckerSetting, EnforcementGlobsIgnore},
     };
 
+    use super::validate_structured;
     use super::*;
     use crate::packs::*;
     use pretty_assertions::assert_eq;
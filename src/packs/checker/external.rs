@@ -0,0 +1,220 @@
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::pack_checker::ViolationDirection;
+use super::reference::Reference;
+use super::suppression::Suppressions;
+use super::{Violation, ViolationIdentifier};
+use crate::packs::{Configuration, SourceLocation};
+
+/// A checker run as an external process, declared in `packwerk.yml`:
+/// external_checkers:
+///   - name: no_sql
+///     command: bin/no_sql_checker
+///     args: ["--verbose"]
+///
+/// It is run once per check from the root, and receives one JSON object per
+/// line on stdin: a `{"type": "pack", ...}` line for each pack, then a
+/// `{"type": "reference", ...}` line for each resolved reference. It prints
+/// one violation per line, in the `ViolationIdentifier` shape, to stdout.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ExternalChecker {
+    pub name: String,
+    // Relative to the root, unless it is on the PATH
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+// What an external checker prints for each violation. `message`, `line`,
+// `column` and `direction` are optional additions to `ViolationIdentifier`.
+#[derive(Debug, Deserialize)]
+struct ExternalViolation {
+    violation_type: String,
+    #[serde(default)]
+    strict: bool,
+    file: String,
+    constant_name: String,
+    referencing_pack_name: String,
+    defining_pack_name: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    line: usize,
+    #[serde(default)]
+    column: usize,
+    #[serde(default)]
+    direction: ViolationDirection,
+}
+
+impl ExternalChecker {
+    pub(crate) fn check_all(
+        &self,
+        references: &[Reference],
+        configuration: &Configuration,
+        suppressions: &Suppressions,
+    ) -> anyhow::Result<Vec<Violation>> {
+        let input = self.input(references, configuration)?;
+
+        let command_path = configuration.absolute_root.join(&self.command);
+        let program = if command_path.exists() {
+            command_path.into_os_string()
+        } else {
+            self.command.clone().into()
+        };
+        let mut child = Command::new(program)
+            .args(&self.args)
+            .current_dir(&configuration.absolute_root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(format!(
+                "Failed to run external checker `{}` ({})",
+                self.name, self.command
+            ))?;
+
+        // Written from another thread, so that a checker printing violations
+        // while it reads can't block on a full pipe
+        let mut stdin = child.stdin.take().unwrap();
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // A checker may exit without reading everything
+                let _ = stdin.write_all(input.as_bytes());
+            });
+            child.wait_with_output()
+        })?;
+
+        if !output.status.success() {
+            bail!(
+                "External checker `{}` failed ({}): {}",
+                self.name,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let mut violations = Vec::new();
+        for line in output.stdout.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let violation: ExternalViolation = serde_json::from_str(&line)
+                .context(format!(
+                    "External checker `{}` printed an invalid violation: {}",
+                    self.name, line
+                ))?;
+            if suppressions.suppresses_at(
+                &violation.file,
+                violation.line,
+                &violation.violation_type,
+            ) {
+                continue;
+            }
+            violations.push(self.violation(violation, configuration));
+        }
+        Ok(violations)
+    }
+
+    fn input(
+        &self,
+        references: &[Reference],
+        configuration: &Configuration,
+    ) -> anyhow::Result<String> {
+        let mut lines: Vec<String> = Vec::new();
+
+        let mut packs: Vec<_> = configuration.pack_set.packs.iter().collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        for pack in packs {
+            lines.push(serde_json::to_string(&json!({
+                "type": "pack",
+                "name": pack.name,
+                "package_yml": pack,
+            }))?);
+        }
+
+        let mut references: Vec<&Reference> = references.iter().collect();
+        references.sort_by(|a, b| {
+            (
+                &a.relative_referencing_file,
+                a.source_location.line,
+                a.source_location.column,
+                &a.constant_name,
+            )
+                .cmp(&(
+                    &b.relative_referencing_file,
+                    b.source_location.line,
+                    b.source_location.column,
+                    &b.constant_name,
+                ))
+        });
+        for reference in references {
+            let mut line = serde_json::to_value(reference)?;
+            line["type"] = json!("reference");
+            lines.push(serde_json::to_string(&line)?);
+        }
+
+        let mut input = lines.join("\n");
+        input.push('\n');
+        Ok(input)
+    }
+
+    fn violation(
+        &self,
+        violation: ExternalViolation,
+        configuration: &Configuration,
+    ) -> Violation {
+        // Like built-in checkers, `enforce_<violation type>: strict` makes
+        // violations strict, in the referencing pack's package.yml for
+        // outgoing violations and in the defining pack's for incoming ones
+        let rules_pack_name = match violation.direction {
+            ViolationDirection::Outgoing => &violation.referencing_pack_name,
+            ViolationDirection::Incoming => &violation.defining_pack_name,
+        };
+        let strict = violation.strict
+            || configuration
+                .pack_set
+                .for_pack(rules_pack_name)
+                .ok()
+                .and_then(|pack| {
+                    pack.client_keys
+                        .get(&format!("enforce_{}", violation.violation_type))
+                })
+                .and_then(|setting| setting.as_str())
+                .is_some_and(|setting| setting == "strict");
+
+        let message = violation.message.unwrap_or_else(|| {
+            format!(
+                "{} violation: `{}` belongs to `{}`, but is referenced from `{}`",
+                violation.violation_type,
+                violation.constant_name,
+                violation.defining_pack_name,
+                violation.referencing_pack_name,
+            )
+        });
+
+        Violation {
+            message: format!(
+                "\x1b[36m{}\x1b[0m:{}:{}\n{}",
+                violation.file, violation.line, violation.column, message
+            ),
+            identifier: ViolationIdentifier {
+                violation_type: violation.violation_type,
+                strict,
+                file: violation.file,
+                constant_name: violation.constant_name,
+                referencing_pack_name: violation.referencing_pack_name,
+                defining_pack_name: violation.defining_pack_name,
+            },
+            source_location: SourceLocation {
+                line: violation.line,
+                column: violation.column,
+            },
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use super::output_helper::print_reference_location;
 use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
 use super::CheckerInterface;
 use crate::packs::checker::reference::Reference;
 use crate::packs::pack::Pack;
@@ -15,6 +16,7 @@ impl CheckerInterface for Checker {
         reference: &Reference,
         configuration: &Configuration,
         _sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>> {
         let pack_checker =
             PackChecker::new(configuration, reference, &self.violation_type())?;
@@ -24,6 +26,10 @@ impl CheckerInterface for Checker {
         let defining_pack = pack_checker.defining_pack.unwrap();
 
         if !folder_visible(pack_checker.referencing_pack, defining_pack) {
+            if suppressions.suppresses(reference, &self.violation_type()) {
+                return Ok(None);
+            }
+
             let loc = print_reference_location(reference);
 
             let message = format!(
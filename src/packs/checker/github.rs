@@ -0,0 +1,131 @@
+// Renders check results as GitHub Actions workflow commands, which GitHub
+// shows as inline annotations on the PR diff.
+// See https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions
+use super::{
+    build_stale_violation_message, build_strict_violation_message,
+    CheckAllResult, JsonViolation, Violation,
+};
+use crate::packs::package_todo::package_todo_relative_path;
+
+pub(crate) fn check_result_to_github(result: &CheckAllResult) -> String {
+    let mut sorted_violations: Vec<&Violation> =
+        result.reportable_violations.iter().collect();
+    sorted_violations.sort_by(|a, b| a.message.cmp(&b.message));
+
+    let mut lines: Vec<String> = sorted_violations
+        .into_iter()
+        .map(|violation| {
+            let violation = JsonViolation::from(violation);
+            // GitHub columns are 1-based, so the `file:line:column` location
+            // the message starts with is rewritten to match `col`
+            let column = violation.column + 1;
+            let location = format!("{}:{}:", violation.file, violation.line);
+            let message = match violation
+                .message
+                .strip_prefix(&format!("{}{}", location, violation.column))
+            {
+                Some(rest) => format!("{}{}{}", location, column, rest),
+                None => violation.message.clone(),
+            };
+            format!(
+                "::error file={},line={},col={}::{}",
+                escape_property(&violation.file),
+                violation.line,
+                column,
+                escape_data(&message)
+            )
+        })
+        .collect();
+    lines.extend(result.strict_mode_violations.iter().map(|identifier| {
+        format!(
+            "::error file={}::{}",
+            escape_property(&identifier.file),
+            escape_data(&build_strict_violation_message(identifier))
+        )
+    }));
+    lines.extend(result.stale_violations.iter().map(|identifier| {
+        format!(
+            "::warning file={}::{}",
+            escape_property(&package_todo_relative_path(
+                &identifier.referencing_pack_name
+            )),
+            escape_data(&build_stale_violation_message(identifier))
+        )
+    }));
+    let parse_error_level = if result.fail_on_parse_errors {
+        "error"
+    } else {
+        "warning"
+    };
+    lines.extend(result.parse_errors.iter().map(|parse_error| {
+        format!(
+            "::{} file={},line={},col={}::{}",
+            parse_error_level,
+            escape_property(&parse_error.file),
+            parse_error.source_location.line,
+            parse_error.source_location.column + 1,
+            escape_data(&format!("Parse error: {}", parse_error.message))
+        )
+    }));
+    lines.extend(result.suppression_errors.iter().map(|suppression| {
+        format!(
+            "::error file={},line={}::{}",
+            escape_property(&suppression.file),
+            suppression.line,
+            escape_data(&suppression.message())
+        )
+    }));
+    lines.join("\n")
+}
+
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::checker::common_test::tests::{
+        build_check_all_result, build_violation_identifier,
+    };
+    use crate::packs::checker::FileParseError;
+    use crate::packs::SourceLocation;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_check_result_to_github() {
+        let result = CheckAllResult {
+            parse_errors: vec![FileParseError {
+                file: "packs/foo/app/services/baz.rb".to_owned(),
+                message: "unexpected END_OF_INPUT".to_owned(),
+                source_location: SourceLocation { line: 7, column: 0 },
+            }],
+            ..build_check_all_result(
+                "\u{1b}[36mpacks/foo/app/services/foo.rb\u{1b}[0m:3:4\nDependency violation: 100% not allowed",
+                build_violation_identifier("dependency", false),
+                vec![build_violation_identifier("privacy", false)],
+                vec![],
+            )
+        };
+
+        assert_eq!(
+            check_result_to_github(&result),
+            "::error file=packs/foo/app/services/foo.rb,line=3,col=5::packs/foo/app/services/foo.rb:3:5%0ADependency violation: 100%25 not allowed\n\
+             ::warning file=packs/foo/package_todo.yml::Stale privacy violation of `::Bar` in packs/foo/app/services/foo.rb is no longer found, please run `packs update`\n\
+             ::warning file=packs/foo/app/services/baz.rb,line=7,col=1::Parse error: unexpected END_OF_INPUT"
+        );
+    }
+
+    #[test]
+    fn test_escape_property() {
+        assert_eq!(escape_property("a,b:c%\n"), "a%2Cb%3Ac%25%0A");
+    }
+}
@@ -0,0 +1,292 @@
+// Renders check results as JUnit XML: one testsuite per referencing pack and
+// one failed testcase per violation. Parse errors and suppression errors get
+// testsuites of their own, parse errors as skipped testcases unless they fail
+// `check`.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::output_helper::xml_escape;
+use super::{
+    build_stale_violation_message, build_strict_violation_message,
+    CheckAllResult, Violation,
+};
+
+const PARSE_ERRORS_SUITE: &str = "parse_errors";
+const SUPPRESSIONS_SUITE: &str = "suppressions";
+
+struct Failure {
+    name: String,
+    file: String,
+    failure_type: String,
+    message: String,
+    // Reported without failing the testsuite
+    skipped: bool,
+}
+
+pub(crate) fn check_result_to_junit(result: &CheckAllResult) -> String {
+    let mut sorted_violations: Vec<&Violation> =
+        result.reportable_violations.iter().collect();
+    sorted_violations.sort_by(|a, b| a.message.cmp(&b.message));
+
+    let mut failures_by_pack: BTreeMap<&str, Vec<Failure>> = BTreeMap::new();
+    for violation in sorted_violations {
+        let identifier = &violation.identifier;
+        failures_by_pack
+            .entry(&identifier.referencing_pack_name)
+            .or_default()
+            .push(Failure {
+                name: format!(
+                    "{}:{}:{} {} violation on {}",
+                    identifier.file,
+                    violation.source_location.line,
+                    violation.source_location.column,
+                    identifier.violation_type,
+                    identifier.constant_name
+                ),
+                file: identifier.file.clone(),
+                failure_type: identifier.violation_type.clone(),
+                message: String::from_utf8_lossy(&strip_ansi_escapes::strip(
+                    &violation.message,
+                ))
+                .to_string(),
+                skipped: false,
+            });
+    }
+    for identifier in &result.strict_mode_violations {
+        failures_by_pack
+            .entry(&identifier.referencing_pack_name)
+            .or_default()
+            .push(Failure {
+                name: format!(
+                    "{} strict mode {} violation on {}",
+                    identifier.file,
+                    identifier.violation_type,
+                    identifier.constant_name
+                ),
+                file: identifier.file.clone(),
+                failure_type: identifier.violation_type.clone(),
+                message: build_strict_violation_message(identifier),
+                skipped: false,
+            });
+    }
+    for identifier in &result.stale_violations {
+        failures_by_pack
+            .entry(&identifier.referencing_pack_name)
+            .or_default()
+            .push(Failure {
+                name: format!(
+                    "{} stale {} violation on {}",
+                    identifier.file,
+                    identifier.violation_type,
+                    identifier.constant_name
+                ),
+                file: identifier.file.clone(),
+                failure_type: "stale_violation".to_owned(),
+                message: build_stale_violation_message(identifier),
+                skipped: false,
+            });
+    }
+    for parse_error in &result.parse_errors {
+        failures_by_pack
+            .entry(PARSE_ERRORS_SUITE)
+            .or_default()
+            .push(Failure {
+                name: format!(
+                    "{}:{}:{} parse error",
+                    parse_error.file,
+                    parse_error.source_location.line,
+                    parse_error.source_location.column
+                ),
+                file: parse_error.file.clone(),
+                failure_type: "parse_error".to_owned(),
+                message: format!("Parse error: {}", parse_error.message),
+                skipped: !result.fail_on_parse_errors,
+            });
+    }
+    for suppression in &result.suppression_errors {
+        failures_by_pack
+            .entry(SUPPRESSIONS_SUITE)
+            .or_default()
+            .push(Failure {
+                name: format!(
+                    "{}:{} suppression",
+                    suppression.file, suppression.line
+                ),
+                file: suppression.file.clone(),
+                failure_type: "suppression".to_owned(),
+                message: suppression.message(),
+                skipped: false,
+            });
+    }
+
+    let count_failed = |failures: &[Failure]| {
+        failures.iter().filter(|failure| !failure.skipped).count()
+    };
+    let total: usize = failures_by_pack.values().map(Vec::len).sum();
+    let total_failed: usize =
+        failures_by_pack.values().map(|f| count_failed(f)).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"pks\" tests=\"{}\" failures=\"{}\"{}>",
+        total,
+        total_failed,
+        skipped_attribute(total - total_failed)
+    );
+    for (pack_name, failures) in failures_by_pack {
+        let failed = count_failed(&failures);
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\"{}>",
+            xml_escape(pack_name),
+            failures.len(),
+            failed,
+            skipped_attribute(failures.len() - failed)
+        );
+        for failure in failures {
+            let _ = writeln!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\">",
+                xml_escape(&failure.name),
+                xml_escape(pack_name),
+                xml_escape(&failure.file)
+            );
+            if failure.skipped {
+                let _ = writeln!(
+                    xml,
+                    "      <skipped message=\"{}\"/>",
+                    xml_escape(&failure.message)
+                );
+            } else {
+                let _ = writeln!(
+                    xml,
+                    "      <failure type=\"{}\" message=\"{}\"/>",
+                    xml_escape(&failure.failure_type),
+                    xml_escape(&failure.message)
+                );
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>");
+    xml
+}
+
+fn skipped_attribute(skipped: usize) -> String {
+    if skipped == 0 {
+        String::new()
+    } else {
+        format!(" skipped=\"{}\"", skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::checker::common_test::tests::{
+        build_check_all_result, build_violation_identifier,
+    };
+    use crate::packs::checker::{
+        FileParseError, SuppressionReport, SuppressionStatus,
+    };
+    use crate::packs::SourceLocation;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_check_result_to_junit() {
+        let result = build_check_all_result(
+            "\u{1b}[36mpacks/foo/app/services/foo.rb\u{1b}[0m:3:4\nPrivacy violation: `::Bar` is private",
+            build_violation_identifier("privacy", false),
+            vec![],
+            vec![build_violation_identifier("privacy", true)],
+        );
+
+        assert_eq!(
+            check_result_to_junit(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="pks" tests="2" failures="2">
+  <testsuite name="packs/foo" tests="2" failures="2">
+    <testcase name="packs/foo/app/services/foo.rb:3:4 privacy violation on ::Bar" classname="packs/foo" file="packs/foo/app/services/foo.rb">
+      <failure type="privacy" message="packs/foo/app/services/foo.rb:3:4&#10;Privacy violation: `::Bar` is private"/>
+    </testcase>
+    <testcase name="packs/foo/app/services/foo.rb strict mode privacy violation on ::Bar" classname="packs/foo" file="packs/foo/app/services/foo.rb">
+      <failure type="privacy" message="packs/foo cannot have privacy violations on packs/bar because strict mode is enabled for privacy violations in the enforcing pack&apos;s package.yml file"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#
+        );
+    }
+
+    #[test]
+    fn test_check_result_to_junit_without_violations() {
+        let result = CheckAllResult::default();
+
+        assert_eq!(
+            check_result_to_junit(&result),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"pks\" tests=\"0\" failures=\"0\">\n</testsuites>"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_to_junit() {
+        let parse_error = FileParseError {
+            file: "packs/foo/app/services/baz.rb".to_owned(),
+            message: "unexpected END_OF_INPUT".to_owned(),
+            source_location: SourceLocation { line: 7, column: 0 },
+        };
+        let result = CheckAllResult {
+            parse_errors: vec![parse_error.clone()],
+            ..CheckAllResult::default()
+        };
+
+        assert_eq!(
+            check_result_to_junit(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="pks" tests="1" failures="0" skipped="1">
+  <testsuite name="parse_errors" tests="1" failures="0" skipped="1">
+    <testcase name="packs/foo/app/services/baz.rb:7:0 parse error" classname="parse_errors" file="packs/foo/app/services/baz.rb">
+      <skipped message="Parse error: unexpected END_OF_INPUT"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#
+        );
+
+        let result = CheckAllResult {
+            parse_errors: vec![parse_error],
+            fail_on_parse_errors: true,
+            ..CheckAllResult::default()
+        };
+        assert!(check_result_to_junit(&result).contains(
+            r#"<failure type="parse_error" message="Parse error: unexpected END_OF_INPUT"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_suppression_errors_to_junit() {
+        let result = CheckAllResult {
+            suppression_errors: vec![SuppressionReport {
+                file: "packs/foo/app/services/foo.rb".to_owned(),
+                line: 2,
+                violation_types: vec!["privacy".to_owned()],
+                reason: Some("legacy".to_owned()),
+                status: SuppressionStatus::Stale,
+                unused_violation_types: vec!["privacy".to_owned()],
+            }],
+            ..CheckAllResult::default()
+        };
+
+        assert_eq!(
+            check_result_to_junit(&result),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="pks" tests="1" failures="1">
+  <testsuite name="suppressions" tests="1" failures="1">
+    <testcase name="packs/foo/app/services/foo.rb:2 suppression" classname="suppressions" file="packs/foo/app/services/foo.rb">
+      <failure type="suppression" message="Stale suppression: no privacy violation is suppressed on the next line, please remove it"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#
+        );
+    }
+}
@@ -1,16 +1,142 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use super::output_helper::print_reference_location;
 use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
 use super::{CheckerInterface, ValidatorInterface};
 use crate::packs::checker::Reference;
 use crate::packs::pack::{CheckerSetting, Pack};
 use crate::packs::{Configuration, Violation};
 use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// `layers` in `packwerk.yml`, either an ordered list, where each layer may
+/// depend on the layers after it:
+/// ```yaml
+/// layers:
+///   - product
+///   - utilities
+/// ```
+/// or a graph of the layers each layer may depend on, where a layer may also
+/// depend on the layers reachable from those:
+/// ```yaml
+/// layers:
+///   web: [domain]
+///   jobs: [domain]
+///   domain: [utility]
+///   admin: [utility]
+///   utility: []
+/// ```
+/// A layer every other layer may use, like `utility`, can be listed under
+/// `'*'` instead of under each layer. It can still depend on other layers,
+/// which then may not use it:
+/// ```yaml
+/// layers:
+///   '*': [utility]
+///   web: [domain]
+///   domain: []
+///   utility: [base]
+///   base: []
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum LayersDefinition {
+    Ordered(Vec<String>),
+    Graph(BTreeMap<String, Option<Vec<String>>>),
+}
+
+impl Default for LayersDefinition {
+    fn default() -> Self {
+        LayersDefinition::Ordered(vec![])
+    }
+}
 
 #[derive(Default, Debug, Clone)]
 pub struct Layers {
     pub layers: Vec<String>,
+    // The layers each layer may depend on, when `layers` is a graph,
+    // including the shared layers
+    pub graph: Option<BTreeMap<String, Vec<String>>>,
+    // The layers listed under `'*'`
+    shared: Vec<String>,
+    // The layers reachable from each layer through the edges of the graph,
+    // computed once rather than for every reference
+    reachable: HashMap<String, HashSet<String>>,
+}
+
+impl From<LayersDefinition> for Layers {
+    fn from(definition: LayersDefinition) -> Self {
+        match definition {
+            LayersDefinition::Ordered(layers) => Layers {
+                layers,
+                ..Layers::default()
+            },
+            LayersDefinition::Graph(graph) => {
+                let mut graph: BTreeMap<String, Vec<String>> = graph
+                    .into_iter()
+                    .map(|(layer, allowed)| {
+                        (layer, allowed.unwrap_or_default())
+                    })
+                    .collect();
+                let shared = graph.remove(ALL_LAYERS).unwrap_or_default();
+                add_shared_layers(&mut graph, &shared);
+                let reachable = graph
+                    .keys()
+                    .map(|layer| (layer.clone(), reachable_from(&graph, layer)))
+                    .collect();
+                Layers {
+                    layers: graph.keys().cloned().collect(),
+                    graph: Some(graph),
+                    shared,
+                    reachable,
+                }
+            }
+        }
+    }
+}
+
+// The key under which `layers` lists the layers any layer may depend on
+const ALL_LAYERS: &str = "*";
+
+// Lets every layer depend on the shared layers, except the layers a shared
+// layer itself depends on, which would otherwise form a cycle with it.
+// Undefined shared layers are left out, `graph_errors` reports them.
+fn add_shared_layers(
+    graph: &mut BTreeMap<String, Vec<String>>,
+    shared: &[String],
+) {
+    let below_shared: Vec<(&String, HashSet<String>)> = shared
+        .iter()
+        .filter(|shared_layer| graph.contains_key(*shared_layer))
+        .map(|shared_layer| (shared_layer, reachable_from(graph, shared_layer)))
+        .collect();
+    for (layer, allowed) in graph.iter_mut() {
+        for (shared_layer, below) in &below_shared {
+            if *shared_layer != layer
+                && !below.contains(layer)
+                && !allowed.contains(shared_layer)
+            {
+                allowed.push((*shared_layer).clone());
+            }
+        }
+    }
+}
+
+// The layers `layer` may depend on, through the edges of the graph. Cycles
+// are reported by `validate`, they don't keep this from terminating.
+fn reachable_from(
+    graph: &BTreeMap<String, Vec<String>>,
+    layer: &str,
+) -> HashSet<String> {
+    let mut reachable: HashSet<String> = HashSet::new();
+    let mut to_visit: Vec<&String> =
+        graph.get(layer).into_iter().flatten().collect();
+    while let Some(next) = to_visit.pop() {
+        if reachable.insert(next.clone()) {
+            to_visit.extend(graph.get(next).into_iter().flatten());
+        }
+    }
+    reachable
 }
 
 const VIOLATION_TYPE: &str = "layer";
@@ -32,7 +158,13 @@ impl Layers {
 
         match (referencing_layer_index, defining_layer_index) {
             (Some(referencing_layer_index), Some(defining_layer_index)) => {
-                Ok(referencing_layer_index <= defining_layer_index)
+                match &self.graph {
+                    Some(_) => Ok(referencing_layer == defining_layer
+                        || self.reachable.get(referencing_layer).is_some_and(
+                            |reachable| reachable.contains(defining_layer),
+                        )),
+                    None => Ok(referencing_layer_index <= defining_layer_index),
+                }
             }
             _ => {
                 bail!("Could not find one of layer `{}` or layer `{}` in `packwerk.yml`",
@@ -41,6 +173,102 @@ impl Layers {
         }
     }
 
+    // Edges to undefined layers and cycles in the graph
+    fn graph_errors(&self) -> Vec<String> {
+        let Some(graph) = &self.graph else {
+            return vec![];
+        };
+        let mut errors: Vec<String> = vec![];
+        for shared_layer in &self.shared {
+            if !graph.contains_key(shared_layer) {
+                errors.push(format!(
+                    "Every layer may depend on `{}` in `packwerk.yml`, but `{}` is not one of the layers defined in `packwerk.yml`",
+                    shared_layer, shared_layer
+                ));
+            }
+        }
+        for (layer, allowed) in graph {
+            for allowed_layer in allowed {
+                if !graph.contains_key(allowed_layer) {
+                    errors.push(format!(
+                        "Layer `{}` may depend on `{}` in `packwerk.yml`, but `{}` is not one of the layers defined in `packwerk.yml`",
+                        layer, allowed_layer, allowed_layer
+                    ));
+                }
+            }
+        }
+
+        let mut visited: HashSet<&String> = HashSet::new();
+        for layer in graph.keys() {
+            let mut path: Vec<&String> = vec![];
+            Self::find_cycles(
+                graph,
+                layer,
+                &mut path,
+                &mut visited,
+                &mut errors,
+            );
+        }
+        errors
+    }
+
+    fn find_cycles<'a>(
+        graph: &'a BTreeMap<String, Vec<String>>,
+        layer: &'a String,
+        path: &mut Vec<&'a String>,
+        visited: &mut HashSet<&'a String>,
+        errors: &mut Vec<String>,
+    ) {
+        if let Some(start) = path.iter().position(|&l| l == layer) {
+            let cycle: Vec<String> = path[start..]
+                .iter()
+                .chain(std::iter::once(&layer))
+                .map(|l| format!("`{}`", l))
+                .collect();
+            errors.push(format!(
+                "Layers in `packwerk.yml` must not depend on each other in a cycle: {}",
+                cycle.join(" -> ")
+            ));
+            return;
+        }
+        if !visited.insert(layer) {
+            return;
+        }
+        path.push(layer);
+        for allowed_layer in graph.get(layer).into_iter().flatten() {
+            Self::find_cycles(graph, allowed_layer, path, visited, errors);
+        }
+        path.pop();
+    }
+
+    // How a layer violation can be fixed, for layers defined as a graph
+    fn allowed_layers_hint(&self, referencing_layer: &str) -> String {
+        if self.graph.is_none() {
+            return String::new();
+        }
+        let mut allowed: Vec<String> = self
+            .reachable
+            .get(referencing_layer)
+            .into_iter()
+            .flatten()
+            .filter(|layer| *layer != referencing_layer)
+            .map(|layer| format!("`{}`", layer))
+            .collect();
+        allowed.sort();
+        if allowed.is_empty() {
+            format!(
+                ". Layer `{}` may not depend on other layers",
+                referencing_layer
+            )
+        } else {
+            format!(
+                ". Layer `{}` may only depend on {}",
+                referencing_layer,
+                allowed.join(", ")
+            )
+        }
+    }
+
     fn pack_enforces_layers<'a>(&self, pack: &'a Pack) -> &'a CheckerSetting {
         match &pack.enforce_layers {
             Some(setting) => setting,
@@ -85,7 +313,7 @@ impl Checker {
 
 impl ValidatorInterface for Checker {
     fn validate(&self, configuration: &Configuration) -> Option<Vec<String>> {
-        let mut error_messages: Vec<String> = vec![];
+        let mut error_messages: Vec<String> = self.layers.graph_errors();
 
         for pack in &configuration.pack_set.packs {
             if let Some(error_message) = self.validate_pack(pack) {
@@ -111,6 +339,7 @@ impl CheckerInterface for Checker {
         reference: &Reference,
         configuration: &Configuration,
         _sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>> {
         let pack_checker =
             PackChecker::new(configuration, reference, &self.violation_type())?;
@@ -128,10 +357,14 @@ impl CheckerInterface for Checker {
                     return Ok(None);
                 }
 
+                if suppressions.suppresses(reference, &self.violation_type()) {
+                    return Ok(None);
+                }
+
                 let loc = print_reference_location(reference);
 
                 let message = format!(
-                    "{}{} violation: `{}` belongs to `{}` (whose layer is `{}`) cannot be accessed from `{}` (whose layer is `{}`)",
+                    "{}{} violation: `{}` belongs to `{}` (whose layer is `{}`) cannot be accessed from `{}` (whose layer is `{}`){}",
                     loc,
                     self.layers.violation_name(),
                     reference.constant_name,
@@ -139,6 +372,7 @@ impl CheckerInterface for Checker {
                     defining_layer,
                     pack_checker.referencing_pack.name,
                     referencing_layer,
+                    self.layers.allowed_layers_hint(referencing_layer),
                 );
 
                 Ok(Some(Violation {
@@ -177,15 +411,180 @@ mod tests {
 
     fn checker_with_layers() -> Checker {
         Checker {
-            layers: Layers {
-                layers: vec![
-                    String::from("product"),
-                    String::from("utilities"),
-                ],
-            },
+            layers: Layers::from(LayersDefinition::Ordered(vec![
+                String::from("product"),
+                String::from("utilities"),
+            ])),
         }
     }
 
+    fn layer_graph(edges: &[(&str, &[&str])]) -> Layers {
+        Layers::from(LayersDefinition::Graph(
+            edges
+                .iter()
+                .map(|(layer, allowed)| {
+                    (
+                        layer.to_string(),
+                        Some(allowed.iter().map(|l| l.to_string()).collect()),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    fn web_jobs_graph() -> Layers {
+        layer_graph(&[
+            ("web", &["domain", "utility"]),
+            ("jobs", &["domain"]),
+            ("domain", &["models"]),
+            ("models", &["utility"]),
+            ("utility", &[]),
+        ])
+    }
+
+    #[test]
+    fn test_can_depend_on_with_graph() -> anyhow::Result<()> {
+        let layers = web_jobs_graph();
+        let tests = [
+            ("web", "domain", true),
+            ("web", "web", true),
+            ("jobs", "utility", true),
+            ("domain", "utility", true),
+            ("web", "jobs", false),
+            ("jobs", "web", false),
+            ("utility", "domain", false),
+            ("models", "domain", false),
+        ];
+        for (referencing, defining, expected) in tests {
+            assert_eq!(
+                layers.can_depend_on(
+                    &referencing.to_string(),
+                    &defining.to_string()
+                )?,
+                expected,
+                "Failed for {} -> {}",
+                referencing,
+                defining
+            );
+        }
+        assert!(layers
+            .can_depend_on(&String::from("web"), &String::from("unknown"))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_errors() {
+        assert_eq!(web_jobs_graph().graph_errors(), Vec::<String>::new());
+
+        let layers = layer_graph(&[
+            ("web", &["domain"]),
+            ("domain", &["models", "web"]),
+            ("models", &["undefined"]),
+        ]);
+        assert_eq!(
+            layers.graph_errors(),
+            vec![
+                String::from("Layer `models` may depend on `undefined` in `packwerk.yml`, but `undefined` is not one of the layers defined in `packwerk.yml`"),
+                String::from("Layers in `packwerk.yml` must not depend on each other in a cycle: `domain` -> `web` -> `domain`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reachable_layers_with_graph() -> anyhow::Result<()> {
+        let layers = layer_graph(&[
+            ("web", &["domain"]),
+            ("domain", &["utility", "web"]),
+            ("admin", &["utility"]),
+            ("scripts", &[]),
+            ("utility", &[]),
+        ]);
+        let reachable = |layer: &str| {
+            let mut reachable: Vec<&String> =
+                layers.reachable[layer].iter().collect();
+            reachable.sort();
+            reachable
+        };
+        assert_eq!(reachable("web"), vec!["domain", "utility", "web"]);
+        assert_eq!(reachable("admin"), vec!["utility"]);
+        assert!(reachable("scripts").is_empty());
+        assert!(layers
+            .can_depend_on(&String::from("admin"), &String::from("utility"))?);
+        assert!(!layers.can_depend_on(
+            &String::from("scripts"),
+            &String::from("utility")
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_layers_with_graph() -> anyhow::Result<()> {
+        let layers = layer_graph(&[
+            ("*", &["utility"]),
+            ("web", &["domain"]),
+            ("domain", &[]),
+            ("utility", &["base"]),
+            ("base", &[]),
+        ]);
+        assert_eq!(layers.layers, vec!["base", "domain", "utility", "web"]);
+        assert_eq!(layers.graph_errors(), Vec::<String>::new());
+        let tests = [
+            ("web", "utility", true),
+            ("domain", "utility", true),
+            ("web", "base", true),
+            ("utility", "base", true),
+            ("base", "utility", false),
+            ("utility", "domain", false),
+        ];
+        for (referencing, defining, expected) in tests {
+            assert_eq!(
+                layers.can_depend_on(
+                    &referencing.to_string(),
+                    &defining.to_string()
+                )?,
+                expected,
+                "Failed for {} -> {}",
+                referencing,
+                defining
+            );
+        }
+
+        let layers = layer_graph(&[("*", &["undefined"]), ("web", &[])]);
+        assert_eq!(
+            layers.graph_errors(),
+            vec![String::from("Every layer may depend on `undefined` in `packwerk.yml`, but `undefined` is not one of the layers defined in `packwerk.yml`")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reference_is_a_layer_violation_with_graph() -> anyhow::Result<()> {
+        let mut test_checker = TestChecker {
+            reference: None,
+            configuration: None,
+            referenced_constant_name: Some(String::from("::Bar")),
+            defining_pack: Some(Pack {
+                name: "packs/bar".to_owned(),
+                layer: Some("web".to_string()),
+                ..default_defining_pack()
+            }),
+            referencing_pack: Pack {
+                name: "packs/foo".to_owned(),
+                enforce_layers: Some(CheckerSetting::True),
+                layer: Some("jobs".to_string()),
+                ..default_referencing_pack()
+            },
+            expected_violation: Some(build_expected_violation(
+                "packs/foo/app/services/foo.rb:3:1\nLayer violation: `::Bar` belongs to `packs/bar` (whose layer is `web`) cannot be accessed from `packs/foo` (whose layer is `jobs`). Layer `jobs` may only depend on `domain`, `models`, `utility`".to_string(),
+                "layer".to_string(), false)),
+        };
+        let checker = Checker {
+            layers: web_jobs_graph(),
+        };
+        test_check(&checker, &mut test_checker)
+    }
+
     #[test]
     fn referencing_and_defining_pack_are_identical() -> anyhow::Result<()> {
         let pack = Pack {
@@ -358,9 +757,7 @@ mod tests {
             ..Configuration::default()
         };
         let checker = Checker {
-            layers: Layers {
-                layers: config_layers,
-            },
+            layers: Layers::from(LayersDefinition::Ordered(config_layers)),
         };
         checker.validate(&configuration)
     }
@@ -477,12 +874,10 @@ mod tests {
         )
         .unwrap();
         let checker = Checker {
-            layers: Layers {
-                layers: vec![
-                    String::from("product"),
-                    String::from("utilities"),
-                ],
-            },
+            layers: Layers::from(LayersDefinition::Ordered(vec![
+                String::from("product"),
+                String::from("utilities"),
+            ])),
         };
 
         let error = checker.validate(&configuration);
@@ -1,8 +1,11 @@
 use crate::packs::{
     pack::{CheckerSetting, Pack},
+    parsing::ReferenceKind,
     Configuration,
 };
 
+use serde::Deserialize;
+
 use super::{reference::Reference, ViolationIdentifier};
 
 pub struct PackChecker<'a> {
@@ -13,8 +16,13 @@ pub struct PackChecker<'a> {
     pub reference: &'a Reference,
 }
 
-enum ViolationDirection {
+// Which pack's settings apply to a violation: the referencing pack's for
+// outgoing violations, the defining pack's for incoming ones
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ViolationDirection {
     Incoming,
+    #[default]
     Outgoing,
 }
 
@@ -24,6 +32,7 @@ pub enum ViolationType {
     FolderPrivacy,
     Layer,
     Privacy,
+    Rule,
     Visibility,
 }
 
@@ -34,6 +43,7 @@ impl From<&str> for ViolationType {
             "folder_privacy" => ViolationType::FolderPrivacy,
             "layer" => ViolationType::Layer,
             "privacy" => ViolationType::Privacy,
+            "rule" => ViolationType::Rule,
             "visibility" => ViolationType::Visibility,
             _ => panic!("unknown violation type: {}", s),
         }
@@ -47,6 +57,7 @@ impl From<ViolationType> for &str {
             ViolationType::FolderPrivacy => "folder_privacy",
             ViolationType::Layer => "layer",
             ViolationType::Privacy => "privacy",
+            ViolationType::Rule => "rule",
             ViolationType::Visibility => "visibility",
         }
     }
@@ -70,9 +81,9 @@ impl<'a> PackChecker<'a> {
 
     fn violation_direction(&self) -> ViolationDirection {
         match self.violation_type {
-            ViolationType::Dependency | ViolationType::Layer => {
-                ViolationDirection::Outgoing
-            }
+            ViolationType::Dependency
+            | ViolationType::Layer
+            | ViolationType::Rule => ViolationDirection::Outgoing,
             ViolationType::Privacy
             | ViolationType::FolderPrivacy
             | ViolationType::Visibility => ViolationDirection::Incoming,
@@ -92,6 +103,9 @@ impl<'a> PackChecker<'a> {
         if self.violation_globally_disabled() {
             return Ok(false);
         }
+        if !self.reference_kind_enforced() {
+            return Ok(false);
+        }
         if self.is_ignored()? {
             return Ok(false);
         }
@@ -123,6 +137,7 @@ impl<'a> PackChecker<'a> {
             ViolationType::Privacy => {
                 self.checker_setting_for(&self.rules_pack().enforce_privacy)
             }
+            ViolationType::Rule => self.rules_pack().enforce_rules(),
             ViolationType::Visibility => {
                 self.checker_setting_for(&self.rules_pack().enforce_visibility)
             }
@@ -141,12 +156,27 @@ impl<'a> PackChecker<'a> {
             ViolationType::Privacy => {
                 self.configuration.disable_enforce_privacy
             }
+            ViolationType::Rule => false,
             ViolationType::Visibility => {
                 self.configuration.disable_enforce_visibility
             }
         }
     }
 
+    // String references are only checked by the checkers listed in
+    // `string_reference_enforcements`
+    fn reference_kind_enforced(&self) -> bool {
+        match self.reference.kind {
+            ReferenceKind::Constant => true,
+            ReferenceKind::String => {
+                let violation_type: &str = self.violation_type.into();
+                self.configuration
+                    .string_reference_enforcements
+                    .contains(violation_type)
+            }
+        }
+    }
+
     fn checker_setting_for(
         &self,
         checker_setting: &'a Option<CheckerSetting>,
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use super::output_helper::print_reference_location;
 use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
 use super::CheckerInterface;
 use crate::packs::checker::Reference;
 use crate::packs::parsing::ruby;
@@ -15,6 +16,7 @@ impl CheckerInterface for Checker {
         reference: &Reference,
         configuration: &Configuration,
         sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>> {
         let pack_checker =
             PackChecker::new(configuration, reference, &self.violation_type())?;
@@ -105,6 +107,10 @@ impl CheckerInterface for Checker {
         // Inference details: this is a reference to ::Constant which seems to be defined in packs/defining_pack/path/to/definition.rb.
         // To receive help interpreting or resolving this error message, see: https://github.com/Shopify/packwerk/blob/main/TROUBLESHOOT.md#Troubleshooting-violations
         // END: Original packwerk message
+        if suppressions.suppresses(reference, &self.violation_type()) {
+            return Ok(None);
+        }
+
         let loc = print_reference_location(reference);
 
         let message = format!(
@@ -138,6 +144,7 @@ mod tests {
             TestChecker,
         },
         pack::EnforcementGlobsIgnore,
+        parsing::ReferenceKind,
     };
 
     use super::*;
@@ -293,6 +300,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -321,6 +329,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -350,6 +359,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -380,6 +390,7 @@ mod tests {
                     "packs/bar/app/services/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -413,6 +424,7 @@ mod tests {
                     "packs/bar/app/services/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -449,6 +461,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -482,6 +495,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -515,6 +529,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: Some(Pack {
@@ -547,6 +562,7 @@ mod tests {
                     "packs/bar/app/public/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             }),
             configuration: None,
             defining_pack: None,
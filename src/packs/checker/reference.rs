@@ -1,13 +1,16 @@
 use std::path::Path;
 
 use anyhow::{bail, Context};
+use serde::Serialize;
 
 use crate::packs::{
-    constant_resolver::ConstantResolver, pack::Pack,
-    parsing::UnresolvedReference, Configuration, PackSet, SourceLocation,
+    constant_resolver::ConstantResolver,
+    pack::Pack,
+    parsing::{ReferenceKind, UnresolvedReference},
+    Configuration, PackSet, SourceLocation,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Reference {
     pub constant_name: String,
     pub defining_pack_name: Option<String>,
@@ -15,6 +18,7 @@ pub struct Reference {
     pub referencing_pack_name: String,
     pub relative_referencing_file: String,
     pub source_location: SourceLocation,
+    pub kind: ReferenceKind,
 }
 
 impl Reference {
@@ -116,6 +120,7 @@ impl Reference {
                             .clone(),
                         source_location: source_location.clone(),
                         relative_defining_file,
+                        kind: unresolved_reference.kind,
                     })
                 })
                 .collect::<anyhow::Result<Vec<Reference>>>()?)
@@ -132,6 +137,7 @@ impl Reference {
                 relative_referencing_file,
                 source_location,
                 relative_defining_file,
+                kind: unresolved_reference.kind,
             }])
         }
     }
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+
+use super::output_helper::print_reference_location;
+use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
+use super::CheckerInterface;
+use crate::packs::checker::Reference;
+use crate::packs::{Configuration, Violation};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePolicy {
+    // Only packs matching `from` may depend on packs matching `to`
+    Allow,
+    // Packs matching `from` may not depend on packs matching `to`
+    Deny,
+}
+
+/// A dependency rule in `packwerk.yml`, e.g.
+/// rules:
+///   - from: packs/domains/**
+///     to: packs/apps/**
+///     policy: deny
+///     message: Domains can't depend on apps
+///
+/// `from` and `to` are globs of pack names, where `*` doesn't match `/` and
+/// `**` matches nested packs. An `allow` rule also exempts the packs it
+/// matches from `deny` rules.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub from: String,
+    pub to: String,
+    pub policy: RulePolicy,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl Rule {
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        for glob in [&self.from, &self.to] {
+            glob_matcher(glob)
+                .context(format!("Invalid glob in rules: `{}`", glob))?;
+        }
+        Ok(())
+    }
+
+    fn message(&self) -> String {
+        match &self.message {
+            Some(message) => message.clone(),
+            None => match self.policy {
+                RulePolicy::Allow => format!(
+                    "only packs matching `{}` may depend on packs matching `{}`",
+                    self.from, self.to
+                ),
+                RulePolicy::Deny => format!(
+                    "packs matching `{}` may not depend on packs matching `{}`",
+                    self.from, self.to
+                ),
+            },
+        }
+    }
+}
+
+fn glob_matcher(glob: &str) -> anyhow::Result<GlobMatcher> {
+    Ok(GlobBuilder::new(glob)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
+}
+
+struct CompiledRule {
+    rule: Rule,
+    from: GlobMatcher,
+    to: GlobMatcher,
+}
+
+impl CompiledRule {
+    fn matches_to(&self, to: &str) -> bool {
+        self.to.is_match(to)
+    }
+
+    fn matches(&self, from: &str, to: &str) -> bool {
+        self.from.is_match(from) && self.matches_to(to)
+    }
+}
+
+pub struct Checker {
+    allow_rules: Vec<CompiledRule>,
+    deny_rules: Vec<CompiledRule>,
+}
+
+impl Checker {
+    // Rules are validated when the configuration is loaded, rules with
+    // invalid globs are skipped
+    pub fn new(rules: &[Rule]) -> Self {
+        let (allow_rules, deny_rules) = rules
+            .iter()
+            .filter_map(|rule| {
+                Some(CompiledRule {
+                    rule: rule.clone(),
+                    from: glob_matcher(&rule.from).ok()?,
+                    to: glob_matcher(&rule.to).ok()?,
+                })
+            })
+            .partition(|rule| rule.rule.policy == RulePolicy::Allow);
+        Self {
+            allow_rules,
+            deny_rules,
+        }
+    }
+
+    // The rule a dependency from `from` on `to` breaks, if any
+    fn broken_rule(&self, from: &str, to: &str) -> Option<&Rule> {
+        if self.allow_rules.iter().any(|rule| rule.matches(from, to)) {
+            return None;
+        }
+        self.deny_rules
+            .iter()
+            .find(|rule| rule.matches(from, to))
+            .or_else(|| {
+                self.allow_rules.iter().find(|rule| rule.matches_to(to))
+            })
+            .map(|rule| &rule.rule)
+    }
+}
+
+impl CheckerInterface for Checker {
+    fn check(
+        &self,
+        reference: &Reference,
+        configuration: &Configuration,
+        _sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
+    ) -> anyhow::Result<Option<Violation>> {
+        if self.allow_rules.is_empty() && self.deny_rules.is_empty() {
+            return Ok(None);
+        }
+        let pack_checker =
+            PackChecker::new(configuration, reference, &self.violation_type())?;
+        if !pack_checker.checkable()? {
+            return Ok(None);
+        }
+        let defining_pack = pack_checker.defining_pack.unwrap();
+
+        let Some(rule) = self.broken_rule(
+            &pack_checker.referencing_pack.name,
+            &defining_pack.name,
+        ) else {
+            return Ok(None);
+        };
+
+        if suppressions.suppresses(reference, &self.violation_type()) {
+            return Ok(None);
+        }
+
+        let loc = print_reference_location(reference);
+
+        let message = format!(
+            "{}Rule violation: `{}` belongs to `{}`, which cannot be accessed from `{}`: {}",
+            loc,
+            reference.constant_name,
+            defining_pack.name,
+            pack_checker.referencing_pack.name,
+            rule.message(),
+        );
+
+        Ok(Some(Violation {
+            message,
+            identifier: pack_checker.violation_identifier(),
+            source_location: reference.source_location.clone(),
+        }))
+    }
+
+    fn violation_type(&self) -> String {
+        "rule".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use self::packs::{
+        checker::common_test::tests::{
+            build_expected_violation, test_check, TestChecker,
+        },
+        pack::{CheckerSetting, Pack},
+    };
+
+    use super::*;
+    use crate::packs::{self};
+
+    fn rule(from: &str, to: &str, policy: RulePolicy) -> Rule {
+        Rule {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            policy,
+            message: None,
+        }
+    }
+
+    fn rules() -> Vec<Rule> {
+        vec![
+            rule("packs/domains/**", "packs/apps/**", RulePolicy::Deny),
+            rule("packs/platform/*", "packs/infra/secrets", RulePolicy::Allow),
+        ]
+    }
+
+    #[test]
+    fn test_broken_rule() {
+        let rules = rules();
+        let checker = Checker::new(&rules);
+        let tests = [
+            ("packs/domains/billing", "packs/apps/admin", Some(0)),
+            (
+                "packs/domains/billing/invoices",
+                "packs/apps/web/ui",
+                Some(0),
+            ),
+            ("packs/apps/admin", "packs/domains/billing", None),
+            ("packs/platform/auth", "packs/infra/secrets", None),
+            ("packs/platform/auth/keys", "packs/infra/secrets", Some(1)),
+            ("packs/domains/billing", "packs/infra/secrets", Some(1)),
+            ("packs/domains/billing", "packs/infra/logging", None),
+        ];
+        for (from, to, expected) in tests {
+            assert_eq!(
+                checker.broken_rule(from, to),
+                expected.map(|index| &rules[index]),
+                "Failed for {} -> {}",
+                from,
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn test_allow_rule_exempts_from_deny_rule() {
+        let checker = Checker::new(&[
+            rule("packs/domains/**", "packs/apps/**", RulePolicy::Deny),
+            rule(
+                "packs/domains/legacy",
+                "packs/apps/admin",
+                RulePolicy::Allow,
+            ),
+        ]);
+        assert_eq!(
+            checker.broken_rule("packs/domains/legacy", "packs/apps/admin"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rule_violation() -> anyhow::Result<()> {
+        let mut expected_violation = build_expected_violation(
+            String::from("packs/foo/app/services/foo.rb:3:1\nRule violation: `::Bar` belongs to `packs/apps/admin`, which cannot be accessed from `packs/domains/billing`: packs matching `packs/domains/**` may not depend on packs matching `packs/apps/**`"),
+            String::from("rule"),
+            true,
+        );
+        expected_violation.identifier.referencing_pack_name =
+            String::from("packs/domains/billing");
+        expected_violation.identifier.defining_pack_name =
+            String::from("packs/apps/admin");
+        let mut test_checker = TestChecker {
+            reference: None,
+            configuration: None,
+            referenced_constant_name: Some(String::from("::Bar")),
+            defining_pack: Some(Pack {
+                name: "packs/apps/admin".to_owned(),
+                ..Pack::default()
+            }),
+            referencing_pack: Pack {
+                name: "packs/domains/billing".to_owned(),
+                enforce_rules: Some(CheckerSetting::Strict),
+                ..Pack::default()
+            },
+            expected_violation: Some(expected_violation),
+        };
+        test_check(&Checker::new(&rules()), &mut test_checker)
+    }
+
+    #[test]
+    fn test_rules_disabled_for_pack() -> anyhow::Result<()> {
+        let mut test_checker = TestChecker {
+            reference: None,
+            configuration: None,
+            referenced_constant_name: Some(String::from("::Bar")),
+            defining_pack: Some(Pack {
+                name: "packs/apps/admin".to_owned(),
+                ..Pack::default()
+            }),
+            referencing_pack: Pack {
+                name: "packs/domains/billing".to_owned(),
+                enforce_rules: Some(CheckerSetting::False),
+                ..Pack::default()
+            },
+            expected_violation: None,
+        };
+        test_check(&Checker::new(&rules()), &mut test_checker)
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let rule = rule("packs/[domains", "packs/apps/**", RulePolicy::Deny);
+        assert!(rule.validate().is_err());
+    }
+}
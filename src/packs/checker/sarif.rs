@@ -0,0 +1,420 @@
+// Maps check results and validation errors onto SARIF 2.1.0, the format
+// code-scanning dashboards and review tools ingest.
+// See https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+use serde::Serialize;
+
+use super::{
+    build_stale_violation_message, build_strict_violation_message,
+    CheckAllResult, FileParseError, SuppressionReport, ValidationError,
+    Violation, ViolationIdentifier,
+};
+use crate::packs::package_todo::package_todo_relative_path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+    invocations: Vec<Invocation>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    // Suppression comments have no column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<Notification>,
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+    descriptor: Descriptor,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Descriptor {
+    id: String,
+}
+
+pub(crate) fn check_result_to_sarif(
+    result: &CheckAllResult,
+) -> serde_json::Result<String> {
+    let mut sorted_violations: Vec<&Violation> =
+        result.reportable_violations.iter().collect();
+    sorted_violations.sort_by(|a, b| a.message.cmp(&b.message));
+
+    let mut results: Vec<SarifResult> = sorted_violations
+        .into_iter()
+        .map(violation_result)
+        .collect();
+    results.extend(result.strict_mode_violations.iter().map(|identifier| {
+        SarifResult {
+            rule_id: identifier.violation_type.clone(),
+            level: "error",
+            message: Message {
+                text: build_strict_violation_message(identifier),
+            },
+            locations: vec![location(identifier.file.clone(), None)],
+        }
+    }));
+    results.extend(result.stale_violations.iter().map(stale_result));
+    results.extend(result.parse_errors.iter().map(|parse_error| {
+        parse_error_result(parse_error, result.fail_on_parse_errors)
+    }));
+    results.extend(result.suppression_errors.iter().map(suppression_result));
+
+    to_sarif(results, vec![])
+}
+
+pub(crate) fn validation_errors_to_sarif(
+    validation_errors: &[ValidationError],
+) -> serde_json::Result<String> {
+    let notifications = validation_errors
+        .iter()
+        .map(|validation_error| Notification {
+            level: "error",
+            message: Message {
+                text: validation_error.message.clone(),
+            },
+            descriptor: Descriptor {
+                id: validation_error.error_type.clone(),
+            },
+            locations: validation_error
+                .file
+                .iter()
+                .map(|file| location(file.clone(), None))
+                .collect(),
+        })
+        .collect();
+
+    to_sarif(vec![], notifications)
+}
+
+fn to_sarif(
+    results: Vec<SarifResult>,
+    notifications: Vec<Notification>,
+) -> serde_json::Result<String> {
+    let log = SarifLog {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "pks",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: "https://github.com/alexevanczuk/packs",
+                },
+            },
+            results,
+            invocations: vec![Invocation {
+                execution_successful: notifications.is_empty(),
+                tool_execution_notifications: notifications,
+            }],
+        }],
+    };
+    serde_json::to_string(&log)
+}
+
+// Violations in strict packs fail `check` outright, the others can still be
+// recorded in package_todo.yml
+fn violation_result(violation: &Violation) -> SarifResult {
+    let message =
+        String::from_utf8_lossy(&strip_ansi_escapes::strip(&violation.message))
+            .to_string();
+    SarifResult {
+        rule_id: violation.identifier.violation_type.clone(),
+        level: if violation.identifier.strict {
+            "error"
+        } else {
+            "warning"
+        },
+        message: Message { text: message },
+        locations: vec![location(
+            violation.identifier.file.clone(),
+            Some(Region {
+                start_line: violation.source_location.line,
+                // SARIF columns are 1-based
+                start_column: Some(violation.source_location.column + 1),
+            }),
+        )],
+    }
+}
+
+fn stale_result(identifier: &ViolationIdentifier) -> SarifResult {
+    SarifResult {
+        rule_id: "stale_violation".to_owned(),
+        level: "warning",
+        message: Message {
+            text: build_stale_violation_message(identifier),
+        },
+        locations: vec![location(
+            package_todo_relative_path(&identifier.referencing_pack_name),
+            None,
+        )],
+    }
+}
+
+// Parse errors only fail `check` with `fail_on_parse_errors`
+fn parse_error_result(
+    parse_error: &FileParseError,
+    fail_on_parse_errors: bool,
+) -> SarifResult {
+    SarifResult {
+        rule_id: "parse_error".to_owned(),
+        level: if fail_on_parse_errors {
+            "error"
+        } else {
+            "warning"
+        },
+        message: Message {
+            text: format!("Parse error: {}", parse_error.message),
+        },
+        locations: vec![location(
+            parse_error.file.clone(),
+            Some(Region {
+                start_line: parse_error.source_location.line,
+                start_column: Some(parse_error.source_location.column + 1),
+            }),
+        )],
+    }
+}
+
+fn suppression_result(suppression: &SuppressionReport) -> SarifResult {
+    SarifResult {
+        rule_id: "suppression".to_owned(),
+        level: "error",
+        message: Message {
+            text: suppression.message(),
+        },
+        locations: vec![location(
+            suppression.file.clone(),
+            Some(Region {
+                start_line: suppression.line,
+                start_column: None,
+            }),
+        )],
+    }
+}
+
+fn location(uri: String, region: Option<Region>) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation { uri },
+            region,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::checker::common_test::tests::{
+        build_check_all_result, build_violation_identifier,
+    };
+    use crate::packs::checker::SuppressionStatus;
+    use crate::packs::SourceLocation;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_check_result_to_sarif() {
+        let result = build_check_all_result(
+            "\u{1b}[36mpacks/foo/app/services/foo.rb\u{1b}[0m:3:4\nPrivacy violation",
+            build_violation_identifier("privacy", true),
+            vec![build_violation_identifier("privacy", false)],
+            vec![],
+        );
+
+        let sarif: Value =
+            serde_json::from_str(&check_result_to_sarif(&result).unwrap())
+                .unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "pks");
+        assert_eq!(
+            sarif["runs"][0]["results"],
+            json!([
+                {
+                    "ruleId": "privacy",
+                    "level": "error",
+                    "message": {"text": "packs/foo/app/services/foo.rb:3:4\nPrivacy violation"},
+                    "locations": [{"physicalLocation": {
+                        "artifactLocation": {"uri": "packs/foo/app/services/foo.rb"},
+                        "region": {"startLine": 3, "startColumn": 5}
+                    }}]
+                },
+                {
+                    "ruleId": "stale_violation",
+                    "level": "warning",
+                    "message": {"text": "Stale privacy violation of `::Bar` in packs/foo/app/services/foo.rb is no longer found, please run `packs update`"},
+                    "locations": [{"physicalLocation": {
+                        "artifactLocation": {"uri": "packs/foo/package_todo.yml"}
+                    }}]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_to_sarif() {
+        for (fail_on_parse_errors, level) in
+            [(false, "warning"), (true, "error")]
+        {
+            let result = CheckAllResult {
+                parse_errors: vec![FileParseError {
+                    file: "packs/foo/app/services/baz.rb".to_owned(),
+                    message: "unexpected END_OF_INPUT".to_owned(),
+                    source_location: SourceLocation { line: 7, column: 0 },
+                }],
+                fail_on_parse_errors,
+                ..CheckAllResult::default()
+            };
+
+            let sarif: Value =
+                serde_json::from_str(&check_result_to_sarif(&result).unwrap())
+                    .unwrap();
+
+            assert_eq!(
+                sarif["runs"][0]["results"],
+                json!([{
+                    "ruleId": "parse_error",
+                    "level": level,
+                    "message": {"text": "Parse error: unexpected END_OF_INPUT"},
+                    "locations": [{"physicalLocation": {
+                        "artifactLocation": {"uri": "packs/foo/app/services/baz.rb"},
+                        "region": {"startLine": 7, "startColumn": 1}
+                    }}]
+                }])
+            );
+        }
+    }
+
+    #[test]
+    fn test_suppression_errors_to_sarif() {
+        let result = CheckAllResult {
+            suppression_errors: vec![SuppressionReport {
+                file: "packs/foo/app/services/foo.rb".to_owned(),
+                line: 2,
+                violation_types: vec!["privacy".to_owned()],
+                reason: Some("legacy".to_owned()),
+                status: SuppressionStatus::Stale,
+                unused_violation_types: vec!["privacy".to_owned()],
+            }],
+            ..CheckAllResult::default()
+        };
+
+        let sarif: Value =
+            serde_json::from_str(&check_result_to_sarif(&result).unwrap())
+                .unwrap();
+
+        assert_eq!(
+            sarif["runs"][0]["results"],
+            json!([{
+                "ruleId": "suppression",
+                "level": "error",
+                "message": {"text": "Stale suppression: no privacy violation is suppressed on the next line, please remove it"},
+                "locations": [{"physicalLocation": {
+                    "artifactLocation": {"uri": "packs/foo/app/services/foo.rb"},
+                    "region": {"startLine": 2}
+                }}]
+            }])
+        );
+    }
+
+    #[test]
+    fn test_validation_errors_to_sarif() {
+        let sarif: Value = serde_json::from_str(
+            &validation_errors_to_sarif(&[ValidationError {
+                error_type: "cycle".to_owned(),
+                message: "Cycle detected".to_owned(),
+                cycle_edges: None,
+                file: Some("packs/foo/package.yml".to_owned()),
+            }])
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(sarif["runs"][0]["results"], json!([]));
+        assert_eq!(
+            sarif["runs"][0]["invocations"],
+            json!([{
+                "executionSuccessful": false,
+                "toolExecutionNotifications": [{
+                    "level": "error",
+                    "message": {"text": "Cycle detected"},
+                    "descriptor": {"id": "cycle"},
+                    "locations": [{"physicalLocation": {
+                        "artifactLocation": {"uri": "packs/foo/package.yml"}
+                    }}]
+                }]
+            }])
+        );
+    }
+}
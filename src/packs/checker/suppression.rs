@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+use super::reference::Reference;
+use crate::packs::{Configuration, ProcessedFile, Suppression};
+
+#[derive(PartialEq, Clone, Copy, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionStatus {
+    // Every listed violation type suppressed a violation
+    Used,
+    // Some listed violation types didn't suppress any violation
+    Stale,
+    // Suppressions without a reason are not honored
+    MissingReason,
+}
+
+/// An inline suppression comment and what it suppressed during a check
+#[derive(PartialEq, Clone, Eq, Hash, Debug, Serialize)]
+pub struct SuppressionReport {
+    pub file: String,
+    pub line: usize,
+    pub violation_types: Vec<String>,
+    pub reason: Option<String>,
+    pub status: SuppressionStatus,
+    pub unused_violation_types: Vec<String>,
+}
+
+impl SuppressionReport {
+    pub fn message(&self) -> String {
+        match self.status {
+            SuppressionStatus::Used => format!(
+                "Suppresses {} violations: {}",
+                self.violation_types.join(", "),
+                self.reason.as_deref().unwrap_or_default()
+            ),
+            SuppressionStatus::Stale => format!(
+                "Stale suppression: no {} violation is suppressed on the next line, please remove it",
+                self.unused_violation_types.join(", ")
+            ),
+            SuppressionStatus::MissingReason => format!(
+                "Suppression of {} violations is missing a reason, e.g. `# packs:disable-next-line {} -- reason: ...`",
+                self.violation_types.join(", "),
+                self.violation_types.join(",")
+            ),
+        }
+    }
+}
+
+impl Display for SuppressionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\x1b[36m{}\x1b[0m:{}\n{}",
+            self.file,
+            self.line,
+            self.message()
+        )
+    }
+}
+
+#[derive(Debug)]
+struct TrackedSuppression {
+    suppression: Suppression,
+    // One flag per violation type, set once it suppressed a violation
+    used: Vec<AtomicBool>,
+}
+
+/// The inline suppressions of the checked files. Checkers share it across
+/// threads and mark the suppressions they honor as used, so that the unused
+/// ones can be reported as stale afterwards.
+#[derive(Debug, Default)]
+pub(crate) struct Suppressions {
+    // By path of the file, relative to the root
+    by_file: HashMap<String, Vec<TrackedSuppression>>,
+}
+
+impl Suppressions {
+    pub(crate) fn from_processed_files(
+        configuration: &Configuration,
+        processed_files: &[&ProcessedFile],
+    ) -> Self {
+        let by_file = processed_files
+            .iter()
+            .filter(|processed_file| !processed_file.suppressions.is_empty())
+            .map(|processed_file| {
+                let file = processed_file
+                    .absolute_path
+                    .strip_prefix(&configuration.absolute_root)
+                    .unwrap_or(&processed_file.absolute_path)
+                    .to_string_lossy()
+                    .to_string();
+                let suppressions = processed_file
+                    .suppressions
+                    .iter()
+                    .map(|suppression| TrackedSuppression {
+                        suppression: suppression.clone(),
+                        used: suppression
+                            .violation_types
+                            .iter()
+                            .map(|_| AtomicBool::new(false))
+                            .collect(),
+                    })
+                    .collect();
+                (file, suppressions)
+            })
+            .collect();
+        Self { by_file }
+    }
+
+    /// Whether a `violation_type` violation by `reference` is suppressed by
+    /// a comment on the line before it, which is then marked as used
+    pub(crate) fn suppresses(
+        &self,
+        reference: &Reference,
+        violation_type: &str,
+    ) -> bool {
+        self.suppresses_at(
+            &reference.relative_referencing_file,
+            reference.source_location.line,
+            violation_type,
+        )
+    }
+
+    /// Like `suppresses`, for a violation at `line` of `file`, relative to
+    /// the root
+    pub(crate) fn suppresses_at(
+        &self,
+        file: &str,
+        line: usize,
+        violation_type: &str,
+    ) -> bool {
+        let Some(suppressions) = self.by_file.get(file) else {
+            return false;
+        };
+
+        let mut suppressed = false;
+        for tracked in suppressions.iter().filter(|tracked| {
+            tracked.suppression.reason.is_some()
+                && tracked.suppression.line + 1 == line
+        }) {
+            let suppression = &tracked.suppression;
+            if let Some(index) = suppression
+                .violation_types
+                .iter()
+                .position(|suppressed_type| suppressed_type == violation_type)
+            {
+                tracked.used[index].store(true, Ordering::Relaxed);
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Every suppression, sorted by file and line
+    pub(crate) fn reports(&self) -> Vec<SuppressionReport> {
+        let mut reports: Vec<SuppressionReport> = self
+            .by_file
+            .iter()
+            .flat_map(|(file, suppressions)| {
+                suppressions.iter().map(move |tracked| {
+                    let suppression = &tracked.suppression;
+                    let unused_violation_types: Vec<String> = suppression
+                        .violation_types
+                        .iter()
+                        .zip(&tracked.used)
+                        .filter(|(_, used)| !used.load(Ordering::Relaxed))
+                        .map(|(violation_type, _)| violation_type.clone())
+                        .collect();
+                    let status = if suppression.reason.is_none() {
+                        SuppressionStatus::MissingReason
+                    } else if !unused_violation_types.is_empty()
+                        || suppression.violation_types.is_empty()
+                    {
+                        SuppressionStatus::Stale
+                    } else {
+                        SuppressionStatus::Used
+                    };
+                    SuppressionReport {
+                        file: file.clone(),
+                        line: suppression.line,
+                        violation_types: suppression.violation_types.clone(),
+                        reason: suppression.reason.clone(),
+                        status,
+                        unused_violation_types,
+                    }
+                })
+            })
+            .collect();
+        reports.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::packs::parsing::ReferenceKind;
+    use crate::packs::SourceLocation;
+    use pretty_assertions::assert_eq;
+
+    fn reference(line: usize) -> Reference {
+        Reference {
+            constant_name: String::from("::Bar"),
+            defining_pack_name: Some(String::from("packs/bar")),
+            relative_defining_file: Some(String::from(
+                "packs/bar/app/services/bar.rb",
+            )),
+            referencing_pack_name: String::from("packs/foo"),
+            relative_referencing_file: String::from(
+                "packs/foo/app/services/foo.rb",
+            ),
+            source_location: SourceLocation { line, column: 4 },
+            kind: ReferenceKind::Constant,
+        }
+    }
+
+    fn suppressions(suppressions: Vec<Suppression>) -> Suppressions {
+        let configuration = Configuration {
+            absolute_root: PathBuf::from("/app"),
+            ..Configuration::default()
+        };
+        let processed_file = ProcessedFile {
+            absolute_path: PathBuf::from("/app/packs/foo/app/services/foo.rb"),
+            unresolved_references: vec![],
+            definitions: vec![],
+            sigils: vec![],
+            suppressions,
+            parse_errors: vec![],
+        };
+        Suppressions::from_processed_files(&configuration, &[&processed_file])
+    }
+
+    #[test]
+    fn test_suppresses_next_line() {
+        let suppressions = suppressions(vec![Suppression {
+            line: 2,
+            violation_types: vec![
+                String::from("dependency"),
+                String::from("privacy"),
+            ],
+            reason: Some(String::from("legacy billing shim")),
+        }]);
+
+        assert!(!suppressions.suppresses(&reference(2), "dependency"));
+        assert!(!suppressions.suppresses(&reference(4), "dependency"));
+        assert!(!suppressions.suppresses(&reference(3), "layer"));
+        assert!(suppressions.suppresses(&reference(3), "dependency"));
+
+        let reports = suppressions.reports();
+        assert_eq!(SuppressionStatus::Stale, reports[0].status);
+        assert_eq!(vec!["privacy"], reports[0].unused_violation_types);
+
+        assert!(suppressions.suppresses(&reference(3), "privacy"));
+        assert_eq!(SuppressionStatus::Used, suppressions.reports()[0].status);
+    }
+
+    #[test]
+    fn test_requires_a_reason() {
+        let suppressions = suppressions(vec![Suppression {
+            line: 2,
+            violation_types: vec![String::from("dependency")],
+            reason: None,
+        }]);
+
+        assert!(!suppressions.suppresses(&reference(3), "dependency"));
+        assert_eq!(
+            SuppressionStatus::MissingReason,
+            suppressions.reports()[0].status
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 
 use super::output_helper::print_reference_location;
 use super::pack_checker::PackChecker;
+use super::suppression::Suppressions;
 use super::CheckerInterface;
 use crate::packs::checker::Reference;
 use crate::packs::{Configuration, Violation};
@@ -17,6 +18,7 @@ impl CheckerInterface for Checker {
         reference: &Reference,
         configuration: &Configuration,
         _sigils: &HashMap<std::path::PathBuf, Vec<crate::packs::Sigil>>,
+        suppressions: &Suppressions,
     ) -> anyhow::Result<Option<Violation>> {
         let pack_checker =
             PackChecker::new(configuration, reference, &self.violation_type())?;
@@ -33,6 +35,10 @@ impl CheckerInterface for Checker {
             return Ok(None);
         }
 
+        if suppressions.suppresses(reference, &self.violation_type()) {
+            return Ok(None);
+        }
+
         let loc = print_reference_location(reference);
 
         let message = format!(
@@ -1,9 +1,14 @@
 use crate::packs;
 
+use crate::packs::caching::content_addressed_cache::parse_size;
 use crate::packs::file_utils::get_absolute_path;
+use crate::packs::graph::{GraphFormat, GraphOptions};
+use crate::packs::metrics::MetricsFormat;
+use crate::packs::{OutputFormat, ValidateFormat};
 use clap::{Parser, Subcommand};
 use clap_derive::Args;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::debug;
 
 use super::logger::install_logger;
@@ -80,10 +85,19 @@ enum Command {
         #[arg(long)]
         ignore_recorded_violations: bool,
 
-        /// Output results as JSON
-        #[arg(long)]
+        /// Output results as JSON (same as `--format json`)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
 
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Check the files changed since this git revision, plus the files
+        /// those changes may affect
+        #[arg(long, conflicts_with = "files")]
+        since: Option<String>,
+
         files: Vec<String>,
     },
 
@@ -93,10 +107,14 @@ enum Command {
         #[arg(long)]
         ignore_recorded_violations: bool,
 
-        /// Output results as JSON
-        #[arg(long)]
+        /// Output results as JSON (same as `--format json`)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
 
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
         file: String,
     },
 
@@ -126,9 +144,13 @@ enum Command {
 
     #[clap(about = "Look for validation errors in the codebase")]
     Validate {
-        /// Output results as JSON
-        #[arg(long)]
+        /// Output results as JSON (same as `--format json`)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ValidateFormat::Text)]
+        format: ValidateFormat,
     },
 
     #[clap(about = "Add a dependency from one pack to another")]
@@ -172,6 +194,12 @@ enum Command {
     )]
     DeleteCache,
 
+    #[clap(about = "Manage the cache")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+
     #[clap(
         about = "List packs based on configuration in packwerk.yml (for debugging purposes)"
     )]
@@ -183,6 +211,36 @@ enum Command {
         pack: String,
     },
 
+    #[clap(
+        about = "Export the pack dependency graph, including dependencies recorded in package_todo.yml files"
+    )]
+    Graph {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+
+        /// Only show this pack and the packs around it
+        #[arg(long)]
+        focus: Option<String>,
+
+        /// How many edges away from the focused pack to go
+        #[arg(long, default_value_t = 1, requires = "focus")]
+        depth: usize,
+
+        /// Merge the packs in this directory into a single node (repeatable)
+        #[arg(long)]
+        collapse: Vec<String>,
+    },
+
+    #[clap(
+        about = "Report coupling, constant and todo violation metrics per pack"
+    )]
+    Metrics {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = MetricsFormat::Table)]
+        format: MetricsFormat,
+    },
+
     #[clap(
         about = "List analyzed files based on configuration in packwerk.yml (for debugging purposes)"
     )]
@@ -198,6 +256,11 @@ enum Command {
     )]
     ListReferences(ListReferencesArgs),
 
+    #[clap(
+        about = "List inline `# packs:disable-next-line` suppressions, and whether they are stale or missing a reason"
+    )]
+    ListSuppressions(ListSuppressionsArgs),
+
     #[clap(about = "Print the path to the package.yml that owns a file")]
     ForFile {
         /// The file to find the owning package.yml for
@@ -223,10 +286,57 @@ enum Command {
         paths: Vec<String>,
     },
 
+    #[clap(
+        about = "Keep packs in memory and answer JSON requests (one per line) over stdio or a Unix socket, re-checking changed files incrementally"
+    )]
+    Server {
+        /// Listen on this Unix socket instead of stdio
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
+    #[clap(
+        about = "Run a language server over stdio, publishing violations as diagnostics"
+    )]
+    Lsp,
+
     #[clap(about = "Upgrade pks to the latest version via cargo install")]
     Upgrade,
 }
 
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    #[clap(
+        about = "Show the number of entries and size on disk per cache directory, and the hit rate of the last full run"
+    )]
+    Stats,
+
+    #[clap(
+        about = "Re-parse a sample of files and compare the results with their cache entries"
+    )]
+    Verify {
+        /// Number of files to sample
+        #[arg(long, default_value_t = 100)]
+        sample: usize,
+    },
+
+    #[clap(about = "Fill the cache for all included files")]
+    Warm,
+
+    #[clap(
+        about = "Remove the entries of files that are no longer included, or garbage collect the content-addressed cache with --max-size/--max-age (least recently used entries first)"
+    )]
+    Prune {
+        /// Remove content-addressed entries until the cache takes at most this much space, e.g. `500M` or `2G`
+        #[arg(long, value_parser = parse_size)]
+        max_size: Option<u64>,
+
+        /// Remove content-addressed entries that were not used for this long, e.g. `30d` or `12h`
+        #[arg(long, value_parser = humantime::parse_duration)]
+        max_age: Option<Duration>,
+    },
+}
+
 #[derive(Debug, Args)]
 struct ListDefinitionsArgs {
     /// Show constants with multiple definitions only
@@ -245,6 +355,13 @@ struct ListReferencesArgs {
     out: Option<PathBuf>,
 }
 
+#[derive(Debug, Args)]
+struct ListSuppressionsArgs {
+    /// Output format: 'json' or 'text'
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
 #[derive(Debug, Args)]
 struct ExposeMonkeyPatchesArgs {
     /// An absolute path to the directory containing Ruby source code (for extracting definitions from Ruby stdlib)
@@ -353,8 +470,10 @@ pub fn run() -> anyhow::Result<()> {
 
     match args.command {
         Command::All => {
-            let check_result = packs::check(&configuration, vec![], false);
-            let validate_result = packs::validate(&configuration, false);
+            let check_result =
+                packs::check(&configuration, vec![], OutputFormat::Text);
+            let validate_result =
+                packs::validate(&configuration, ValidateFormat::Text);
             let lint_result = packs::lint(&configuration);
 
             check_result.and(validate_result).and(lint_result)
@@ -377,6 +496,21 @@ pub fn run() -> anyhow::Result<()> {
         Command::ListPackDependencies { pack } => {
             packs::list_dependencies(&configuration, pack)
         }
+        Command::Graph {
+            format,
+            focus,
+            depth,
+            collapse,
+        } => packs::graph(
+            &configuration,
+            &GraphOptions {
+                format,
+                focus,
+                depth,
+                collapse,
+            },
+        ),
+        Command::Metrics { format } => packs::metrics(&configuration, format),
         Command::AddDependency { from, to } => {
             packs::add_dependency(&configuration, from, to)
         }
@@ -384,16 +518,23 @@ pub fn run() -> anyhow::Result<()> {
         Command::Check {
             ignore_recorded_violations,
             json,
+            format,
+            since,
             files,
         } => {
             configuration.ignore_recorded_violations =
                 ignore_recorded_violations;
+            let format = output_format(json, format);
+            if let Some(rev) = since {
+                return packs::check_since(configuration, &rev, format);
+            }
             configuration.input_files_count = files.len();
-            packs::check(&configuration, files, json)
+            packs::check(&configuration, files, format)
         }
         Command::CheckContents {
             ignore_recorded_violations,
             json,
+            format,
             file,
         } => {
             configuration.ignore_recorded_violations =
@@ -402,7 +543,11 @@ pub fn run() -> anyhow::Result<()> {
             let absolute_path = get_absolute_path(file.clone(), &configuration);
             configuration.stdin_file_path = Some(absolute_path);
             configuration.input_files_count = 1;
-            packs::check(&configuration, vec![file], json)
+            packs::check(
+                &configuration,
+                vec![file],
+                output_format(json, format),
+            )
         }
         Command::Update {
             files,
@@ -420,7 +565,10 @@ pub fn run() -> anyhow::Result<()> {
                 defining_pack_name: defining_pack,
             },
         ),
-        Command::Validate { json } => packs::validate(&configuration, json),
+        Command::Validate { json, format } => {
+            let format = if json { ValidateFormat::Json } else { format };
+            packs::validate(&configuration, format)
+        }
         Command::CheckUnnecessaryDependencies { auto_correct } => {
             packs::check_unnecessary_dependencies(&configuration, auto_correct)
         }
@@ -434,6 +582,16 @@ pub fn run() -> anyhow::Result<()> {
             packs::delete_cache(configuration);
             Ok(())
         }
+        Command::Cache { command } => match command {
+            CacheCommand::Stats => packs::cache_stats(&configuration),
+            CacheCommand::Verify { sample } => {
+                packs::cache_verify(&configuration, sample)
+            }
+            CacheCommand::Warm => packs::cache_warm(&configuration),
+            CacheCommand::Prune { max_size, max_age } => {
+                packs::cache_prune(&configuration, max_size, max_age)
+            }
+        },
         Command::ListDefinitions(args) => {
             let ambiguous = args.ambiguous;
             packs::list_definitions(&configuration, ambiguous)
@@ -443,6 +601,9 @@ pub fn run() -> anyhow::Result<()> {
             &args.format,
             args.out.as_deref(),
         ),
+        Command::ListSuppressions(args) => {
+            packs::list_suppressions(&configuration, &args.format)
+        }
         Command::ExposeMonkeyPatches(args) => packs::expose_monkey_patches(
             &configuration,
             &args.rubydir,
@@ -457,6 +618,17 @@ pub fn run() -> anyhow::Result<()> {
         Command::Move { destination, paths } => {
             packs::move_to_pack(&configuration, &destination, paths)
         }
+        Command::Lsp => packs::lsp(configuration),
+        Command::Server { socket } => packs::server(configuration, socket),
         Command::Upgrade => unreachable!("handled before config loading"),
     }
 }
+
+// `--json` predates `--format` and is kept as an alias for `--format json`
+fn output_format(json: bool, format: OutputFormat) -> OutputFormat {
+    if json {
+        OutputFormat::Json
+    } else {
+        format
+    }
+}
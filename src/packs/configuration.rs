@@ -1,16 +1,27 @@
 use super::caching::{
-    cache::Cache, create_cache_dir_idempotently, noop_cache::NoopCache,
-    per_file_cache::PerFileCache,
+    cache::Cache, content_addressed_cache::ContentAddressedCache,
+    create_cache_dir_idempotently, noop_cache::NoopCache,
+    packed_cache::PackedCache, per_file_cache::PerFileCache, CacheBackend,
 };
+use super::checker::external::ExternalChecker;
 use super::checker::layer::Layers;
+use super::checker::rules::Rule;
 use super::file_utils::{
     file_content_digest, user_inputted_paths_to_absolute_filepaths,
 };
 
+use super::parsing::ruby::{
+    inflector_shim::Inflections, rails_utils::get_inflections_from_disk,
+    RubyParser,
+};
 use super::{
-    constant_resolver::ConstantResolverConfiguration, raw_configuration,
-    raw_configuration::RawConfiguration, walk_directory,
-    walk_directory::WalkDirectoryResult, PackSet,
+    constant_resolver::ConstantResolverConfiguration,
+    parsing::StringReferenceCalls,
+    raw_configuration,
+    raw_configuration::RawConfiguration,
+    walk_directory,
+    walk_directory::{FileMatcher, WalkDirectoryResult},
+    PackSet,
 };
 
 use std::collections::HashMap;
@@ -24,24 +35,35 @@ use walk_directory::walk_directory;
 #[derive(Debug)]
 pub struct Configuration {
     pub included_files: HashSet<PathBuf>,
+    pub(crate) file_matcher: FileMatcher,
     pub input_files_count: usize, // Helpful for optimizations in privacy chcker
     pub absolute_root: PathBuf,
     pub cache_enabled: bool,
     pub cache_directory: PathBuf,
+    pub cache_backend: CacheBackend,
     pub config_file_path: Option<PathBuf>,
     pub pack_set: PackSet,
     pub layers: Layers,
+    pub rules: Vec<Rule>,
+    pub external_checkers: Vec<ExternalChecker>,
     pub experimental_parser: bool,
+    pub ruby_parser: RubyParser,
     pub ignored_definitions: HashMap<String, HashSet<PathBuf>>,
     pub autoload_roots: HashMap<PathBuf, String>,
+    pub autoload_collapse: Vec<String>,
+    pub autoload_ignore: Vec<String>,
     pub inflections_path: PathBuf,
+    pub inflections: Inflections,
     pub custom_associations: Vec<String>,
+    pub string_reference_calls: StringReferenceCalls,
+    pub string_reference_enforcements: HashSet<String>,
     pub stdin_file_path: Option<PathBuf>,
     // Note that it'd probably be better to use the logger library, `tracing` (see logger.rs)
     // and configure logging in one place. As the complexity of how/why we want to see different logs
     // grows, we can refactor this.
     pub print_files: bool,
     pub packs_first_mode: bool,
+    pub fail_on_parse_errors: bool,
     pub ignore_recorded_violations: bool,
     pub disable_enforce_dependencies: bool,
     pub disable_enforce_folder_privacy: bool,
@@ -69,33 +91,61 @@ impl Configuration {
         }
     }
 
+    pub(crate) fn parser_dir_name(&self) -> &'static str {
+        if self.experimental_parser {
+            "experimental"
+        } else {
+            "zeitwerk"
+        }
+    }
+
+    // The directory of the per file and packed caches
+    pub(crate) fn cache_dir(&self) -> PathBuf {
+        // Include config file digest in cache path so config changes invalidate cache
+        let mut config_digest_prefix = self
+            .config_file_path
+            .as_ref()
+            .and_then(|path| file_content_digest(path).ok())
+            .map(|digest| digest[..8].to_string())
+            .unwrap_or_else(|| "no_config".to_string());
+        // Associations are inflected with the custom inflections
+        if let Some(digest) = self.inflections_digest() {
+            config_digest_prefix.push('-');
+            config_digest_prefix.push_str(&digest[..8]);
+        }
+
+        self.cache_directory
+            .join(self.parser_dir_name())
+            .join(config_digest_prefix)
+    }
+
+    pub(crate) fn inflections_digest(&self) -> Option<String> {
+        file_content_digest(&self.inflections_path).ok()
+    }
+
     pub(crate) fn get_cache(&self) -> Box<dyn Cache + Send + Sync> {
-        if self.cache_enabled {
-            let parser_dir = if self.experimental_parser {
-                "experimental"
-            } else {
-                "zeitwerk"
-            };
-
-            // Include config file digest in cache path so config changes invalidate cache
-            let config_digest_prefix = self
-                .config_file_path
-                .as_ref()
-                .and_then(|path| file_content_digest(path).ok())
-                .map(|digest| digest[..8].to_string())
-                .unwrap_or_else(|| "no_config".to_string());
-
-            let parser_cache_dir = self.cache_directory.join(parser_dir);
-            let cache_dir = parser_cache_dir.join(&config_digest_prefix);
+        if self.cache_enabled
+            && self.cache_backend == CacheBackend::ContentAddressed
+        {
+            // Keys don't depend on the config file, so there are no stale
+            // directories to clean up
+            Box::new(ContentAddressedCache::new(
+                &self.cache_directory,
+                self.experimental_parser,
+                self.ruby_parser,
+                &self.custom_associations,
+                &self.string_reference_calls,
+                self.inflections_digest().as_deref(),
+            ))
+        } else if self.cache_enabled {
+            let cache_dir = self.cache_dir();
+            let parser_cache_dir = cache_dir.parent().unwrap();
 
             // Clean up old cache directories with different config digests
-            if let Ok(entries) = std::fs::read_dir(&parser_cache_dir) {
+            if let Ok(entries) = std::fs::read_dir(parser_cache_dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
-                    if path.is_dir()
-                        && path.file_name()
-                            != Some(std::ffi::OsStr::new(&config_digest_prefix))
-                    {
+                    if path.is_dir() && path != cache_dir {
                         let _ = std::fs::remove_dir_all(&path);
                     }
                 }
@@ -103,7 +153,10 @@ impl Configuration {
 
             create_cache_dir_idempotently(&cache_dir);
 
-            Box::new(PerFileCache { cache_dir })
+            match self.cache_backend {
+                CacheBackend::Packed => Box::new(PackedCache::new(cache_dir)),
+                _ => Box::new(PerFileCache { cache_dir }),
+            }
         } else {
             Box::new(NoopCache {})
         }
@@ -118,6 +171,9 @@ impl Configuration {
             cache_enabled: self.cache_enabled,
             autoload_roots: &self.autoload_roots,
             inflections_path: &self.inflections_path,
+            acronyms: &self.inflections.acronyms,
+            autoload_collapse: &self.autoload_collapse,
+            autoload_ignore: &self.autoload_ignore,
         }
     }
 }
@@ -155,20 +211,35 @@ pub(crate) fn from_raw(
     } = walk_directory_result;
 
     let absolute_root = absolute_root.to_path_buf();
+    let file_matcher = FileMatcher::new(&raw_config);
     let pack_set = PackSet::build(included_packs, owning_package_yml_for_file)?;
 
     let cache_directory = absolute_root.join(raw_config.cache_directory);
     let cache_enabled = raw_config.cache;
+    let cache_backend = raw_config.cache_backend;
     let experimental_parser = raw_config.experimental_parser;
+    let ruby_parser = raw_config.ruby_parser;
+    if !ruby_parser.is_available() {
+        anyhow::bail!(
+            "`ruby_parser: prism` in the configuration needs `pks` to be built with the `prism` feature"
+        );
+    }
 
-    let layers = Layers {
-        layers: raw_config.layers,
-    };
+    let layers = Layers::from(raw_config.layers);
+
+    let rules = raw_config.rules;
+    for rule in &rules {
+        rule.validate()?;
+    }
+    let external_checkers = raw_config.external_checkers;
 
     let ignored_definitions = raw_config.ignored_definitions;
     let autoload_roots: HashMap<PathBuf, String> = raw_config.autoload_roots;
+    let autoload_collapse = raw_config.autoload_collapse;
+    let autoload_ignore = raw_config.autoload_ignore;
 
     let packs_first_mode = raw_config.packs_first_mode;
+    let fail_on_parse_errors = raw_config.fail_on_parse_errors;
 
     let inflections_path = absolute_root.join(
         raw_config
@@ -176,6 +247,8 @@ pub(crate) fn from_raw(
             .unwrap_or(PathBuf::from("config/initializers/inflections.rb")),
     );
 
+    let inflections = get_inflections_from_disk(&inflections_path);
+
     let custom_associations = raw_config
         .custom_associations
         .iter()
@@ -183,25 +256,51 @@ pub(crate) fn from_raw(
         .map(|a| a.trim_start_matches(':').to_owned())
         .collect();
 
+    let string_reference_calls = if raw_config.string_references {
+        StringReferenceCalls {
+            methods: raw_config.string_reference_methods,
+            keywords: raw_config
+                .string_reference_keywords
+                .iter()
+                .map(|k| k.trim_start_matches(':').to_owned())
+                .collect(),
+        }
+    } else {
+        StringReferenceCalls::default()
+    };
+    let string_reference_enforcements =
+        raw_config.string_reference_enforcements;
+
     debug!("Finished building configuration");
 
     Ok(Configuration {
         included_files,
+        file_matcher,
         input_files_count: input_files_count.to_owned(),
         absolute_root,
         cache_enabled,
         cache_directory,
+        cache_backend,
         config_file_path,
         pack_set,
         layers,
+        rules,
+        external_checkers,
         experimental_parser,
+        ruby_parser,
         ignored_definitions,
         autoload_roots,
+        autoload_collapse,
+        autoload_ignore,
         inflections_path,
+        inflections,
         custom_associations,
+        string_reference_calls,
+        string_reference_enforcements,
         stdin_file_path: None,
         print_files: false,
         packs_first_mode,
+        fail_on_parse_errors,
         ignore_recorded_violations: false,
         disable_enforce_dependencies: false,
         disable_enforce_folder_privacy: false,
@@ -251,6 +350,7 @@ mod tests {
                 enforce_visibility: None,
                 enforce_folder_privacy: None,
                 enforce_folder_visibility: None,
+                enforce_rules: None,
                 enforce_layers: None,
                 owner: None,
                 yml: absolute_root.join("packs/bar/package.yml"),
@@ -273,6 +373,7 @@ mod tests {
                 enforce_visibility: None,
                 enforce_folder_privacy: None,
                 enforce_folder_visibility: None,
+                enforce_rules: None,
                 enforce_layers: None,
                 owner: None,
                 yml: absolute_root.join("packs/baz/package.yml"),
@@ -295,6 +396,7 @@ mod tests {
                 enforce_visibility: None,
                 enforce_folder_privacy: None,
                 enforce_folder_visibility: None,
+                enforce_rules: None,
                 enforce_layers: None,
                 owner: None,
                 yml: absolute_root.join("packs/foo/package.yml"),
@@ -320,6 +422,7 @@ mod tests {
                 enforce_visibility: None,
                 enforce_folder_privacy: None,
                 enforce_folder_visibility: None,
+                enforce_rules: None,
                 enforce_layers: None,
                 owner: None,
                 yml: absolute_root.join("package.yml"),
@@ -101,7 +101,7 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
-    use crate::packs::{PackSet, SourceLocation};
+    use crate::packs::{parsing::ReferenceKind, PackSet, SourceLocation};
 
     fn example_references() -> Vec<Reference> {
         vec![
@@ -116,6 +116,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             },
             Reference {
                 constant_name: String::from("::Bar::BarChild"),
@@ -128,6 +129,7 @@ mod tests {
                     "packs/bar/app/api/bar.rb",
                 )),
                 source_location: SourceLocation { line: 3, column: 1 },
+                kind: ReferenceKind::Constant,
             },
             Reference {
                 constant_name: String::from("::BarChild"),
@@ -143,6 +145,7 @@ mod tests {
                     line: 33,
                     column: 1,
                 },
+                kind: ReferenceKind::Constant,
             },
             Reference {
                 constant_name: String::from("::Bar"),
@@ -158,6 +161,7 @@ mod tests {
                     line: 53,
                     column: 1,
                 },
+                kind: ReferenceKind::Constant,
             },
         ]
     }
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +17,9 @@ pub struct ConstantResolverConfiguration<'a> {
     pub cache_directory: &'a PathBuf,
     pub cache_enabled: bool,
     pub inflections_path: &'a PathBuf,
+    pub acronyms: &'a HashSet<String>,
+    pub autoload_collapse: &'a [String],
+    pub autoload_ignore: &'a [String],
     pub autoload_roots: &'a HashMap<PathBuf, String>,
 }
 
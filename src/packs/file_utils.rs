@@ -8,12 +8,13 @@ use std::{
 use crate::packs::Configuration;
 use anyhow::Context;
 use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
-use regex::Regex;
 
 #[derive(PartialEq, Debug)]
 pub enum SupportedFileType {
     Ruby,
     Erb,
+    Haml,
+    Slim,
 }
 
 pub fn get_file_type(path: &Path) -> Option<SupportedFileType> {
@@ -37,7 +38,11 @@ pub fn get_file_type(path: &Path) -> Option<SupportedFileType> {
     } else if is_erb_file {
         Some(SupportedFileType::Erb)
     } else {
-        None
+        match extension.and_then(|ext| ext.to_str()) {
+            Some("haml") => Some(SupportedFileType::Haml),
+            Some("slim") => Some(SupportedFileType::Slim),
+            _ => None,
+        }
     }
 }
 
@@ -101,20 +106,6 @@ pub fn user_inputted_paths_to_absolute_filepaths(
         .collect::<HashSet<_>>()
 }
 
-pub(crate) fn convert_erb_to_ruby_without_sourcemaps(
-    contents: String,
-) -> String {
-    let regex_pattern = r"(?s)<%=?-?\s*(.*?)\s*-?%>";
-    let regex = Regex::new(regex_pattern).unwrap();
-
-    let extracted_contents: Vec<&str> = regex
-        .captures_iter(&contents)
-        .map(|capture| capture.get(1).unwrap().as_str())
-        .collect();
-
-    extracted_contents.join("\n")
-}
-
 pub(crate) fn file_content_digest(file: &Path) -> anyhow::Result<String> {
     let mut file_content = Vec::new();
 
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use anyhow::{bail, Context};
+
+/// Absolute paths of the files that differ between `rev` and the working
+/// tree, including untracked files that are not git-ignored. Deleted files
+/// and both paths of renamed files are included too, since references to
+/// the constants they defined at `rev` may have been affected.
+pub(crate) fn changed_files(
+    absolute_root: &Path,
+    rev: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = git(
+        absolute_root,
+        &[
+            "diff",
+            "-z",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            rev,
+            "--",
+        ],
+    )?;
+    files.extend(git(
+        absolute_root,
+        &["ls-files", "-z", "--others", "--exclude-standard"],
+    )?);
+    files.sort();
+    files.dedup();
+    Ok(files
+        .into_iter()
+        .map(|file| absolute_root.join(file))
+        .collect())
+}
+
+/// The contents of `relative_path` at `rev`, or `None` if it didn't exist
+pub(crate) fn file_at_rev(
+    absolute_root: &Path,
+    rev: &str,
+    relative_path: &Path,
+) -> anyhow::Result<Option<String>> {
+    // `./` makes the path relative to the root rather than to the top-level
+    // directory of the repository
+    let object = format!("{}:./{}", rev, relative_path.to_string_lossy());
+    let output = run_git(absolute_root, &["show", &object])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+// Paths are NUL separated (`-z`), so that git doesn't quote paths with
+// spaces or non-ASCII characters
+fn git(absolute_root: &Path, args: &[&str]) -> anyhow::Result<Vec<String>> {
+    let output = run_git(absolute_root, args)?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output
+        .stdout
+        .split(|byte| *byte == b'\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).into_owned())
+        .collect())
+}
+
+fn run_git(absolute_root: &Path, args: &[&str]) -> anyhow::Result<Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(absolute_root)
+        .args(args)
+        .output()
+        .context("Failed to run git, is it installed?")
+}
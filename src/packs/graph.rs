@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use anyhow::bail;
+use serde::Serialize;
+
+use super::dependencies;
+use super::pack::Pack;
+use super::Configuration;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Mermaid,
+    Json,
+}
+
+pub struct GraphOptions {
+    pub format: GraphFormat,
+    /// Only keep this pack and the packs within `depth` edges of it
+    pub focus: Option<String>,
+    pub depth: usize,
+    /// Packs under any of these directories are merged into a single node
+    pub collapse: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Node {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    /// The packs merged into this node, when collapsed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    packs: Vec<String>,
+}
+
+#[derive(
+    Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+enum EdgeKind {
+    /// Listed in the `dependencies` of the referencing package.yml
+    Declared,
+    /// Recorded as violations in the referencing package_todo.yml
+    Implicit,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Edge {
+    from: String,
+    to: String,
+    kind: EdgeKind,
+    /// Recorded constants per violation type, for implicit edges
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    violations: BTreeMap<String, usize>,
+}
+
+pub(crate) fn render(
+    configuration: &Configuration,
+    options: &GraphOptions,
+) -> anyhow::Result<String> {
+    let mut graph = build_graph(configuration, &options.collapse)?;
+    if let Some(focus) = &options.focus {
+        graph = graph.focus(
+            &collapsed_name(focus.trim_end_matches('/'), &options.collapse),
+            options.depth,
+        )?;
+    }
+    Ok(match options.format {
+        GraphFormat::Dot => to_dot(&graph),
+        GraphFormat::Mermaid => to_mermaid(&graph),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph)?,
+    })
+}
+
+fn build_graph(
+    configuration: &Configuration,
+    collapse: &[String],
+) -> anyhow::Result<Graph> {
+    let mut packs_by_node: BTreeMap<String, Vec<&Pack>> = BTreeMap::new();
+    for pack in &configuration.pack_set.packs {
+        packs_by_node
+            .entry(collapsed_name(&pack.name, collapse))
+            .or_default()
+            .push(pack);
+    }
+
+    let mut edges: BTreeMap<
+        (String, String, EdgeKind),
+        BTreeMap<String, usize>,
+    > = BTreeMap::new();
+    for pack in &configuration.pack_set.packs {
+        let to = collapsed_name(&pack.name, collapse);
+        let dependencies =
+            dependencies::find_dependencies(configuration, &pack.name)?;
+        for dependent in dependencies.explicit {
+            let from = collapsed_name(&dependent, collapse);
+            if from != to {
+                edges
+                    .entry((from, to.clone(), EdgeKind::Declared))
+                    .or_default();
+            }
+        }
+        for (dependent, violations) in dependencies.implicit {
+            let from = collapsed_name(&dependent, collapse);
+            if from != to {
+                let counts = edges
+                    .entry((from, to.clone(), EdgeKind::Implicit))
+                    .or_default();
+                for (violation_type, count) in violations {
+                    *counts.entry(violation_type).or_default() += count;
+                }
+            }
+        }
+    }
+
+    Ok(Graph {
+        nodes: packs_by_node
+            .into_iter()
+            .map(|(name, packs)| {
+                let mut pack_names: Vec<String> =
+                    packs.iter().map(|pack| pack.name.clone()).collect();
+                if pack_names == [name.clone()] {
+                    pack_names.clear();
+                }
+                pack_names.sort();
+                Node {
+                    layer: shared_value(
+                        packs.iter().map(|pack| pack.layer.as_ref()),
+                    ),
+                    owner: shared_value(
+                        packs.iter().map(|pack| pack.owner.as_ref()),
+                    ),
+                    name,
+                    packs: pack_names,
+                }
+            })
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(|((from, to, kind), violations)| Edge {
+                from,
+                to,
+                kind,
+                violations,
+            })
+            .collect(),
+    })
+}
+
+fn collapsed_name(pack_name: &str, collapse: &[String]) -> String {
+    for prefix in collapse {
+        let prefix = prefix.trim_end_matches('/');
+        if pack_name == prefix || pack_name.starts_with(&format!("{}/", prefix))
+        {
+            return prefix.to_owned();
+        }
+    }
+    pack_name.to_owned()
+}
+
+// A collapsed node only has a layer (or owner) if all its packs agree on it
+fn shared_value<'a>(
+    mut values: impl Iterator<Item = Option<&'a String>>,
+) -> Option<String> {
+    let first = values.next()??;
+    values
+        .all(|value| value == Some(first))
+        .then(|| first.clone())
+}
+
+impl Graph {
+    fn focus(self, name: &str, depth: usize) -> anyhow::Result<Graph> {
+        if !self.nodes.iter().any(|node| node.name == name) {
+            bail!("No pack found '{}'", name)
+        }
+
+        let mut kept: HashSet<&str> = HashSet::from([name]);
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(name, 0)]);
+        while let Some((current, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for edge in &self.edges {
+                let neighbor = if edge.from == current {
+                    &edge.to
+                } else if edge.to == current {
+                    &edge.from
+                } else {
+                    continue;
+                };
+                if kept.insert(neighbor) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+
+        let kept: HashSet<String> =
+            kept.into_iter().map(String::from).collect();
+        Ok(Graph {
+            nodes: self
+                .nodes
+                .into_iter()
+                .filter(|node| kept.contains(&node.name))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .filter(|edge| {
+                    kept.contains(&edge.from) && kept.contains(&edge.to)
+                })
+                .collect(),
+        })
+    }
+}
+
+fn node_label_lines(node: &Node) -> Vec<String> {
+    let mut lines = vec![node.name.clone()];
+    if let Some(layer) = &node.layer {
+        lines.push(format!("layer: {}", layer));
+    }
+    if let Some(owner) = &node.owner {
+        lines.push(format!("owner: {}", owner));
+    }
+    lines
+}
+
+fn violations_label(violations: &BTreeMap<String, usize>) -> String {
+    violations
+        .iter()
+        .map(|(violation_type, count)| format!("{}: {}", violation_type, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn to_dot(graph: &Graph) -> String {
+    let quote = |text: &str| {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    };
+
+    let mut lines = vec!["digraph packs {".to_owned()];
+    lines.push("  node [shape=box];".to_owned());
+    for node in &graph.nodes {
+        let label = node_label_lines(node)
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        lines.push(format!("  {} [label=\"{}\"];", quote(&node.name), label));
+    }
+    for edge in &graph.edges {
+        let attributes = match edge.kind {
+            EdgeKind::Declared => String::new(),
+            EdgeKind::Implicit => format!(
+                " [style=dashed, color=red, label={}]",
+                quote(&violations_label(&edge.violations))
+            ),
+        };
+        lines.push(format!(
+            "  {} -> {}{};",
+            quote(&edge.from),
+            quote(&edge.to),
+            attributes
+        ));
+    }
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
+
+fn to_mermaid(graph: &Graph) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('"', "#quot;"));
+    // Pack names are not valid mermaid ids, so nodes are numbered
+    let ids: BTreeMap<&str, String> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.name.as_str(), format!("n{}", index)))
+        .collect();
+
+    let mut lines = vec!["flowchart LR".to_owned()];
+    for node in &graph.nodes {
+        lines.push(format!(
+            "  {}[{}]",
+            ids[node.name.as_str()],
+            quote(&node_label_lines(node).join("<br/>"))
+        ));
+    }
+    for edge in &graph.edges {
+        let (from, to) = (&ids[edge.from.as_str()], &ids[edge.to.as_str()]);
+        lines.push(match edge.kind {
+            EdgeKind::Declared => format!("  {} --> {}", from, to),
+            EdgeKind::Implicit => format!(
+                "  {} -.->|{}| {}",
+                from,
+                quote(&violations_label(&edge.violations)),
+                to
+            ),
+        });
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn graph() -> Graph {
+        Graph {
+            nodes: vec![
+                Node {
+                    name: "packs/bar".to_owned(),
+                    layer: None,
+                    owner: None,
+                    packs: vec![],
+                },
+                Node {
+                    name: "packs/foo".to_owned(),
+                    layer: Some("product".to_owned()),
+                    owner: Some("Team \"A\"".to_owned()),
+                    packs: vec![],
+                },
+            ],
+            edges: vec![
+                Edge {
+                    from: "packs/foo".to_owned(),
+                    to: "packs/bar".to_owned(),
+                    kind: EdgeKind::Declared,
+                    violations: BTreeMap::new(),
+                },
+                Edge {
+                    from: "packs/foo".to_owned(),
+                    to: "packs/bar".to_owned(),
+                    kind: EdgeKind::Implicit,
+                    violations: BTreeMap::from([
+                        ("dependency".to_owned(), 2),
+                        ("privacy".to_owned(), 1),
+                    ]),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            to_dot(&graph()),
+            r#"digraph packs {
+  node [shape=box];
+  "packs/bar" [label="packs/bar"];
+  "packs/foo" [label="packs/foo\nlayer: product\nowner: Team \"A\""];
+  "packs/foo" -> "packs/bar";
+  "packs/foo" -> "packs/bar" [style=dashed, color=red, label="dependency: 2, privacy: 1"];
+}"#
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        assert_eq!(
+            to_mermaid(&graph()),
+            r#"flowchart LR
+  n0["packs/bar"]
+  n1["packs/foo<br/>layer: product<br/>owner: Team #quot;A#quot;"]
+  n1 --> n0
+  n1 -.->|"dependency: 2, privacy: 1"| n0"#
+        );
+    }
+
+    #[test]
+    fn test_collapsed_name() {
+        let collapse = vec!["packs/platform/".to_owned()];
+        assert_eq!(
+            collapsed_name("packs/platform/auth", &collapse),
+            "packs/platform"
+        );
+        assert_eq!(
+            collapsed_name("packs/platform", &collapse),
+            "packs/platform"
+        );
+        assert_eq!(
+            collapsed_name("packs/platformer", &collapse),
+            "packs/platformer"
+        );
+    }
+
+    #[test]
+    fn test_focus() {
+        let mut graph = graph();
+        graph.nodes.push(Node {
+            name: "packs/baz".to_owned(),
+            layer: None,
+            owner: None,
+            packs: vec![],
+        });
+        graph.edges.push(Edge {
+            from: "packs/baz".to_owned(),
+            to: "packs/foo".to_owned(),
+            kind: EdgeKind::Declared,
+            violations: BTreeMap::new(),
+        });
+
+        let focused = graph.focus("packs/bar", 1).unwrap();
+        assert_eq!(
+            focused
+                .nodes
+                .iter()
+                .map(|node| node.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["packs/bar", "packs/foo"]
+        );
+        assert_eq!(focused.edges.len(), 2);
+    }
+}
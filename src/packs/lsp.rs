@@ -0,0 +1,705 @@
+// `pks lsp` speaks the Language Server Protocol over stdio. It keeps the same
+// in-memory state as `pks server`, publishes the violations of open documents
+// as diagnostics, offers code actions to resolve them, and jumps to constant
+// definitions through the constant resolver.
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+use lsp_server::{
+    Connection, ErrorCode, Message, Notification, Request, Response,
+};
+use lsp_types::{
+    notification::{
+        DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{
+        CodeActionRequest, ExecuteCommand, GotoDefinition, Request as _,
+    },
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Command, Diagnostic, DiagnosticSeverity,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Location, NumberOrString,
+    OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Url,
+};
+use serde_json::Value;
+
+use super::{
+    checker::ViolationIdentifier,
+    constant_resolver::ConstantDefinition,
+    pack::{write_pack_to_disk, Pack},
+    server::ServerState,
+    Configuration, Violation,
+};
+
+const ADD_DEPENDENCY: &str = "pks.addDependency";
+const IGNORE_PRIVATE_CONSTANT: &str = "pks.ignorePrivateConstant";
+const MOVE_TO_PUBLIC_FOLDER: &str = "pks.moveToPublicFolder";
+
+pub(crate) fn run(configuration: Configuration) -> anyhow::Result<()> {
+    let state = ServerState::new(configuration)?;
+    // The watcher stops when dropped, so it has to live as long as the server
+    let _watcher = state.watch()?;
+
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut language_server = LanguageServer {
+        connection,
+        state,
+        open_documents: HashSet::new(),
+    };
+    language_server.main_loop()?;
+
+    // Dropping the connection lets the writer thread finish
+    drop(language_server);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::NONE),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                ADD_DEPENDENCY.to_owned(),
+                IGNORE_PRIVATE_CONSTANT.to_owned(),
+                MOVE_TO_PUBLIC_FOLDER.to_owned(),
+            ],
+            ..ExecuteCommandOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    }
+}
+
+struct LanguageServer {
+    connection: Connection,
+    state: ServerState,
+    // Diagnostics are based on the saved contents of these files
+    open_documents: HashSet<PathBuf>,
+}
+
+impl LanguageServer {
+    fn main_loop(&mut self) -> anyhow::Result<()> {
+        let receiver = self.connection.receiver.clone();
+        for message in &receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    // Notifications have no response to carry an error, so
+                    // stderr is the best we can do
+                    if let Err(err) = self.handle_notification(notification) {
+                        eprintln!("{:#}", err);
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Response {
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => serde_json::from_value(request.params)
+                .map_err(anyhow::Error::from)
+                .and_then(|params| self.definition(params))
+                .and_then(|response| Ok(serde_json::to_value(response)?)),
+            CodeActionRequest::METHOD => serde_json::from_value(request.params)
+                .map_err(anyhow::Error::from)
+                .and_then(|params| self.code_actions(params))
+                .and_then(|actions| Ok(serde_json::to_value(actions)?)),
+            ExecuteCommand::METHOD => serde_json::from_value(request.params)
+                .map_err(anyhow::Error::from)
+                .and_then(|params| self.execute_command(params))
+                .map(|_| Value::Null),
+            method => {
+                return Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request: {}", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(err) => Response::new_err(
+                request.id,
+                ErrorCode::RequestFailed as i32,
+                format!("{:#}", err),
+            ),
+        }
+    }
+
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> anyhow::Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.open_documents
+                    .insert(file_path(&params.text_document.uri)?);
+                self.publish_diagnostics()
+            }
+            DidSaveTextDocument::METHOD => {
+                let params: DidSaveTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.state
+                    .mark_changed(file_path(&params.text_document.uri)?);
+                self.publish_diagnostics()
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let path = file_path(&params.text_document.uri)?;
+                self.open_documents.remove(&path);
+                self.publish(&path, vec![])
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn publish_diagnostics(&mut self) -> anyhow::Result<()> {
+        // An empty list of files means "check everything"
+        if self.open_documents.is_empty() {
+            return Ok(());
+        }
+        let files = self
+            .open_documents
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let result = self.state.check(files)?;
+
+        let mut diagnostics_by_path: HashMap<PathBuf, Vec<Diagnostic>> = self
+            .open_documents
+            .iter()
+            .map(|path| (path.clone(), vec![]))
+            .collect();
+        for violation in result.reportable_violations() {
+            let path = self
+                .state
+                .configuration()
+                .absolute_root
+                .join(&violation.identifier.file);
+            let diagnostic = self.diagnostic(&path, violation);
+            diagnostics_by_path
+                .entry(path)
+                .or_default()
+                .push(diagnostic);
+        }
+
+        for (path, diagnostics) in diagnostics_by_path {
+            self.publish(&path, diagnostics)?;
+        }
+        Ok(())
+    }
+
+    fn publish(
+        &self,
+        absolute_path: &Path,
+        diagnostics: Vec<Diagnostic>,
+    ) -> anyhow::Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri: file_url(absolute_path)?,
+            diagnostics,
+            version: None,
+        };
+        self.connection.sender.send(Message::Notification(
+            Notification::new(PublishDiagnostics::METHOD.to_owned(), params),
+        ))?;
+        Ok(())
+    }
+
+    fn diagnostic(
+        &self,
+        absolute_path: &Path,
+        violation: &Violation,
+    ) -> Diagnostic {
+        let message = String::from_utf8_lossy(&strip_ansi_escapes::strip(
+            &violation.message,
+        ))
+        .to_string();
+        // The editor already shows where the violation is, so drop the
+        // `file:line:column` line the CLI prints first
+        let message = match message.split_once('\n') {
+            Some((location, rest))
+                if location.starts_with(&violation.identifier.file) =>
+            {
+                rest.to_owned()
+            }
+            _ => message,
+        };
+
+        Diagnostic {
+            range: self.violation_range(absolute_path, violation),
+            // Like the checkstyle output, only strict violations are errors
+            severity: Some(if violation.identifier.strict {
+                DiagnosticSeverity::ERROR
+            } else {
+                DiagnosticSeverity::WARNING
+            }),
+            code: Some(NumberOrString::String(
+                violation.identifier.violation_type.clone(),
+            )),
+            source: Some("pks".to_owned()),
+            message,
+            data: serde_json::to_value(&violation.identifier).ok(),
+            ..Diagnostic::default()
+        }
+    }
+
+    // Violations only record where a reference starts, so we look up the end
+    // of the reference in the processed file
+    fn violation_range(
+        &self,
+        absolute_path: &Path,
+        violation: &Violation,
+    ) -> Range {
+        let line = violation.source_location.line;
+        let column = violation.source_location.column;
+        let end =
+            self.state
+                .processed_file(absolute_path)
+                .and_then(|processed_file| {
+                    processed_file.unresolved_references.iter().find(
+                        |reference| {
+                            reference.location.start_row == line
+                                && reference.location.start_col == column
+                        },
+                    )
+                })
+                .map(|reference| {
+                    position(
+                        reference.location.end_row,
+                        reference.location.end_col.saturating_sub(1),
+                    )
+                })
+                .unwrap_or_else(|| {
+                    let name = violation
+                        .identifier
+                        .constant_name
+                        .rsplit("::")
+                        .next()
+                        .unwrap_or_default();
+                    position(line, column + name.len())
+                });
+
+        Range {
+            start: position(line, column),
+            end,
+        }
+    }
+
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> anyhow::Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params.position;
+        let path =
+            file_path(&params.text_document_position_params.text_document.uri)?;
+        let cursor = (position.line as usize + 1, position.character as usize);
+
+        let reference =
+            self.state.processed_file(&path).and_then(|processed_file| {
+                processed_file
+                    .unresolved_references
+                    .iter()
+                    .find(|reference| {
+                        let location = &reference.location;
+                        (location.start_row, location.start_col) <= cursor
+                            && cursor
+                                < (
+                                    location.end_row,
+                                    location.end_col.saturating_sub(1),
+                                )
+                    })
+            });
+        let reference = match reference {
+            Some(reference) => reference,
+            None => return Ok(None),
+        };
+
+        let namespace_path: Vec<&str> = reference
+            .namespace_path
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let locations = self
+            .state
+            .constant_resolver()
+            .resolve(&reference.name, &namespace_path)
+            .unwrap_or_default()
+            .iter()
+            .map(|definition| {
+                Ok(Location::new(
+                    file_url(&definition.absolute_path_of_definition)?,
+                    self.definition_range(definition),
+                ))
+            })
+            .collect::<anyhow::Result<Vec<Location>>>()?;
+
+        Ok(Some(GotoDefinitionResponse::Array(locations)))
+    }
+
+    // The zeitwerk resolver only knows which file defines a constant, so we
+    // look up where in that file it is defined. Only the experimental parser
+    // keeps the definitions it parses, in which case `class Foo::Bar` also
+    // counts as defining `Foo`.
+    fn definition_range(&self, definition: &ConstantDefinition) -> Range {
+        let name = &definition.fully_qualified_name;
+        let nested_prefix = format!("{}::", name);
+        let parsed_range = self
+            .state
+            .processed_file(&definition.absolute_path_of_definition)
+            .and_then(|processed_file| {
+                let definitions = &processed_file.definitions;
+                definitions
+                    .iter()
+                    .find(|parsed| &parsed.fully_qualified_name == name)
+                    .or_else(|| {
+                        definitions.iter().find(|parsed| {
+                            parsed
+                                .fully_qualified_name
+                                .starts_with(&nested_prefix)
+                        })
+                    })
+            })
+            .map(|parsed| Range {
+                start: position(
+                    parsed.location.start_row,
+                    parsed.location.start_col,
+                ),
+                end: position(
+                    parsed.location.end_row,
+                    parsed.location.end_col.saturating_sub(1),
+                ),
+            });
+        parsed_range
+            .or_else(|| {
+                let contents = std::fs::read_to_string(
+                    &definition.absolute_path_of_definition,
+                )
+                .ok()?;
+                declaration_range(&contents, name)
+            })
+            .unwrap_or_default()
+    }
+
+    fn code_actions(
+        &self,
+        params: CodeActionParams,
+    ) -> anyhow::Result<Vec<CodeActionOrCommand>> {
+        let mut actions = Vec::new();
+        for diagnostic in &params.context.diagnostics {
+            let identifier: ViolationIdentifier = match diagnostic
+                .data
+                .clone()
+                .and_then(|data| serde_json::from_value(data).ok())
+            {
+                Some(identifier) => identifier,
+                None => continue,
+            };
+
+            match identifier.violation_type.as_str() {
+                "dependency" => actions.push(code_action(
+                    format!(
+                        "Add `{}` as a dependency of `{}`",
+                        identifier.defining_pack_name,
+                        identifier.referencing_pack_name
+                    ),
+                    ADD_DEPENDENCY,
+                    vec![
+                        identifier.referencing_pack_name.clone(),
+                        identifier.defining_pack_name.clone(),
+                    ],
+                    diagnostic,
+                )),
+                "privacy" => {
+                    actions.push(code_action(
+                        format!(
+                            "Add `{}` to the ignored_private_constants of `{}`",
+                            identifier.constant_name,
+                            identifier.defining_pack_name
+                        ),
+                        IGNORE_PRIVATE_CONSTANT,
+                        vec![
+                            identifier.defining_pack_name.clone(),
+                            identifier.constant_name.clone(),
+                        ],
+                        diagnostic,
+                    ));
+                    if let Some(defining_file) = self.defining_file(&identifier)
+                    {
+                        actions.push(code_action(
+                            format!(
+                                "Move `{}` into the public folder of `{}`",
+                                defining_file, identifier.defining_pack_name
+                            ),
+                            MOVE_TO_PUBLIC_FOLDER,
+                            vec![
+                                identifier.defining_pack_name.clone(),
+                                defining_file,
+                            ],
+                            diagnostic,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(actions)
+    }
+
+    fn defining_file(
+        &self,
+        identifier: &ViolationIdentifier,
+    ) -> Option<String> {
+        let configuration = self.state.configuration();
+        let definitions = self
+            .state
+            .constant_resolver()
+            .resolve(&identifier.constant_name, &[])?;
+        definitions.iter().find_map(|definition| {
+            let pack = configuration
+                .pack_set
+                .for_file(&definition.absolute_path_of_definition)
+                .ok()??;
+            if pack.name != identifier.defining_pack_name {
+                return None;
+            }
+            definition
+                .absolute_path_of_definition
+                .strip_prefix(&configuration.absolute_root)
+                .ok()
+                .map(|path| path.to_string_lossy().into_owned())
+        })
+    }
+
+    fn execute_command(
+        &mut self,
+        params: ExecuteCommandParams,
+    ) -> anyhow::Result<()> {
+        let arguments = params
+            .arguments
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<String>, _>>()
+            .context("Command arguments must be strings")?;
+
+        let configuration = self.state.configuration();
+        let pack_set = &configuration.pack_set;
+        match (params.command.as_str(), arguments.as_slice()) {
+            (ADD_DEPENDENCY, [from, to]) => {
+                let from_pack = pack_set.for_pack(from)?;
+                let to_pack = pack_set.for_pack(to)?;
+                write_pack_to_disk(&from_pack.add_dependency(to_pack))?;
+            }
+            (IGNORE_PRIVATE_CONSTANT, [pack_name, constant]) => {
+                let pack = pack_set.for_pack(pack_name)?;
+                write_pack_to_disk(
+                    &pack.add_ignored_private_constant(constant),
+                )?;
+            }
+            (MOVE_TO_PUBLIC_FOLDER, [pack_name, file]) => {
+                let pack = pack_set.for_pack(pack_name)?;
+                move_to_public_folder(configuration, pack, Path::new(file))?;
+            }
+            (command, _) => {
+                bail!("Unsupported command: {} {:?}", command, arguments)
+            }
+        }
+
+        self.state.reload()?;
+        self.publish_diagnostics()
+    }
+}
+
+fn code_action(
+    title: String,
+    command: &str,
+    arguments: Vec<String>,
+    diagnostic: &Diagnostic,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        command: Some(Command::new(
+            title,
+            command.to_owned(),
+            Some(arguments.into_iter().map(Value::from).collect()),
+        )),
+        ..CodeAction::default()
+    })
+}
+
+fn move_to_public_folder(
+    configuration: &Configuration,
+    pack: &Pack,
+    relative_file: &Path,
+) -> anyhow::Result<()> {
+    let destination = public_folder_destination(pack, relative_file);
+    let absolute_origin = configuration.absolute_root.join(relative_file);
+    let absolute_destination = configuration.absolute_root.join(&destination);
+    if absolute_destination.exists() {
+        bail!("{} already exists", destination.display());
+    }
+    if let Some(parent) = absolute_destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&absolute_origin, &absolute_destination).context(format!(
+        "Failed to move {} to {}",
+        relative_file.display(),
+        destination.display()
+    ))
+}
+
+// Keeps the file's path relative to its autoload root, so the constant it
+// defines doesn't change, e.g.
+// packs/foo/app/services/foo/bar.rb -> packs/foo/app/public/foo/bar.rb
+fn public_folder_destination(pack: &Pack, relative_file: &Path) -> PathBuf {
+    let within_pack = relative_file
+        .strip_prefix(&pack.relative_path)
+        .unwrap_or(relative_file);
+    let autoload_root_depth =
+        if within_pack.starts_with("app") { 2 } else { 1 };
+    let within_autoload_root: PathBuf =
+        within_pack.components().skip(autoload_root_depth).collect();
+    pack.public_folder().join(within_autoload_root)
+}
+
+// The name in the first `class` or `module` statement declaring the last
+// segment of `fully_qualified_name`
+fn declaration_range(
+    contents: &str,
+    fully_qualified_name: &str,
+) -> Option<Range> {
+    let name = fully_qualified_name.rsplit("::").next()?;
+    contents.lines().enumerate().find_map(|(row, line)| {
+        let statement = line.trim_start();
+        let keyword = ["class ", "module "]
+            .into_iter()
+            .find(|keyword| statement.starts_with(keyword))?;
+        let declared = statement[keyword.len()..]
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .next()?;
+        if declared != name && !declared.ends_with(&format!("::{}", name)) {
+            return None;
+        }
+        let column =
+            line.len() - statement.len() + keyword.len() + declared.len()
+                - name.len();
+        Some(Range {
+            start: position(row + 1, column),
+            end: position(row + 1, column + name.len()),
+        })
+    })
+}
+
+fn position(row: usize, column: usize) -> Position {
+    Position::new(row.saturating_sub(1) as u32, column as u32)
+}
+
+fn file_path(uri: &Url) -> anyhow::Result<PathBuf> {
+    let path = uri
+        .to_file_path()
+        .map_err(|_| anyhow!("Not a file URI: {}", uri))?;
+    // The project root is canonicalized, so paths have to be as well to match
+    Ok(path.canonicalize().unwrap_or(path))
+}
+
+fn file_url(absolute_path: &Path) -> anyhow::Result<Url> {
+    Url::from_file_path(absolute_path).map_err(|_| {
+        anyhow!("Not an absolute path: {}", absolute_path.display())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_declaration_range() {
+        let contents = "module Foo\n  class Bar::Baz < Base\n  end\nend\n";
+        let range = |start: (u32, u32), end: (u32, u32)| {
+            Some(Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            })
+        };
+
+        assert_eq!(
+            declaration_range(contents, "::Foo"),
+            range((0, 7), (0, 10))
+        );
+        assert_eq!(
+            declaration_range(contents, "::Foo::Bar::Baz"),
+            range((1, 13), (1, 16))
+        );
+        assert_eq!(declaration_range(contents, "::Foo::Qux"), None);
+    }
+
+    #[test]
+    fn test_public_folder_destination() {
+        let pack = Pack {
+            name: "packs/foo".to_owned(),
+            relative_path: PathBuf::from("packs/foo"),
+            ..Pack::default()
+        };
+
+        assert_eq!(
+            public_folder_destination(
+                &pack,
+                Path::new("packs/foo/app/services/foo/bar.rb")
+            ),
+            PathBuf::from("packs/foo/app/public/foo/bar.rb")
+        );
+        assert_eq!(
+            public_folder_destination(&pack, Path::new("packs/foo/lib/baz.rb")),
+            PathBuf::from("packs/foo/app/public/baz.rb")
+        );
+    }
+
+    #[test]
+    fn test_public_folder_destination_with_custom_public_folder() {
+        let pack = Pack {
+            name: "packs/foo".to_owned(),
+            relative_path: PathBuf::from("packs/foo"),
+            public_folder: Some(PathBuf::from("packs/foo/app/api")),
+            ..Pack::default()
+        };
+
+        assert_eq!(
+            public_folder_destination(
+                &pack,
+                Path::new("packs/foo/app/models/foo.rb")
+            ),
+            PathBuf::from("packs/foo/app/api/foo.rb")
+        );
+    }
+}
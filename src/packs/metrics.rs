@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::Serialize;
+
+use super::constant_resolver::ConstantResolver;
+use super::Configuration;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetricsFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct PackMetrics {
+    pub pack: String,
+    pub files: usize,
+    /// Packs that declare a dependency on this pack
+    pub afferent_coupling: usize,
+    /// Packs this pack declares a dependency on
+    pub efferent_coupling: usize,
+    /// efferent / (afferent + efferent), unset for packs without coupling
+    pub instability: Option<f64>,
+    pub public_constants: usize,
+    pub private_constants: usize,
+    /// Violations recorded in package_todo.yml, by violation type
+    pub todo_violations: BTreeMap<String, usize>,
+}
+
+pub(crate) fn compute(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+) -> anyhow::Result<Vec<PackMetrics>> {
+    let pack_set = &configuration.pack_set;
+
+    let mut afferent: HashMap<&str, HashSet<&str>> = HashMap::new();
+    let mut efferent: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for dependency in pack_set.all_pack_dependencies(configuration)? {
+        let from = dependency.from_pack.name.as_str();
+        let to = dependency.to_pack.name.as_str();
+        if from != to {
+            efferent.entry(from).or_default().insert(to);
+            afferent.entry(to).or_default().insert(from);
+        }
+    }
+
+    // (public, private) constant names per pack
+    let mut constants: HashMap<&str, (HashSet<&str>, HashSet<&str>)> =
+        HashMap::new();
+    for (name, definitions) in constant_resolver
+        .fully_qualified_constant_name_to_constant_definition_map()
+    {
+        for definition in definitions {
+            let Some(pack) =
+                pack_set.for_file(&definition.absolute_path_of_definition)?
+            else {
+                continue;
+            };
+            let is_public = definition
+                .absolute_path_of_definition
+                .strip_prefix(&configuration.absolute_root)
+                .is_ok_and(|relative_path| {
+                    relative_path.starts_with(pack.public_folder())
+                });
+            let (public, private) =
+                constants.entry(pack.name.as_str()).or_default();
+            if is_public {
+                public.insert(name);
+            } else {
+                private.insert(name);
+            }
+        }
+    }
+
+    let mut metrics: Vec<PackMetrics> = pack_set
+        .packs
+        .iter()
+        .map(|pack| {
+            let afferent_coupling =
+                afferent.get(pack.name.as_str()).map_or(0, HashSet::len);
+            let efferent_coupling =
+                efferent.get(pack.name.as_str()).map_or(0, HashSet::len);
+            let coupling = afferent_coupling + efferent_coupling;
+
+            let mut todo_violations: BTreeMap<String, usize> = BTreeMap::new();
+            for violation_group in pack
+                .package_todo
+                .violations_by_defining_pack
+                .values()
+                .flat_map(|by_constant| by_constant.values())
+            {
+                for violation_type in &violation_group.violation_types {
+                    *todo_violations
+                        .entry(violation_type.clone())
+                        .or_default() += violation_group.files.len();
+                }
+            }
+
+            let (public, private) = constants
+                .get(pack.name.as_str())
+                .map_or((0, 0), |(public, private)| {
+                    (public.len(), private.len())
+                });
+
+            PackMetrics {
+                pack: pack.name.clone(),
+                files: pack_set.files_for_pack(&pack.name).len(),
+                afferent_coupling,
+                efferent_coupling,
+                instability: (coupling > 0)
+                    .then(|| efferent_coupling as f64 / coupling as f64),
+                public_constants: public,
+                private_constants: private,
+                todo_violations,
+            }
+        })
+        .collect();
+    metrics.sort_by(|a, b| a.pack.cmp(&b.pack));
+    Ok(metrics)
+}
+
+pub(crate) fn render(
+    metrics: &[PackMetrics],
+    format: MetricsFormat,
+) -> anyhow::Result<String> {
+    Ok(match format {
+        MetricsFormat::Json => serde_json::to_string_pretty(metrics)?,
+        MetricsFormat::Csv => rows(metrics)
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| csv_escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        MetricsFormat::Table => {
+            let rows = rows(metrics);
+            let widths: Vec<usize> = (0..rows[0].len())
+                .map(|column| {
+                    rows.iter().map(|row| row[column].len()).max().unwrap_or(0)
+                })
+                .collect();
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| format!("{:<width$}", cell))
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                        .trim_end()
+                        .to_owned()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    })
+}
+
+// A header row followed by one row per pack. Every violation type recorded
+// in any pack gets a `todo_<type>` column.
+fn rows(metrics: &[PackMetrics]) -> Vec<Vec<String>> {
+    let violation_types: BTreeSet<&String> = metrics
+        .iter()
+        .flat_map(|pack_metrics| pack_metrics.todo_violations.keys())
+        .collect();
+
+    let mut header: Vec<String> = [
+        "pack",
+        "files",
+        "afferent_coupling",
+        "efferent_coupling",
+        "instability",
+        "public_constants",
+        "private_constants",
+    ]
+    .iter()
+    .map(|column| column.to_string())
+    .collect();
+    header.extend(
+        violation_types
+            .iter()
+            .map(|violation_type| format!("todo_{}", violation_type)),
+    );
+
+    let mut rows = vec![header];
+    for pack_metrics in metrics {
+        let mut row = vec![
+            pack_metrics.pack.clone(),
+            pack_metrics.files.to_string(),
+            pack_metrics.afferent_coupling.to_string(),
+            pack_metrics.efferent_coupling.to_string(),
+            pack_metrics
+                .instability
+                .map_or(String::new(), |instability| {
+                    format!("{:.2}", instability)
+                }),
+            pack_metrics.public_constants.to_string(),
+            pack_metrics.private_constants.to_string(),
+        ];
+        row.extend(violation_types.iter().map(|violation_type| {
+            pack_metrics
+                .todo_violations
+                .get(*violation_type)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        }));
+        rows.push(row);
+    }
+    rows
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn metrics() -> Vec<PackMetrics> {
+        vec![
+            PackMetrics {
+                pack: "packs/bar".to_owned(),
+                files: 2,
+                afferent_coupling: 1,
+                efferent_coupling: 0,
+                instability: Some(0.0),
+                public_constants: 1,
+                private_constants: 1,
+                todo_violations: BTreeMap::new(),
+            },
+            PackMetrics {
+                pack: "packs/foo,old".to_owned(),
+                files: 10,
+                afferent_coupling: 0,
+                efferent_coupling: 0,
+                instability: None,
+                public_constants: 0,
+                private_constants: 3,
+                todo_violations: BTreeMap::from([("privacy".to_owned(), 2)]),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            render(&metrics(), MetricsFormat::Table).unwrap(),
+            "\
+pack           files  afferent_coupling  efferent_coupling  instability  public_constants  private_constants  todo_privacy
+packs/bar      2      1                  0                  0.00         1                 1                  0
+packs/foo,old  10     0                  0                               0                 3                  2"
+        );
+    }
+
+    #[test]
+    fn test_render_csv() {
+        assert_eq!(
+            render(&metrics(), MetricsFormat::Csv).unwrap(),
+            "\
+pack,files,afferent_coupling,efferent_coupling,instability,public_constants,private_constants,todo_privacy
+packs/bar,2,1,0,0.00,1,1,0
+\"packs/foo,old\",10,0,0,,0,3,2"
+        );
+    }
+}
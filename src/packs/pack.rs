@@ -118,6 +118,14 @@ pub struct Pack {
     )]
     pub enforce_folder_visibility: Option<CheckerSetting>, // deprecated
 
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_checker_setting",
+        deserialize_with = "deserialize_checker_setting"
+    )]
+    pub enforce_rules: Option<CheckerSetting>,
+
     #[serde(skip_serializing_if = "is_default_public_folder")]
     pub public_folder: Option<PathBuf>,
 
@@ -329,6 +337,15 @@ impl Pack {
         }
     }
 
+    // The `rules:` of packwerk.yml apply to every pack, unless it sets
+    // `enforce_rules: false`
+    pub(crate) fn enforce_rules(&self) -> &CheckerSetting {
+        match &self.enforce_rules {
+            Some(setting) => setting,
+            None => &CheckerSetting::True,
+        }
+    }
+
     pub(crate) fn public_folder(&self) -> PathBuf {
         match &self.public_folder {
             Some(folder) => folder.to_owned(),
@@ -342,6 +359,14 @@ impl Pack {
         new_pack
     }
 
+    pub(crate) fn add_ignored_private_constant(&self, constant: &str) -> Pack {
+        let mut new_pack = self.clone();
+        new_pack
+            .ignored_private_constants
+            .insert(constant.to_owned());
+        new_pack
+    }
+
     pub(crate) fn ignores_for_enforcement(
         &self,
         enforcement: &str,
@@ -407,6 +432,7 @@ const KEY_SORT_ORDER: &[&str] = &[
     "enforce_folder_privacy",
     "enforce_folder_visibility",
     "enforce_architecture",
+    "enforce_rules",
     "layer",
     "public_path",
     "dependencies",
@@ -6,7 +6,7 @@ use tracing::debug;
 
 use super::{pack::Pack, Configuration, Violation};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct UpdateStats {
     pub violations_added: usize,
     pub violations_removed: usize,
@@ -403,6 +403,15 @@ fn delete_package_todo_from_disk(responsible_pack: &Pack) {
     }
 }
 
+// Pack names are their paths relative to the project root, with "." as root
+pub(crate) fn package_todo_relative_path(pack_name: &str) -> String {
+    if pack_name == "." {
+        "package_todo.yml".to_owned()
+    } else {
+        format!("{}/package_todo.yml", pack_name)
+    }
+}
+
 fn header(responsible_pack_name: &String, packs_first_mode: bool) -> String {
     let command = if packs_first_mode {
         "pks update"
@@ -1,2 +1,339 @@
-pub(crate) mod experimental;
-pub(crate) mod packwerk;
+pub(crate) mod tokenizer;
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::tokenizer::extract_ruby;
+    use crate::packs::parsing::template::process_from_contents;
+    use crate::packs::parsing::{Range, ReferenceKind};
+    use crate::packs::{Configuration, Sigil, UnresolvedReference};
+
+    #[test]
+    fn trivial_case() {
+        let contents: String = String::from("<%= Foo %>");
+        let configuration = Configuration::default();
+
+        assert_eq!(
+            vec![UnresolvedReference {
+                name: String::from("Foo"),
+                namespace_path: vec![],
+                location: Range {
+                    start_row: 1,
+                    start_col: 4,
+                    end_row: 1,
+                    end_col: 8,
+                },
+                kind: ReferenceKind::Constant,
+            }],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn multiple_references() {
+        let contents: String = String::from("<%= Foo %><%= Bar %>");
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![
+                UnresolvedReference {
+                    name: String::from("Foo"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 1,
+                        start_col: 4,
+                        end_row: 1,
+                        end_col: 8,
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Bar"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 1,
+                        start_col: 14,
+                        end_row: 1,
+                        end_col: 18,
+                    },
+                    kind: ReferenceKind::Constant,
+                }
+            ],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+    #[test]
+    fn multiline_erb() {
+        let contents: String = String::from(
+            "/
+<%
+    Foo
+%>
+        ",
+        );
+
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![UnresolvedReference {
+                name: String::from("Foo"),
+                namespace_path: vec![],
+                location: Range {
+                    start_row: 3,
+                    start_col: 4,
+                    end_row: 3,
+                    end_col: 8,
+                },
+                kind: ReferenceKind::Constant,
+            }],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn erb_with_leading_hyphen_syntax() {
+        let contents: String = String::from(
+            "/
+  <%- Foo %>
+    <%= do_thing() %>
+  <%- end %>
+        ",
+        );
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![UnresolvedReference {
+                name: String::from("Foo"),
+                namespace_path: vec![],
+                location: Range {
+                    start_row: 2,
+                    start_col: 6,
+                    end_row: 2,
+                    end_col: 10,
+                },
+                kind: ReferenceKind::Constant,
+            }],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn erb_with_trailing_hyphen_syntax() {
+        let contents: String = String::from(
+            "/
+<% Foo %>
+<div>
+  <div>
+    <p>
+      <% if condition %>
+      <% else %>
+      <% end -%>
+    </p>
+  </div>
+</div>
+        ",
+        );
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![UnresolvedReference {
+                name: String::from("Foo"),
+                namespace_path: vec![],
+                location: Range {
+                    start_row: 2,
+                    start_col: 3,
+                    end_row: 2,
+                    end_col: 7,
+                },
+                kind: ReferenceKind::Constant,
+            }],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn complex_multiline_erb() {
+        let contents: String = String::from(
+            "/
+<%
+    # Comment
+    # Comment
+    Foo
+    # Comment
+    # Comment
+%>
+        ",
+        );
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![UnresolvedReference {
+                name: String::from("Foo"),
+                namespace_path: vec![],
+                location: Range {
+                    start_row: 5,
+                    start_col: 4,
+                    end_row: 5,
+                    end_col: 8,
+                },
+                kind: ReferenceKind::Constant,
+            }],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn complex_erb() {
+        let contents: String = String::from(
+            "/
+<!DOCTYPE html>
+<html>
+<head>
+  <title>ERB Snippet</title>
+</head>
+<body>
+  <% if Foo %>
+    <h1>Hello, World!</h1>
+  <% else %>
+    <p>Welcome to the ERB snippet!</p>
+  <% end %>
+
+  <% unless Bar.empty? %>
+    <ul>
+      <% Baz.each do |item| %>
+        <li><%= item %></li>
+      <% end %>
+    </ul>
+  <% end %>
+
+  <% for i in Boo %>
+    <p>Iteration <%= Bee %></p>
+  <% end %>
+</body>
+</html>
+        ",
+        );
+        let configuration = Configuration::default();
+        assert_eq!(
+            vec![
+                UnresolvedReference {
+                    name: String::from("Foo"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 8,
+                        start_col: 8,
+                        end_row: 8,
+                        end_col: 12,
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Bar"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 14,
+                        start_col: 12,
+                        end_row: 14,
+                        end_col: 16,
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Baz"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 16,
+                        start_col: 9,
+                        end_row: 16,
+                        end_col: 13,
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Boo"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 22,
+                        start_col: 14,
+                        end_row: 22,
+                        end_col: 18,
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Bee"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 23,
+                        start_col: 21,
+                        end_row: 23,
+                        end_col: 25,
+                    },
+                    kind: ReferenceKind::Constant,
+                }
+            ],
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+                extract_ruby,
+            )
+            .unresolved_references
+        );
+    }
+
+    #[test]
+    fn sigils_with_both_parsers() {
+        for experimental_parser in [false, true] {
+            let configuration = Configuration {
+                experimental_parser,
+                ..Configuration::default()
+            };
+            assert_eq!(
+                vec![Sigil {
+                    name: String::from("public"),
+                    value: true,
+                }],
+                process_from_contents(
+                    String::from("<%# pack_public: true %>\n<%= Foo %>"),
+                    &PathBuf::from("path/to/file.html.erb"),
+                    &configuration,
+                    extract_ruby,
+                )
+                .sigils
+            );
+        }
+    }
+}
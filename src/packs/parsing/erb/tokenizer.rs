@@ -0,0 +1,63 @@
+use line_col::LineColLookup;
+
+use crate::packs::parsing::source_map::EmbeddedRuby;
+
+/// Extracts the Ruby code of the `<% %>` and `<%= %>` tags of an ERB template,
+/// one tag per line, remembering where each tag's code starts in the template.
+/// Comments (`<%# %>`) and escaped tags (`<%%`) are skipped.
+pub(crate) fn extract_ruby(template: &str) -> EmbeddedRuby {
+    let lookup = LineColLookup::new(template);
+    let mut embedded_ruby = EmbeddedRuby::default();
+
+    let mut position = 0;
+    while let Some(tag_start) = template[position..].find("<%") {
+        let code_start = position + tag_start + 2;
+        if template[code_start..].starts_with('%') {
+            // `<%%` renders a literal `<%`
+            position = code_start + 1;
+            continue;
+        }
+        let Some(tag_length) = template[code_start..].find("%>") else {
+            break;
+        };
+        let code_end = code_start + tag_length;
+        position = code_end + 2;
+
+        let tag = &template[code_start..code_end];
+        if tag.starts_with('#') {
+            continue;
+        }
+        // `<%=`, `<%==` and `<%-` open a tag, `-%>` closes one
+        let code = tag
+            .strip_prefix("==")
+            .or_else(|| tag.strip_prefix('='))
+            .or_else(|| tag.strip_prefix('-'))
+            .unwrap_or(tag);
+        let code_offset = code_start + tag.len() - code.len();
+        let code = code.strip_suffix('-').unwrap_or(code);
+
+        let trimmed_code = code.trim();
+        if !trimmed_code.is_empty() {
+            let leading_whitespace = code.len() - code.trim_start().len();
+            embedded_ruby.push(
+                trimmed_code,
+                code_offset + leading_whitespace,
+                &lookup,
+            );
+        }
+    }
+
+    embedded_ruby
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ruby() {
+        let template = "<%# Comment %>\n<%% literal %>\n<p><%== Foo %></p>\n<%- if Bar -%>\n  <%\n    Baz\n  %>\n<% end %>\n";
+        let embedded_ruby = extract_ruby(template);
+        assert_eq!(embedded_ruby.ruby, "Foo\nif Bar\nBaz\nend");
+    }
+}
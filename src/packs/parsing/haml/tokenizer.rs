@@ -0,0 +1,225 @@
+use crate::packs::parsing::source_map::EmbeddedRuby;
+use crate::packs::parsing::template::{
+    block_end, expression_end, line_index_of, lines, statement_end, Line,
+    TemplateRuby,
+};
+
+// A line of Ruby code goes on while it ends with a comma
+const CONTINUATIONS: [char; 1] = [','];
+
+/// Extracts the Ruby code of a HAML template: `-` and `=` lines, attribute
+/// hashes, object references, `#{}` interpolations and `:ruby` filters.
+/// Comments (`-#`) and the text of other filters are skipped, apart from
+/// their interpolations.
+pub(crate) fn extract_ruby(template: &str) -> EmbeddedRuby {
+    let lines = lines(template);
+    let mut ruby = TemplateRuby::new(template);
+
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.is_blank() {
+            index += 1;
+            continue;
+        }
+
+        let content = &template[line.content_start..line.end];
+        index = if content.starts_with("-#") {
+            ruby.close_blocks(line.indent, false);
+            block_end(&lines, index)
+        } else if let Some(filter) = content.strip_prefix(':') {
+            ruby.close_blocks(line.indent, false);
+            let end = block_end(&lines, index);
+            if filter.trim() == "ruby" {
+                ruby.push_lines(&lines[index + 1..end]);
+            } else {
+                for filtered_line in &lines[index + 1..end] {
+                    ruby.push_interpolations(
+                        filtered_line.content_start,
+                        filtered_line.end,
+                    );
+                }
+            }
+            end
+        } else if content.starts_with('/') || content.starts_with("!!!") {
+            ruby.close_blocks(line.indent, false);
+            index + 1
+        } else {
+            process_content(
+                &mut ruby,
+                template,
+                &lines,
+                index,
+                line.content_start,
+            )
+        };
+    }
+
+    ruby.finish()
+}
+
+// Processes a line from `start`, returning the index of the next line to
+// process
+fn process_content(
+    ruby: &mut TemplateRuby,
+    template: &str,
+    lines: &[Line],
+    index: usize,
+    start: usize,
+) -> usize {
+    let indent = lines[index].indent;
+    let content = &template[start..lines[index].end];
+
+    if let Some(prefix_length) = script_prefix_length(content) {
+        let (end, last_index) =
+            statement_end(template, lines, index, &CONTINUATIONS);
+        ruby.push_statement(indent, start + prefix_length, end);
+        return last_index + 1;
+    }
+
+    ruby.close_blocks(indent, false);
+    let is_element = content.starts_with('%')
+        || content.starts_with('.')
+        || (content.starts_with('#') && !content.starts_with("#{"));
+    if !is_element {
+        let text_start = if content.starts_with('\\') {
+            start + 1
+        } else {
+            start
+        };
+        ruby.push_interpolations(text_start, lines[index].end);
+        return index + 1;
+    }
+
+    let position = element(ruby, template, start);
+    let last_index = line_index_of(lines, index, position);
+    let rest = &template[position..lines[last_index].end];
+    let rest_start = position + rest.len() - rest.trim_start().len();
+    if rest_start < lines[last_index].end {
+        process_content(ruby, template, lines, last_index, rest_start)
+    } else {
+        last_index + 1
+    }
+}
+
+// `-`, `=`, `!=`, `&=` and `~` start a line of Ruby code, `==` starts text
+fn script_prefix_length(content: &str) -> Option<usize> {
+    if content.starts_with("==") {
+        return None;
+    }
+    ["-", "!=", "&=", "=", "~"]
+        .into_iter()
+        .find(|prefix| content.starts_with(prefix))
+        .map(str::len)
+}
+
+// Skips over the tag, classes and ids of an element starting at `start`,
+// pushing the code of its attributes. Returns the offset of what follows.
+fn element(ruby: &mut TemplateRuby, template: &str, start: usize) -> usize {
+    let bytes = template.as_bytes();
+    let mut position = start;
+    while matches!(bytes.get(position), Some(b'%' | b'.' | b'#'))
+        && !template[position..].starts_with("#{")
+    {
+        position += 1;
+        while bytes.get(position).is_some_and(|byte| {
+            byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b':')
+        }) {
+            position += 1;
+        }
+    }
+
+    loop {
+        let opening = bytes.get(position).copied();
+        if !matches!(opening, Some(b'{' | b'(' | b'[')) {
+            break;
+        }
+        let end = (expression_end(template, position + 1, false) + 1)
+            .min(bytes.len());
+        if opening == Some(b'(') {
+            // HTML style attributes, whose values are mostly strings
+            ruby.push_interpolations(position, end);
+        } else {
+            // A hash of attributes or an object reference
+            ruby.push_expression(position, end);
+        }
+        position = end;
+    }
+
+    // Whitespace removal and self-closing markers
+    while matches!(bytes.get(position), Some(b'<' | b'>' | b'/')) {
+        position += 1;
+    }
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::parsing::Range;
+
+    #[test]
+    fn test_extract_ruby() {
+        let template = [
+            "!!!",
+            "-# Comment mentioning Foo",
+            "  and Bar",
+            "%div{ class: Classes::Foo, data: {",
+            "  id: Ids::Bar } }",
+            "  %p.intro#main= Greeting.new(user,",
+            "    name)",
+            "  - if admin?",
+            "    %span Hello #{Admin.name}",
+            "  - else",
+            "    = render Other",
+            "  - items.each do |item|",
+            "    %li[item]",
+            "/ An HTML comment",
+            ":ruby",
+            "  x = Baz.new",
+            "  y = x",
+            ":javascript",
+            "  var a = #{Qux.to_json};",
+        ]
+        .join("\n");
+        let embedded_ruby = extract_ruby(&template);
+        assert_eq!(
+            embedded_ruby.ruby,
+            [
+                "{ class: Classes::Foo, data: {",
+                "  id: Ids::Bar } }",
+                "Greeting.new(user,",
+                "    name)",
+                "if admin?",
+                "Admin.name",
+                "else",
+                "render Other",
+                "end",
+                "items.each do |item|",
+                "[item]",
+                "end",
+                "x = Baz.new",
+                "  y = x",
+                "Qux.to_json",
+            ]
+            .join("\n")
+        );
+
+        let location = |start_row, start_col, end_col| Range {
+            start_row,
+            start_col,
+            end_row: start_row,
+            end_col,
+        };
+        // `Admin` on line 6 of the Ruby
+        assert_eq!(
+            embedded_ruby.map_range(&location(6, 0, 6)),
+            location(9, 18, 24)
+        );
+        // `Ids` on line 2 of the Ruby
+        assert_eq!(
+            embedded_ruby.map_range(&location(2, 6, 10)),
+            location(5, 6, 10)
+        );
+    }
+}
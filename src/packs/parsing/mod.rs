@@ -1,21 +1,24 @@
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 pub(crate) mod ruby;
-pub(crate) use ruby::experimental::parser::process_from_path as process_from_ruby_path_experimental;
-pub(crate) use ruby::packwerk::parser::process_from_path as process_from_ruby_path;
+pub(crate) use ruby::process_from_contents as process_from_ruby_contents;
+pub(crate) use ruby::process_from_path as process_from_ruby_path;
 mod erb;
-pub(crate) use erb::experimental::parser::process_from_path as process_from_erb_path_experimental;
-pub(crate) use erb::packwerk::parser::process_from_path as process_from_erb_path;
+mod haml;
+mod slim;
+pub(crate) mod source_map;
+pub(crate) mod template;
 
 use crate::packs::file_utils::is_stdin_file;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    caching::{cache::Cache, CacheResult},
+    caching::{cache::Cache, maintenance::RunStats, CacheResult},
     file_utils::{get_file_type, SupportedFileType},
     Configuration, ProcessedFile,
 };
@@ -32,19 +35,23 @@ pub fn process_file(
     let result = if let Some(file_type) = file_type_option {
         match file_type {
             SupportedFileType::Ruby => {
-                if configuration.experimental_parser {
-                    process_from_ruby_path_experimental(path, configuration)
-                } else {
-                    process_from_ruby_path(path, configuration)
-                }
-            }
-            SupportedFileType::Erb => {
-                if configuration.experimental_parser {
-                    process_from_erb_path_experimental(path, configuration)
-                } else {
-                    process_from_erb_path(path, configuration)
-                }
+                process_from_ruby_path(path, configuration)
             }
+            SupportedFileType::Erb => template::process_from_path(
+                path,
+                configuration,
+                erb::tokenizer::extract_ruby,
+            ),
+            SupportedFileType::Haml => template::process_from_path(
+                path,
+                configuration,
+                haml::tokenizer::extract_ruby,
+            ),
+            SupportedFileType::Slim => template::process_from_path(
+                path,
+                configuration,
+                slim::tokenizer::extract_ruby,
+            ),
         }
     } else {
         // Later, we can perhaps have this error, since in theory the Configuration.intersect
@@ -54,6 +61,8 @@ pub fn process_file(
             unresolved_references: vec![],
             definitions: vec![], // TODO
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         })
     };
 
@@ -69,6 +78,32 @@ pub struct UnresolvedReference {
     pub name: String,
     pub namespace_path: Vec<String>,
     pub location: Range,
+    #[serde(default)]
+    pub kind: ReferenceKind,
+}
+
+/// How a constant is referred to in the code
+#[derive(
+    Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// A constant, e.g. `Foo::Bar`, or an association
+    #[default]
+    Constant,
+    /// A string literal naming a constant, e.g. `"Foo::Bar".constantize`
+    String,
+}
+
+/// The calls whose string literals name constants. Both are empty unless
+/// `string_references` is enabled.
+#[derive(Debug, Default, Clone)]
+pub struct StringReferenceCalls {
+    // Methods called on the string (`"Foo".constantize`) or with it as their
+    // first argument (`Object.const_get("Foo")`)
+    pub methods: Vec<String>,
+    // Keyword arguments whose value is the string (`class_name: "Foo"`)
+    pub keywords: Vec<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Default)]
@@ -79,6 +114,14 @@ pub struct Range {
     pub end_col: usize,
 }
 
+/// A syntax error reported by the parser. The references of a file with
+/// parse errors may be incomplete.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct ParseDiagnostic {
+    pub message: String,
+    pub location: Range,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
 pub struct ParsedDefinition {
     pub fully_qualified_name: String,
@@ -90,7 +133,18 @@ pub fn process_files_with_cache(
     cache: Box<dyn Cache + Send + Sync>,
     configuration: &Configuration,
 ) -> anyhow::Result<Vec<ProcessedFile>> {
-    paths
+    Ok(process_files_with_cache_and_stats(paths, cache, configuration)?.0)
+}
+
+/// Like `process_files_with_cache`, also returning the cache hits and misses
+pub(crate) fn process_files_with_cache_and_stats(
+    paths: &HashSet<PathBuf>,
+    cache: Box<dyn Cache + Send + Sync>,
+    configuration: &Configuration,
+) -> anyhow::Result<(Vec<ProcessedFile>, RunStats)> {
+    let hits = AtomicUsize::new(0);
+    let misses = AtomicUsize::new(0);
+    let processed_files = paths
         .par_iter()
         .map(|absolute_path| -> anyhow::Result<ProcessedFile> {
             if is_stdin_file(absolute_path, configuration) {
@@ -98,9 +152,11 @@ pub fn process_files_with_cache(
             } else {
                 match cache.get(absolute_path)? {
                     CacheResult::Processed(processed_file) => {
+                        hits.fetch_add(1, Ordering::Relaxed);
                         Ok(processed_file)
                     }
                     CacheResult::Miss(empty_cache_entry) => {
+                        misses.fetch_add(1, Ordering::Relaxed);
                         let processed_file =
                             process_file(absolute_path, configuration)?;
                         cache.write(&empty_cache_entry, &processed_file)?;
@@ -109,7 +165,13 @@ pub fn process_files_with_cache(
                 }
             }
         })
-        .collect()
+        .collect::<anyhow::Result<Vec<ProcessedFile>>>()?;
+    cache.flush()?;
+    let run_stats = RunStats {
+        hits: hits.into_inner(),
+        misses: misses.into_inner(),
+    };
+    Ok((processed_files, run_stats))
 }
 
 #[cfg(test)]
@@ -144,4 +206,16 @@ mod tests {
     fn identifies_erb_files() {
         assert_is_erb("foo.erb");
     }
+
+    #[test]
+    fn identifies_haml_and_slim_files() {
+        assert_eq!(
+            Some(SupportedFileType::Haml),
+            get_file_type(Path::new("foo.html.haml"))
+        );
+        assert_eq!(
+            Some(SupportedFileType::Slim),
+            get_file_type(Path::new("foo.html.slim"))
+        );
+    }
 }
@@ -50,7 +50,7 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::packs::parsing::ruby::experimental::parser::process_from_contents;
-    use crate::packs::parsing::{ParsedDefinition, Range};
+    use crate::packs::parsing::{ParsedDefinition, Range, ReferenceKind};
     use crate::packs::{
         Configuration, ProcessedFile, Sigil, UnresolvedReference,
     };
@@ -71,6 +71,7 @@ mod tests {
                 end_row: 1,
                 end_col: 4,
             },
+            kind: ReferenceKind::Constant,
         }];
 
         let definitions = vec![];
@@ -82,6 +83,8 @@ mod tests {
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -101,6 +104,7 @@ mod tests {
                 end_row: 1,
                 end_col: 9,
             },
+            kind: ReferenceKind::Constant,
         }];
 
         let definitions = vec![];
@@ -112,6 +116,8 @@ mod tests {
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -132,6 +138,7 @@ mod tests {
                 end_row: 1,
                 end_col: 14,
             },
+            kind: ReferenceKind::Constant,
         }];
 
         let definitions = vec![];
@@ -143,6 +150,8 @@ mod tests {
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -163,6 +172,7 @@ mod tests {
                 end_row: 1,
                 end_col: 19,
             },
+            kind: ReferenceKind::Constant,
         }];
 
         let definitions = vec![];
@@ -174,6 +184,8 @@ mod tests {
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -201,6 +213,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -238,6 +252,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -275,6 +291,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -311,6 +329,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -351,6 +371,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
         assert_eq!(expected, actual);
     }
@@ -392,6 +414,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
 
         assert_eq!(expected, actual);
@@ -434,6 +458,8 @@ end
             unresolved_references,
             definitions,
             sigils: vec![],
+            suppressions: vec![],
+            parse_errors: vec![],
         };
 
         assert_eq!(expected, actual);
@@ -459,7 +485,8 @@ end
                     start_col: 0,
                     end_row: 1,
                     end_col: 4
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
         );
     }
@@ -1,13 +1,20 @@
-use crate::packs::file_utils::file_read_contents;
-use crate::packs::parsing::ruby::parse_utils::extract_sigils_from_contents;
+use crate::packs::parsing::ruby::parse_utils::{
+    extract_parse_errors, extract_sigils_from_contents,
+    extract_suppressions_from_contents,
+};
 use crate::packs::{
     parsing::{
-        ruby::parse_utils::{
-            fetch_const_const_name, fetch_const_name, fetch_node_location,
-            get_constant_assignment_definition, get_definition_from,
-            get_reference_from_active_record_association, loc_to_range,
+        ruby::{
+            inflector_shim::Inflections,
+            parse_utils::{
+                fetch_const_const_name, fetch_const_name, fetch_node_location,
+                get_constant_assignment_definition, get_definition_from,
+                get_reference_from_active_record_association,
+                get_references_from_strings, loc_to_range,
+            },
         },
-        ParsedDefinition, UnresolvedReference,
+        ParsedDefinition, ReferenceKind, StringReferenceCalls,
+        UnresolvedReference,
     },
     Configuration, ProcessedFile,
 };
@@ -24,6 +31,8 @@ struct ReferenceCollector<'a> {
     pub line_col_lookup: LineColLookup<'a>,
     pub behavioral_change_in_namespace: bool,
     pub custom_associations: Vec<String>,
+    pub inflections: Inflections,
+    pub string_reference_calls: StringReferenceCalls,
     pub is_spec_file: bool,
 }
 
@@ -89,10 +98,18 @@ impl<'a> Visitor for ReferenceCollector<'a> {
                     &self.current_namespaces,
                     &self.line_col_lookup,
                     &self.custom_associations,
+                    &self.inflections,
                 );
 
             if let Some(association_reference) = association_reference {
                 self.references.push(association_reference);
+            } else {
+                self.references.extend(get_references_from_strings(
+                    node,
+                    &self.current_namespaces,
+                    &self.line_col_lookup,
+                    &self.string_reference_calls,
+                ));
             }
 
             lib_ruby_parser::traverse::visitor::visit_send(self, node);
@@ -173,6 +190,7 @@ impl<'a> Visitor for ReferenceCollector<'a> {
             name,
             namespace_path,
             location: loc_to_range(&node.expression_l, &self.line_col_lookup),
+            kind: ReferenceKind::Constant,
         })
     }
 
@@ -191,12 +209,10 @@ impl<'a> Visitor for ReferenceCollector<'a> {
     }
 }
 
-pub(crate) fn process_from_path(
-    path: &Path,
-    configuration: &Configuration,
-) -> anyhow::Result<ProcessedFile> {
-    let contents = file_read_contents(path, configuration)?;
-    Ok(process_from_contents(contents, path, configuration))
+// Spec files are parsed differently, see `process_from_contents`
+pub(crate) fn is_spec_file(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.contains("_spec.rb") || path.contains("/spec/")
 }
 
 pub(crate) fn process_from_contents(
@@ -212,6 +228,7 @@ pub(crate) fn process_from_contents(
     let lookup = LineColLookup::new(&contents);
     let parser = Parser::new(contents.clone(), options);
     let parse_result = parser.do_parse();
+    let parse_errors = extract_parse_errors(&parse_result.diagnostics, &lookup);
 
     let ast_option: Option<Box<Node>> = parse_result.ast;
 
@@ -223,6 +240,8 @@ pub(crate) fn process_from_contents(
                 unresolved_references: vec![],
                 definitions: vec![],
                 sigils: vec![],
+                suppressions: vec![],
+                parse_errors,
             }
         }
     };
@@ -246,8 +265,7 @@ pub(crate) fn process_from_contents(
 
        To address this, we disable the monkey patch detection in spec files.
     */
-    let is_spec_file = path.to_string_lossy().contains("_spec.rb")
-        || path.to_string_lossy().contains("/spec/");
+    let is_spec_file = is_spec_file(path);
 
     let mut collector = ReferenceCollector {
         references: vec![],
@@ -256,6 +274,8 @@ pub(crate) fn process_from_contents(
         line_col_lookup: lookup,
         behavioral_change_in_namespace: false,
         custom_associations: configuration.custom_associations.clone(),
+        inflections: configuration.inflections.clone(),
+        string_reference_calls: configuration.string_reference_calls.clone(),
         is_spec_file,
     };
 
@@ -271,11 +291,14 @@ pub(crate) fn process_from_contents(
     let definitions = collector.definitions;
 
     let sigils = extract_sigils_from_contents(&contents);
+    let suppressions = extract_suppressions_from_contents(&contents);
 
     ProcessedFile {
         absolute_path,
         unresolved_references,
         definitions,
         sigils,
+        suppressions,
+        parse_errors,
     }
 }
@@ -14,14 +14,199 @@ const CLASS_CASE_TO_SINGULAR: [(&str, &str); 4] = [
     ("Daum", "Datum"),
 ];
 
+/// The custom inflections of an app, defined with
+/// `ActiveSupport::Inflector.inflections` in
+/// `config/initializers/inflections.rb`. They are applied on top of the
+/// inflector's defaults.
+#[derive(Debug, Default, Clone)]
+pub struct Inflections {
+    pub acronyms: HashSet<String>,
+    // Newest first, since later rules take precedence
+    singulars: Vec<(Regex, String)>,
+    uncountables: HashSet<String>,
+}
+
+/// The rule of `inflect.singular` or `inflect.plural`
+pub(crate) enum InflectionRule {
+    // Matches literally, e.g. `"people"`
+    Literal(String),
+    // e.g. `/(ox)en$/i`
+    Pattern(Regex),
+}
+
+impl Inflections {
+    pub(crate) fn add_acronym(&mut self, word: &str) {
+        self.acronyms.insert(word.to_owned());
+    }
+
+    pub(crate) fn add_singular(
+        &mut self,
+        rule: InflectionRule,
+        replacement: &str,
+    ) {
+        let rule = match rule {
+            InflectionRule::Literal(literal) => {
+                self.uncountables.remove(&literal);
+                Regex::new(&regex::escape(&literal)).unwrap()
+            }
+            InflectionRule::Pattern(pattern) => pattern,
+        };
+        self.uncountables.remove(replacement);
+        self.singulars
+            .insert(0, (rule, to_regex_replacement(replacement)));
+    }
+
+    // Constant names are never pluralized, so plural rules only matter for
+    // the uncountable words they remove
+    pub(crate) fn add_plural(
+        &mut self,
+        rule: InflectionRule,
+        replacement: &str,
+    ) {
+        if let InflectionRule::Literal(literal) = rule {
+            self.uncountables.remove(&literal);
+        }
+        self.uncountables.remove(replacement);
+    }
+
+    // Like `ActiveSupport::Inflector::Inflections#irregular`, which keeps the
+    // case of the first letter
+    pub(crate) fn add_irregular(&mut self, singular: &str, plural: &str) {
+        self.uncountables.remove(singular);
+        self.uncountables.remove(plural);
+
+        let (Some(singular_first), Some(plural_first)) =
+            (singular.chars().next(), plural.chars().next())
+        else {
+            return;
+        };
+        let singular_rest = &singular[singular_first.len_utf8()..];
+        let plural_rest = &plural[plural_first.len_utf8()..];
+
+        if singular_first
+            .to_lowercase()
+            .eq(plural_first.to_lowercase())
+        {
+            for word in [plural, singular] {
+                let first = word.chars().next().unwrap();
+                let rest = &word[first.len_utf8()..];
+                let rule = format!(
+                    "(?i)({}){}$",
+                    regex::escape(&first.to_string()),
+                    regex::escape(rest)
+                );
+                self.add_singular(
+                    InflectionRule::Pattern(Regex::new(&rule).unwrap()),
+                    &format!("\\1{}", singular_rest),
+                );
+            }
+        } else {
+            for (first, rest, singular_first) in [
+                (
+                    plural_first.to_uppercase().to_string(),
+                    plural_rest,
+                    singular_first.to_uppercase().to_string(),
+                ),
+                (
+                    plural_first.to_lowercase().to_string(),
+                    plural_rest,
+                    singular_first.to_lowercase().to_string(),
+                ),
+                (
+                    singular_first.to_uppercase().to_string(),
+                    singular_rest,
+                    singular_first.to_uppercase().to_string(),
+                ),
+                (
+                    singular_first.to_lowercase().to_string(),
+                    singular_rest,
+                    singular_first.to_lowercase().to_string(),
+                ),
+            ] {
+                let rule = format!(
+                    "{}(?i:{})$",
+                    regex::escape(&first),
+                    regex::escape(rest)
+                );
+                self.add_singular(
+                    InflectionRule::Pattern(Regex::new(&rule).unwrap()),
+                    &format!("{}{}", singular_first, singular_rest),
+                );
+            }
+        }
+    }
+
+    pub(crate) fn add_uncountable(&mut self, word: &str) {
+        self.uncountables.insert(word.to_lowercase());
+    }
+
+    /// Singularizes `word` with the custom inflections, or returns `None` if
+    /// none of them apply
+    pub(crate) fn singularize(&self, word: &str) -> Option<String> {
+        if self.is_uncountable(word) {
+            return Some(word.to_owned());
+        }
+        self.singulars
+            .iter()
+            .find(|(rule, _)| rule.is_match(word))
+            .map(|(rule, replacement)| {
+                rule.replace(word, replacement.as_str()).into_owned()
+            })
+    }
+
+    // Whether the last word of `word` is uncountable
+    fn is_uncountable(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.uncountables.iter().any(|uncountable| {
+            word.strip_suffix(uncountable.as_str())
+                .is_some_and(|before| {
+                    !before
+                        .chars()
+                        .last()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                })
+        })
+    }
+}
+
+// Ruby replacements refer to groups as `\1`, Rust ones as `${1}`
+fn to_regex_replacement(replacement: &str) -> String {
+    let mut converted = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    converted.push_str(&format!("${{{}}}", digit));
+                    chars.next();
+                }
+                Some('&') => {
+                    converted.push_str("${0}");
+                    chars.next();
+                }
+                _ => converted.push(c),
+            },
+            '$' => converted.push_str("$$"),
+            _ => converted.push(c),
+        }
+    }
+    converted
+}
+
 // See https://github.com/whatisinternet/Inflector/pull/87
 // Note that as of the PR that adds this comment, we are now using https://github.com/alexevanczuk/ruby_inflector,
 // so that we have an easier time making this inflector more specific to ruby applications (for now)
 pub fn to_class_case(
     s: &str,
     should_singularize: bool,
-    acronyms: &HashSet<String>,
+    inflections: &Inflections,
 ) -> String {
+    if should_singularize {
+        if let Some(singular) = inflections.singularize(s) {
+            return to_class_case(&singular, false, inflections);
+        }
+    }
+    let acronyms = &inflections.acronyms;
     let options = CamelOptions {
         new_word: true,
         last_char: ' ',
@@ -140,15 +325,18 @@ mod tests {
 
     #[test]
     fn test_trivial() {
-        let actual = to_class_case("my_string", false, &HashSet::new());
+        let actual = to_class_case("my_string", false, &Inflections::default());
         let expected = "MyString";
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_digits() {
-        let actual =
-            to_class_case("my_string_401k_thing", false, &HashSet::new());
+        let actual = to_class_case(
+            "my_string_401k_thing",
+            false,
+            &Inflections::default(),
+        );
         let expected = "MyString401kThing";
         assert_eq!(expected, actual);
     }
@@ -177,8 +365,11 @@ mod tests {
         ];
 
         for (input, should_singularize, expected) in tests {
-            let actual =
-                to_class_case(input, should_singularize, &HashSet::new());
+            let actual = to_class_case(
+                input,
+                should_singularize,
+                &Inflections::default(),
+            );
             assert_eq!(
                 expected, actual,
                 "Failed for input: {}, and singularize: {}",
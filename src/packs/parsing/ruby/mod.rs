@@ -1,8 +1,78 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::packs::{
+    file_utils::file_read_contents, Configuration, ProcessedFile,
+};
+
 pub(crate) mod experimental;
-mod inflector_shim;
+pub(crate) mod inflector_shim;
 mod namespace_calculator;
 pub(crate) mod packwerk;
 pub(crate) mod parse_utils;
-mod rails_utils;
+#[cfg(feature = "prism")]
+pub(crate) mod prism;
+pub(crate) mod rails_utils;
 mod ruby_utils;
 pub(crate) mod zeitwerk;
+
+/// The library Ruby is parsed with. Either one collects references the way
+/// the packwerk or the experimental parser does.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RubyParser {
+    /// `lib-ruby-parser`
+    #[default]
+    LibRubyParser,
+    /// Prism, the parser that ships with Ruby. Needs `pks` to be built with
+    /// the `prism` feature.
+    Prism,
+}
+
+impl RubyParser {
+    /// Whether this build of `pks` can parse with this parser
+    pub(crate) fn is_available(self) -> bool {
+        match self {
+            RubyParser::LibRubyParser => true,
+            RubyParser::Prism => cfg!(feature = "prism"),
+        }
+    }
+}
+
+pub(crate) fn process_from_path(
+    path: &Path,
+    configuration: &Configuration,
+) -> anyhow::Result<ProcessedFile> {
+    let contents = file_read_contents(path, configuration)?;
+    Ok(process_from_contents(contents, path, configuration))
+}
+
+/// Collects the references of Ruby code with the parser and the mode (packwerk
+/// or experimental) selected by the configuration
+pub(crate) fn process_from_contents(
+    contents: String,
+    path: &Path,
+    configuration: &Configuration,
+) -> ProcessedFile {
+    #[cfg(feature = "prism")]
+    if configuration.ruby_parser == RubyParser::Prism {
+        return prism::parser::process_from_contents(
+            contents,
+            path,
+            configuration,
+        );
+    }
+
+    if configuration.experimental_parser {
+        experimental::parser::process_from_contents(
+            contents,
+            path,
+            configuration,
+        )
+    } else {
+        packwerk::parser::process_from_contents(contents, path, configuration)
+    }
+}
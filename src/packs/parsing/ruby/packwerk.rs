@@ -5,8 +5,10 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::packs::parsing::ruby::packwerk::parser::process_from_contents;
-    use crate::packs::parsing::Range;
-    use crate::packs::{Configuration, Sigil, UnresolvedReference};
+    use crate::packs::parsing::{Range, ReferenceKind, StringReferenceCalls};
+    use crate::packs::{
+        Configuration, Sigil, Suppression, UnresolvedReference,
+    };
 
     #[test]
     fn trivial_case() {
@@ -21,7 +23,8 @@ mod tests {
                     start_col: 0,
                     end_row: 1,
                     end_col: 4
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
             process_from_contents(
                 contents,
@@ -45,7 +48,8 @@ mod tests {
                     start_col: 0,
                     end_row: 1,
                     end_col: 9
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
             process_from_contents(
                 contents,
@@ -69,7 +73,8 @@ mod tests {
                     start_col: 0,
                     end_row: 1,
                     end_col: 14
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
             process_from_contents(
                 contents,
@@ -93,7 +98,8 @@ mod tests {
                     start_col: 0,
                     end_row: 1,
                     end_col: 19
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
             process_from_contents(
                 contents,
@@ -123,7 +129,8 @@ end
                     start_col: 6,
                     end_row: 1,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
             process_from_contents(
                 contents,
@@ -154,7 +161,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 6
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -189,7 +197,8 @@ end
                     start_col: 4,
                     end_row: 3,
                     end_col: 8
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -230,7 +239,8 @@ end
                     start_col: 6,
                     end_row: 4,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -264,7 +274,8 @@ end
                         start_col: 7,
                         end_row: 1,
                         end_col: 11
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 },
                 UnresolvedReference {
                     name: String::from("Bar"),
@@ -274,7 +285,8 @@ end
                         start_col: 2,
                         end_row: 2,
                         end_col: 6
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 }
             ],
             process_from_contents(
@@ -308,7 +320,8 @@ end
                     start_col: 4,
                     end_row: 3,
                     end_col: 8
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -349,7 +362,8 @@ end
                     start_col: 6,
                     end_row: 4,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -391,6 +405,7 @@ end
                     end_row: 4,
                     end_col: 10
                 },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -424,7 +439,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 6
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -459,7 +475,8 @@ end
                     start_col: 9,
                     end_row: 2,
                     end_col: 13
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *process_from_contents(
                 contents,
@@ -499,7 +516,8 @@ end
                     start_col: 1,
                     end_row: 1,
                     end_col: 5
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference
         );
@@ -530,7 +548,8 @@ end
                     start_col: 1,
                     end_row: 1,
                     end_col: 5
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference1
         );
@@ -547,7 +566,8 @@ end
                     start_col: 6,
                     end_row: 1,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference2,
         );
@@ -579,7 +599,8 @@ end
                     start_col: 1,
                     end_row: 1,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference,
         );
@@ -611,7 +632,8 @@ end
                     start_col: 0,
                     end_row: 1,
                     end_col: 6
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference,
         );
@@ -653,7 +675,8 @@ end
                     start_col: 0,
                     end_row: 1,
                     end_col: 4
-                }
+                },
+                kind: ReferenceKind::Constant,
             }],
         );
     }
@@ -687,7 +710,8 @@ end
                     start_col: 6,
                     end_row: 1,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             }]
         )
     }
@@ -724,7 +748,8 @@ end
                         start_col: 6,
                         end_row: 1,
                         end_col: 10
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 },
                 UnresolvedReference {
                     name: String::from("::Foo::Baz"),
@@ -734,7 +759,8 @@ end
                         start_col: 8,
                         end_row: 2,
                         end_col: 12
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 }
             ]
         );
@@ -770,7 +796,8 @@ end
                     start_col: 12,
                     end_row: 1,
                     end_col: 16
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -805,7 +832,8 @@ end
                     start_col: 6,
                     end_row: 1,
                     end_col: 15
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -841,7 +869,8 @@ end
                         start_col: 6,
                         end_row: 1,
                         end_col: 10
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 },
                 UnresolvedReference {
                     name: String::from("::Foo::Bar"),
@@ -851,7 +880,8 @@ end
                         start_col: 8,
                         end_row: 2,
                         end_col: 12
-                    }
+                    },
+                    kind: ReferenceKind::Constant,
                 }
             ]
         );
@@ -886,7 +916,8 @@ FOO = BAR
                     start_col: 6,
                     end_row: 1,
                     end_col: 10
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference
         )
@@ -925,7 +956,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 27
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -964,7 +996,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 47
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1002,7 +1035,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 29
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1041,7 +1075,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 24
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1077,7 +1112,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 21
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1114,7 +1150,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 22
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1150,7 +1187,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 17
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1189,12 +1227,84 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 25
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
     }
 
+    #[test]
+    fn string_references() {
+        let contents: String = String::from(
+            r#"class Foo
+  "Bar::Baz".constantize
+  Object.const_get("Qux")
+  "not a constant".constantize
+  register :thing, class_name: "Quux"
+  has_one :thing, class_name: "Corge"
+end
+        "#,
+        );
+        let configuration = Configuration {
+            string_reference_calls: StringReferenceCalls {
+                methods: vec!["constantize".to_owned(), "const_get".to_owned()],
+                keywords: vec!["class_name".to_owned()],
+            },
+            ..Configuration::default()
+        };
+
+        let string_references: Vec<UnresolvedReference> =
+            process_from_contents(
+                contents.clone(),
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+            )
+            .unresolved_references
+            .into_iter()
+            .filter(|reference| reference.kind == ReferenceKind::String)
+            .collect();
+        let location = |start_row, start_col, end_col| Range {
+            start_row,
+            start_col,
+            end_row: start_row,
+            end_col,
+        };
+        assert_eq!(
+            string_references,
+            vec![
+                UnresolvedReference {
+                    name: String::from("Bar::Baz"),
+                    namespace_path: vec![],
+                    location: location(2, 2, 13),
+                    kind: ReferenceKind::String,
+                },
+                UnresolvedReference {
+                    name: String::from("Qux"),
+                    namespace_path: vec![],
+                    location: location(3, 19, 25),
+                    kind: ReferenceKind::String,
+                },
+                UnresolvedReference {
+                    name: String::from("Quux"),
+                    namespace_path: vec![String::from("Foo")],
+                    location: location(5, 31, 38),
+                    kind: ReferenceKind::String,
+                },
+            ]
+        );
+
+        // Disabled by default
+        assert!(process_from_contents(
+            contents,
+            &PathBuf::from("path/to/file.rb"),
+            &Configuration::default(),
+        )
+        .unresolved_references
+        .iter()
+        .all(|reference| reference.kind == ReferenceKind::Constant));
+    }
+
     #[test]
     fn has_many_association_with_class_name_after_block() {
         let contents: String = String::from(
@@ -1225,7 +1335,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 72
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *first_reference,
         );
@@ -1263,7 +1374,8 @@ end
                     start_col: 2,
                     end_row: 2,
                     end_col: 6
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference,
         );
@@ -1330,7 +1442,8 @@ end
                     start_col: 6,
                     end_row: 1,
                     end_col: 15
-                }
+                },
+                kind: ReferenceKind::Constant,
             },
             *reference,
         );
@@ -1381,4 +1494,60 @@ Foo
             vec![]
         );
     }
+
+    #[test]
+    fn parse_error() {
+        let contents: String = String::from("class Foo\n  Bar\n");
+
+        let configuration = Configuration::default();
+
+        let processed_file = process_from_contents(
+            contents,
+            &PathBuf::from("path/to/file.rb"),
+            &configuration,
+        );
+        assert_eq!(processed_file.parse_errors.len(), 1);
+        assert_eq!(processed_file.parse_errors[0].location.start_row, 3);
+    }
+
+    #[test]
+    fn suppressions() {
+        let contents: String = String::from(
+            "\
+class Foo
+  # packs:disable-next-line dependency,privacy -- reason: legacy billing shim
+  Bar
+  # packs:disable-next-line layer
+  Baz
+  # Mentions packs:disable-next-line without being one
+end
+",
+        );
+
+        let configuration = Configuration::default();
+
+        std::assert_eq!(
+            process_from_contents(
+                contents,
+                &PathBuf::from("path/to/file.rb"),
+                &configuration,
+            )
+            .suppressions,
+            vec![
+                Suppression {
+                    line: 2,
+                    violation_types: vec![
+                        String::from("dependency"),
+                        String::from("privacy")
+                    ],
+                    reason: Some(String::from("legacy billing shim")),
+                },
+                Suppression {
+                    line: 4,
+                    violation_types: vec![String::from("layer")],
+                    reason: None,
+                },
+            ]
+        );
+    }
 }
@@ -1,17 +1,22 @@
-use crate::packs::file_utils::file_read_contents;
-use crate::packs::parsing::ruby::parse_utils::extract_sigils_from_contents;
+use crate::packs::parsing::ruby::parse_utils::{
+    extract_parse_errors, extract_sigils_from_contents,
+    extract_suppressions_from_contents,
+};
 
 use crate::packs::{
     parsing::{
         ruby::{
+            inflector_shim::Inflections,
             namespace_calculator::possible_fully_qualified_constants,
             parse_utils::{
                 fetch_const_const_name, fetch_const_name, fetch_node_location,
                 get_constant_assignment_definition, get_definition_from,
-                get_reference_from_active_record_association, loc_to_range,
+                get_reference_from_active_record_association,
+                get_references_from_strings, loc_to_range,
             },
         },
-        ParsedDefinition, Range, UnresolvedReference,
+        ParsedDefinition, Range, ReferenceKind, StringReferenceCalls,
+        UnresolvedReference,
     },
     Configuration, ProcessedFile,
 };
@@ -36,6 +41,8 @@ struct ReferenceCollector<'a> {
     pub in_superclass: bool,
     pub superclasses: Vec<SuperclassReference>,
     pub custom_associations: Vec<String>,
+    pub inflections: Inflections,
+    pub string_reference_calls: StringReferenceCalls,
 }
 
 impl<'a> Visitor for ReferenceCollector<'a> {
@@ -73,6 +80,7 @@ impl<'a> Visitor for ReferenceCollector<'a> {
             name,
             namespace_path,
             location,
+            kind: ReferenceKind::Constant,
         });
 
         // Note – is there a way to use lifetime specifiers to get rid of this and
@@ -95,10 +103,18 @@ impl<'a> Visitor for ReferenceCollector<'a> {
                 &self.current_namespaces,
                 &self.line_col_lookup,
                 &self.custom_associations,
+                &self.inflections,
             );
 
         if let Some(association_reference) = association_reference {
             self.references.push(association_reference);
+        } else {
+            self.references.extend(get_references_from_strings(
+                node,
+                &self.current_namespaces,
+                &self.line_col_lookup,
+                &self.string_reference_calls,
+            ));
         }
 
         lib_ruby_parser::traverse::visitor::visit_send(self, node);
@@ -145,6 +161,7 @@ impl<'a> Visitor for ReferenceCollector<'a> {
             name,
             namespace_path,
             location,
+            kind: ReferenceKind::Constant,
         });
 
         // Note – is there a way to use lifetime specifiers to get rid of this and
@@ -201,18 +218,11 @@ impl<'a> Visitor for ReferenceCollector<'a> {
             name,
             namespace_path,
             location: loc_to_range(&node.expression_l, &self.line_col_lookup),
+            kind: ReferenceKind::Constant,
         })
     }
 }
 
-pub(crate) fn process_from_path(
-    path: &Path,
-    configuration: &Configuration,
-) -> anyhow::Result<ProcessedFile> {
-    let contents = file_read_contents(path, configuration)?;
-    Ok(process_from_contents(contents, path, configuration))
-}
-
 pub(crate) fn process_from_contents(
     contents: String,
     path: &Path,
@@ -226,6 +236,7 @@ pub(crate) fn process_from_contents(
     let lookup = LineColLookup::new(&contents);
     let parser = Parser::new(contents.clone(), options);
     let parse_result = parser.do_parse();
+    let parse_errors = extract_parse_errors(&parse_result.diagnostics, &lookup);
 
     let ast_option: Option<Box<Node>> = parse_result.ast;
 
@@ -237,6 +248,8 @@ pub(crate) fn process_from_contents(
                 unresolved_references: vec![],
                 definitions: vec![],
                 sigils: vec![],
+                suppressions: vec![],
+                parse_errors,
             }
         }
     };
@@ -249,13 +262,44 @@ pub(crate) fn process_from_contents(
         in_superclass: false,
         superclasses: vec![],
         custom_associations: configuration.custom_associations.clone(),
+        inflections: configuration.inflections.clone(),
+        string_reference_calls: configuration.string_reference_calls.clone(),
     };
 
     collector.visit(&ast);
 
+    let unresolved_references =
+        without_local_references(collector.references, &collector.definitions);
+
+    let absolute_path = path.to_owned();
+
+    // The packwerk parser uses a ConstantResolver constructed by constants inferred from the file system
+    // see zeitwerk_utils for more.
+    // For a parser that uses parsed constants, see the experimental parser
+    let definitions = vec![];
+
+    let sigils = extract_sigils_from_contents(&contents);
+    let suppressions = extract_suppressions_from_contents(&contents);
+
+    ProcessedFile {
+        absolute_path,
+        unresolved_references,
+        definitions,
+        sigils,
+        suppressions,
+        parse_errors,
+    }
+}
+
+// Drops the references to constants the file itself defines, other than the
+// definitions themselves
+pub(crate) fn without_local_references(
+    references: Vec<UnresolvedReference>,
+    definitions: &[ParsedDefinition],
+) -> Vec<UnresolvedReference> {
     let mut definition_to_location_map: HashMap<String, Range> = HashMap::new();
 
-    for d in &collector.definitions {
+    for d in definitions {
         let parts: Vec<&str> = d.fully_qualified_name.split("::").collect();
         // We do this to handle nested constants, e.g.
         // class Foo::Bar
@@ -276,8 +320,7 @@ pub(crate) fn process_from_contents(
         }
     }
 
-    let unresolved_references = collector
-        .references
+    references
         .into_iter()
         .filter(|r| {
             let mut should_ignore_local_reference = false;
@@ -304,21 +347,5 @@ pub(crate) fn process_from_contents(
             }
             !should_ignore_local_reference
         })
-        .collect();
-
-    let absolute_path = path.to_owned();
-
-    // The packwerk parser uses a ConstantResolver constructed by constants inferred from the file system
-    // see zeitwerk_utils for more.
-    // For a parser that uses parsed constants, see the experimental parser
-    let definitions = vec![];
-
-    let sigils = extract_sigils_from_contents(&contents);
-
-    ProcessedFile {
-        absolute_path,
-        unresolved_references,
-        definitions,
-        sigils,
-    }
+        .collect()
 }
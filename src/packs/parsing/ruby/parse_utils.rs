@@ -1,14 +1,15 @@
-use std::collections::HashSet;
-
-use lib_ruby_parser::{nodes, Loc, Node};
+use lib_ruby_parser::{nodes, Diagnostic, ErrorLevel, Loc, Node};
 use line_col::LineColLookup;
 
 use crate::packs::{
-    parsing::{ParsedDefinition, Range, UnresolvedReference},
-    Sigil,
+    parsing::{
+        ParseDiagnostic, ParsedDefinition, Range, ReferenceKind,
+        StringReferenceCalls, UnresolvedReference,
+    },
+    Sigil, Suppression,
 };
 
-use super::inflector_shim::to_class_case;
+use super::inflector_shim::{to_class_case, Inflections};
 
 #[derive(Debug)]
 pub enum ParseError {
@@ -52,8 +53,17 @@ pub fn get_definition_from(
 }
 
 pub fn loc_to_range(loc: &Loc, lookup: &LineColLookup) -> Range {
-    let (start_row, start_col) = lookup.get(loc.begin); // There's an off-by-one difference here with packwerk
-    let (end_row, end_col) = lookup.get(loc.end);
+    offsets_to_range(loc.begin, loc.end, lookup)
+}
+
+// The range between two byte offsets of the source
+pub fn offsets_to_range(
+    begin: usize,
+    end: usize,
+    lookup: &LineColLookup,
+) -> Range {
+    let (start_row, start_col) = lookup.get(begin); // There's an off-by-one difference here with packwerk
+    let (end_row, end_col) = lookup.get(end);
 
     Range {
         start_row,
@@ -63,6 +73,21 @@ pub fn loc_to_range(loc: &Loc, lookup: &LineColLookup) -> Range {
     }
 }
 
+// Warnings are left out, since they don't affect the references we find
+pub fn extract_parse_errors(
+    diagnostics: &[Diagnostic],
+    lookup: &LineColLookup,
+) -> Vec<ParseDiagnostic> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| matches!(diagnostic.level, ErrorLevel::Error))
+        .map(|diagnostic| ParseDiagnostic {
+            message: diagnostic.render_message(),
+            location: loc_to_range(&diagnostic.loc, lookup),
+        })
+        .collect()
+}
+
 pub fn fetch_const_name(node: &nodes::Node) -> Result<String, ParseError> {
     match node {
         Node::Const(const_node) => Ok(fetch_const_const_name(const_node)?),
@@ -98,7 +123,7 @@ fn fetch_casgn_name(node: &nodes::Casgn) -> Result<String, ParseError> {
     }
 }
 
-const ASSOCIATION_METHOD_NAMES: [&str; 4] = [
+pub(crate) const ASSOCIATION_METHOD_NAMES: [&str; 4] = [
     "has_one",
     "has_many",
     "belongs_to",
@@ -110,6 +135,7 @@ pub fn get_reference_from_active_record_association(
     current_namespaces: &[String],
     line_col_lookup: &LineColLookup,
     custom_associations: &[String],
+    inflections: &Inflections,
 ) -> Option<UnresolvedReference> {
     // TODO: Read in args, process associations as a separate class
     // These can get complicated! e.g. we can specify a class name
@@ -142,7 +168,7 @@ pub fn get_reference_from_active_record_association(
                 name = Some(to_class_case(
                     &d.name.to_string_lossy(),
                     true,
-                    &HashSet::new(), // todo: pass in acronyms here
+                    inflections,
                 ));
             }
         }
@@ -160,6 +186,7 @@ pub fn get_reference_from_active_record_association(
                 name: unwrapped_name,
                 namespace_path: current_namespaces.to_owned(),
                 location: loc_to_range(&node.expression_l, line_col_lookup),
+                kind: ReferenceKind::Constant,
             })
         } else {
             None
@@ -197,6 +224,86 @@ fn extract_class_name_from_kwargs(kwargs: &nodes::Kwargs) -> Option<String> {
     None
 }
 
+/// References to the constants named by string literals in a call, e.g.
+/// `"Foo::Bar".constantize`, `Object.const_get("Foo")` or
+/// `configure class_name: "Foo"`
+pub fn get_references_from_strings(
+    node: &nodes::Send,
+    current_namespaces: &[String],
+    line_col_lookup: &LineColLookup,
+    string_reference_calls: &StringReferenceCalls,
+) -> Vec<UnresolvedReference> {
+    let mut references = vec![];
+
+    if string_reference_calls.methods.contains(&node.method_name) {
+        let string_node = match (node.args.first(), &node.recv) {
+            (Some(first_arg), _) => Some(first_arg),
+            (None, Some(recv)) => Some(recv.as_ref()),
+            (None, None) => None,
+        };
+        if let Some(Node::Str(string)) = string_node {
+            // `constantize` and `const_get` look constants up from the top
+            // level
+            references.extend(string_reference(
+                string,
+                vec![],
+                line_col_lookup,
+            ));
+        }
+    }
+
+    for arg in node.args.iter() {
+        let Node::Kwargs(kwargs) = arg else {
+            continue;
+        };
+        for pair_node in kwargs.pairs.iter() {
+            let Node::Pair(pair) = pair_node else {
+                continue;
+            };
+            let (Node::Sym(key), Node::Str(value)) =
+                (pair.key.as_ref(), pair.value.as_ref())
+            else {
+                continue;
+            };
+            let key = key.name.to_string_lossy();
+            if string_reference_calls.keywords.contains(&key) {
+                references.extend(string_reference(
+                    value,
+                    current_namespaces.to_owned(),
+                    line_col_lookup,
+                ));
+            }
+        }
+    }
+
+    references
+}
+
+fn string_reference(
+    string: &nodes::Str,
+    namespace_path: Vec<String>,
+    line_col_lookup: &LineColLookup,
+) -> Option<UnresolvedReference> {
+    let name = string.value.to_string_lossy();
+    if !is_constant_name(&name) {
+        return None;
+    }
+    Some(UnresolvedReference {
+        name,
+        namespace_path,
+        location: loc_to_range(&string.expression_l, line_col_lookup),
+        kind: ReferenceKind::String,
+    })
+}
+
+// e.g. `Foo`, `Foo::Bar` or `::Foo`, but not `foo` or `Foo Bar`
+pub(crate) fn is_constant_name(name: &str) -> bool {
+    name.trim_start_matches("::").split("::").all(|part| {
+        part.starts_with(|c: char| c.is_ascii_uppercase())
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
 pub fn get_constant_assignment_definition(
     node: &nodes::Casgn,
     current_namespaces: Vec<String>,
@@ -237,3 +344,54 @@ pub fn extract_sigils_from_contents(contents: &str) -> Vec<Sigil> {
 
     sigils
 }
+
+const SUPPRESSION_DIRECTIVE: &str = "packs:disable-next-line";
+
+// Comments like
+// `# packs:disable-next-line dependency,privacy -- reason: legacy billing shim`
+// anywhere in the file. The `reason:` label is optional. Templates use the same
+// comment, e.g. `<%# packs:disable-next-line privacy -- reason: ... %>`.
+pub fn extract_suppressions_from_contents(contents: &str) -> Vec<Suppression> {
+    if !contents.contains(SUPPRESSION_DIRECTIVE) {
+        return vec![];
+    }
+
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (before, after) = line.split_once(SUPPRESSION_DIRECTIVE)?;
+            if !before.trim_end().ends_with('#') {
+                return None;
+            }
+
+            let (violation_types, reason) = match after.split_once("--") {
+                Some((violation_types, reason)) => {
+                    (violation_types, Some(reason))
+                }
+                None => (after, None),
+            };
+            let violation_types: Vec<String> = violation_types
+                .trim_end()
+                .trim_end_matches("%>")
+                .split([',', ' '])
+                .map(str::trim)
+                .filter(|violation_type| !violation_type.is_empty())
+                .map(str::to_owned)
+                .collect();
+            let reason = reason
+                .map(|reason| {
+                    let reason = reason.trim().trim_end_matches("%>").trim();
+                    reason.strip_prefix("reason:").unwrap_or(reason).trim()
+                })
+                .filter(|reason| !reason.is_empty())
+                .map(str::to_owned);
+
+            Some(Suppression {
+                line: index + 1,
+                violation_types,
+                reason,
+            })
+        })
+        .collect()
+}
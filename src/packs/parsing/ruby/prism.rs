@@ -0,0 +1,200 @@
+pub(crate) mod parser;
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::packs::parsing::ruby::prism::parser::process_from_contents;
+    use crate::packs::parsing::ruby::{experimental, packwerk};
+    use crate::packs::parsing::{
+        ParseDiagnostic, Range, ReferenceKind, StringReferenceCalls,
+    };
+    use crate::packs::{Configuration, ProcessedFile, UnresolvedReference};
+    use pretty_assertions::assert_eq;
+
+    fn lib_ruby_parser_result(
+        contents: &str,
+        path: &Path,
+        configuration: &Configuration,
+    ) -> ProcessedFile {
+        if configuration.experimental_parser {
+            experimental::parser::process_from_contents(
+                contents.to_owned(),
+                path,
+                configuration,
+            )
+        } else {
+            packwerk::parser::process_from_contents(
+                contents.to_owned(),
+                path,
+                configuration,
+            )
+        }
+    }
+
+    #[test]
+    fn test_same_references_as_lib_ruby_parser() {
+        let sources = [
+            "Foo::Bar::Baz",
+            "::Foo.bar(Baz)",
+            "foo::Bar",
+            "\
+module Foo
+  class Bar < Baz::Qux
+    BAR = Bar
+    A, B::C = 1, 2
+    Foo::D ||= Qux
+
+    def foo
+      Bar.new(Baz)
+    end
+  end
+end
+",
+            "\
+class Foo::Bar < Bar
+  private_constant :Bar
+  def self.baz; end
+end
+",
+            "\
+class Company
+  has_many :employees
+  has_one :ceo, class_name: \"Person\"
+  belongs_to :parent, class_name: Holdings::Company.name
+  has_one_thing :owner
+  configure(class_name: \"Configuration\")
+  \"Foo::Bar\".constantize
+  Object.const_get(\"Baz\")
+end
+",
+            "\
+module Foo
+  RSpec.describe Bar do
+    has_many :widgets
+  end
+end
+",
+            "\
+module Foo
+  has_many :bars do
+    Baz
+  end
+  qux&.call(Qux)
+  quux[Quux]
+  class << self
+    def corge; end
+  end
+end
+",
+            "\
+# pack_public: true
+# packs:disable-next-line privacy -- reason: legacy
+Foo
+",
+        ];
+
+        for experimental_parser in [false, true] {
+            let configuration = Configuration {
+                experimental_parser,
+                custom_associations: vec!["has_one_thing".to_owned()],
+                string_reference_calls: StringReferenceCalls {
+                    methods: vec![
+                        "constantize".to_owned(),
+                        "const_get".to_owned(),
+                    ],
+                    keywords: vec!["class_name".to_owned()],
+                },
+                ..Configuration::default()
+            };
+            for path in ["app/models/foo.rb", "spec/models/foo_spec.rb"] {
+                let path = PathBuf::from(path);
+                for contents in sources {
+                    let expected =
+                        lib_ruby_parser_result(contents, &path, &configuration);
+                    assert_eq!(
+                        expected,
+                        process_from_contents(
+                            contents.to_owned(),
+                            &path,
+                            &configuration
+                        ),
+                        "{} with experimental_parser: {}",
+                        contents,
+                        experimental_parser
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_newer_syntax() {
+        let contents = "\
+class Foo
+  def bar(*, **) = Bar.new(*, **)
+
+  def baz(value)
+    case value
+    in {name: String => name} then Baz.new(name)
+    end
+  end
+
+  def qux = [1].map { it + Qux::VALUE }
+end
+";
+        let processed_file = process_from_contents(
+            contents.to_owned(),
+            &PathBuf::from("path/to/file.rb"),
+            &Configuration::default(),
+        );
+
+        assert_eq!(Vec::<ParseDiagnostic>::new(), processed_file.parse_errors);
+        assert_eq!(
+            vec!["::Foo", "Bar", "String", "Baz", "Qux::VALUE"],
+            processed_file
+                .unresolved_references
+                .iter()
+                .map(|reference| reference.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_references_despite_parse_errors() {
+        let processed_file = process_from_contents(
+            String::from("Foo.bar(\nBaz\n"),
+            &PathBuf::from("path/to/file.rb"),
+            &Configuration::default(),
+        );
+
+        assert!(!processed_file.parse_errors.is_empty());
+        assert_eq!(
+            vec![
+                UnresolvedReference {
+                    name: String::from("Foo"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 1,
+                        start_col: 0,
+                        end_row: 1,
+                        end_col: 4
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+                UnresolvedReference {
+                    name: String::from("Baz"),
+                    namespace_path: vec![],
+                    location: Range {
+                        start_row: 2,
+                        start_col: 0,
+                        end_row: 2,
+                        end_col: 4
+                    },
+                    kind: ReferenceKind::Constant,
+                },
+            ],
+            processed_file.unresolved_references
+        );
+    }
+}
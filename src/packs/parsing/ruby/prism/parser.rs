@@ -0,0 +1,581 @@
+use crate::packs::parsing::ruby::parse_utils::{
+    extract_sigils_from_contents, extract_suppressions_from_contents,
+};
+use crate::packs::{
+    parsing::{
+        ruby::{
+            experimental::parser::is_spec_file,
+            inflector_shim::{to_class_case, Inflections},
+            packwerk::parser::without_local_references,
+            parse_utils::{
+                get_definition_from, is_constant_name, offsets_to_range,
+                ASSOCIATION_METHOD_NAMES,
+            },
+        },
+        ParseDiagnostic, ParsedDefinition, Range, ReferenceKind,
+        StringReferenceCalls, UnresolvedReference,
+    },
+    Configuration, ProcessedFile,
+};
+use line_col::LineColLookup;
+use ruby_prism::{
+    CallNode, ClassNode, ConstantAndWriteNode, ConstantId,
+    ConstantOperatorWriteNode, ConstantOrWriteNode, ConstantPathAndWriteNode,
+    ConstantPathNode, ConstantPathOperatorWriteNode, ConstantPathOrWriteNode,
+    ConstantPathTargetNode, ConstantPathWriteNode, ConstantReadNode,
+    ConstantTargetNode, ConstantWriteNode, DefNode, KeywordHashNode, Location,
+    ModuleNode, Node, StringNode, Visit,
+};
+use std::path::Path;
+
+struct SuperclassReference {
+    pub name: String,
+    pub namespace_path: Vec<String>,
+}
+
+// Collects what the packwerk parser does, or with `experimental` set, what the
+// experimental parser does. See the `ReferenceCollector`s of those parsers for
+// the reasoning behind each rule.
+struct ReferenceCollector<'a> {
+    pub references: Vec<UnresolvedReference>,
+    pub definitions: Vec<ParsedDefinition>,
+    pub current_namespaces: Vec<String>,
+    pub line_col_lookup: LineColLookup<'a>,
+    pub experimental: bool,
+    // Only used like the packwerk parser does
+    pub in_superclass: bool,
+    pub superclasses: Vec<SuperclassReference>,
+    // Only used like the experimental parser does
+    pub behavioral_change_in_namespace: bool,
+    pub is_spec_file: bool,
+    pub custom_associations: Vec<String>,
+    pub inflections: Inflections,
+    pub string_reference_calls: StringReferenceCalls,
+}
+
+impl<'pr> Visit<'pr> for ReferenceCollector<'_> {
+    fn visit_class_node(&mut self, node: &ClassNode<'pr>) {
+        // Like the other parsers, stop traversing classes with names we
+        // can't tell, e.g. `class foo::Bar`
+        let Some(namespace) = constant_name(&node.constant_path()) else {
+            return;
+        };
+
+        if let Some(superclass) = node.superclass() {
+            self.in_superclass = !self.experimental;
+            self.visit(&superclass);
+            self.in_superclass = false;
+        }
+
+        let location = self.range(&node.constant_path().location());
+        self.visit_namespace(namespace, location, node.body());
+
+        if !self.experimental {
+            self.superclasses.pop();
+        }
+    }
+
+    fn visit_module_node(&mut self, node: &ModuleNode<'pr>) {
+        let Some(namespace) = constant_name(&node.constant_path()) else {
+            return;
+        };
+
+        let location = self.range(&node.constant_path().location());
+        self.visit_namespace(namespace, location, node.body());
+    }
+
+    fn visit_call_node(&mut self, node: &CallNode<'pr>) {
+        let method_name = to_string(node.name().as_slice());
+        // `lib-ruby-parser` has other nodes than method calls for `foo&.bar`
+        // and `foo[bar]`
+        let is_send = !node.is_safe_navigation()
+            && method_name != "[]"
+            && method_name != "[]=";
+        let is_ignored = self.experimental
+            && (method_name == "private_constant" || self.is_spec_file);
+
+        if is_send && !is_ignored {
+            if self.experimental {
+                self.behavioral_change_in_namespace = true;
+            }
+
+            if let Some(association_reference) =
+                self.association_reference(node, &method_name)
+            {
+                self.references.push(association_reference);
+            } else {
+                let string_references =
+                    self.string_references(node, &method_name);
+                self.references.extend(string_references);
+            }
+        }
+
+        ruby_prism::visit_call_node(self, node);
+    }
+
+    fn visit_def_node(&mut self, node: &DefNode<'pr>) {
+        if self.experimental && !self.is_spec_file {
+            self.behavioral_change_in_namespace = true;
+        }
+        ruby_prism::visit_def_node(self, node);
+    }
+
+    fn visit_constant_read_node(&mut self, node: &ConstantReadNode<'pr>) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_reference(name, &node.location());
+    }
+
+    fn visit_constant_path_node(&mut self, node: &ConstantPathNode<'pr>) {
+        let Some(name) = constant_path_name(node.parent(), node.name()) else {
+            // e.g. `foo::Bar`, where only the references in `foo` count
+            ruby_prism::visit_constant_path_node(self, node);
+            return;
+        };
+        self.push_constant_reference(name, &node.location());
+    }
+
+    fn visit_constant_write_node(&mut self, node: &ConstantWriteNode<'pr>) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_definition(Some(name), &node.location());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_or_write_node(
+        &mut self,
+        node: &ConstantOrWriteNode<'pr>,
+    ) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_definition(Some(name), &node.name_loc());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_and_write_node(
+        &mut self,
+        node: &ConstantAndWriteNode<'pr>,
+    ) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_definition(Some(name), &node.name_loc());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_operator_write_node(
+        &mut self,
+        node: &ConstantOperatorWriteNode<'pr>,
+    ) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_definition(Some(name), &node.name_loc());
+        self.visit(&node.value());
+    }
+
+    // e.g. `A` in `A, B = 1, 2`
+    fn visit_constant_target_node(&mut self, node: &ConstantTargetNode<'pr>) {
+        let name = to_string(node.name().as_slice());
+        self.push_constant_definition(Some(name), &node.location());
+    }
+
+    fn visit_constant_path_write_node(
+        &mut self,
+        node: &ConstantPathWriteNode<'pr>,
+    ) {
+        let target = node.target();
+        let name = constant_path_name(target.parent(), target.name());
+        self.push_constant_definition(name, &node.location());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_path_or_write_node(
+        &mut self,
+        node: &ConstantPathOrWriteNode<'pr>,
+    ) {
+        let target = node.target();
+        let name = constant_path_name(target.parent(), target.name());
+        self.push_constant_definition(name, &target.location());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_path_and_write_node(
+        &mut self,
+        node: &ConstantPathAndWriteNode<'pr>,
+    ) {
+        let target = node.target();
+        let name = constant_path_name(target.parent(), target.name());
+        self.push_constant_definition(name, &target.location());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_path_operator_write_node(
+        &mut self,
+        node: &ConstantPathOperatorWriteNode<'pr>,
+    ) {
+        let target = node.target();
+        let name = constant_path_name(target.parent(), target.name());
+        self.push_constant_definition(name, &target.location());
+        self.visit(&node.value());
+    }
+
+    fn visit_constant_path_target_node(
+        &mut self,
+        node: &ConstantPathTargetNode<'pr>,
+    ) {
+        let name = constant_path_name(node.parent(), node.name());
+        self.push_constant_definition(name, &node.location());
+    }
+}
+
+impl ReferenceCollector<'_> {
+    fn range(&self, location: &Location) -> Range {
+        offsets_to_range(
+            location.start_offset(),
+            location.end_offset(),
+            &self.line_col_lookup,
+        )
+    }
+
+    fn visit_namespace<'pr>(
+        &mut self,
+        namespace: String,
+        location: Range,
+        body: Option<Node<'pr>>,
+    ) {
+        let definition = get_definition_from(
+            &namespace,
+            &self.current_namespaces,
+            &location,
+        );
+
+        if !self.experimental {
+            // Packwerk also considers a definition to be a "reference"
+            self.references.push(UnresolvedReference {
+                name: definition.fully_qualified_name.to_owned(),
+                namespace_path: self.current_namespaces.to_owned(),
+                location,
+                kind: ReferenceKind::Constant,
+            });
+            self.definitions.push(definition.clone());
+        }
+
+        self.current_namespaces.push(namespace);
+
+        // Each time we open up a new class/module, we reset the behavioral change flag
+        let previous_behavioral_change = self.behavioral_change_in_namespace;
+        self.behavioral_change_in_namespace = false;
+
+        if let Some(body) = body {
+            self.visit(&body);
+        }
+
+        if self.experimental && self.behavioral_change_in_namespace {
+            self.definitions.push(definition);
+        }
+
+        self.behavioral_change_in_namespace = previous_behavioral_change;
+
+        self.current_namespaces.pop();
+    }
+
+    fn push_constant_reference(&mut self, name: String, location: &Location) {
+        let namespace_path = if self.experimental {
+            self.current_namespaces
+                .iter()
+                .filter(|namespace| *namespace != &name)
+                .cloned()
+                .collect()
+        } else {
+            if self.in_superclass {
+                self.superclasses.push(SuperclassReference {
+                    name: name.to_owned(),
+                    namespace_path: self.current_namespaces.to_owned(),
+                })
+            }
+            // In packwerk, NodeHelpers.enclosing_namespace_path ignores
+            // namespaces where a superclass OR namespace is the same as the current reference name
+            match self
+                .superclasses
+                .iter()
+                .find(|superclass| superclass.name == name)
+            {
+                Some(matching_superclass) => {
+                    matching_superclass.namespace_path.to_owned()
+                }
+                None => self
+                    .current_namespaces
+                    .iter()
+                    .filter(|namespace| *namespace != &name)
+                    .cloned()
+                    .collect(),
+            }
+        };
+
+        self.references.push(UnresolvedReference {
+            name,
+            namespace_path,
+            location: self.range(location),
+            kind: ReferenceKind::Constant,
+        })
+    }
+
+    fn push_constant_definition(
+        &mut self,
+        name: Option<String>,
+        location: &Location,
+    ) {
+        if let Some(name) = name {
+            let location = self.range(location);
+            self.definitions.push(get_definition_from(
+                &name,
+                &self.current_namespaces,
+                &location,
+            ));
+        }
+    }
+
+    fn association_reference(
+        &self,
+        node: &CallNode,
+        method_name: &str,
+    ) -> Option<UnresolvedReference> {
+        let is_association = ASSOCIATION_METHOD_NAMES.contains(&method_name)
+            || self
+                .custom_associations
+                .iter()
+                .any(|association| association == method_name);
+        if !is_association {
+            return None;
+        }
+
+        let arguments = arguments(node);
+        let name = arguments
+            .iter()
+            .filter_map(|argument| argument.as_keyword_hash_node())
+            .filter_map(|keywords| class_name_from_keywords(&keywords))
+            .next_back()
+            .or_else(|| {
+                // By convention Rails singularizes the class name declared
+                // via a symbol, e.g. `has_many :companies` looks for a class
+                // named `Company`
+                let symbol = arguments.first()?.as_symbol_node()?;
+                Some(to_class_case(
+                    &to_string(symbol.unescaped()),
+                    true,
+                    &self.inflections,
+                ))
+            })?;
+
+        Some(UnresolvedReference {
+            name,
+            namespace_path: self.current_namespaces.to_owned(),
+            location: offsets_to_range(
+                node.location().start_offset(),
+                call_end_offset(node),
+                &self.line_col_lookup,
+            ),
+            kind: ReferenceKind::Constant,
+        })
+    }
+
+    // See `get_references_from_strings`
+    fn string_references(
+        &self,
+        node: &CallNode,
+        method_name: &str,
+    ) -> Vec<UnresolvedReference> {
+        let mut references = vec![];
+        let arguments = arguments(node);
+
+        if self
+            .string_reference_calls
+            .methods
+            .iter()
+            .any(|method| method == method_name)
+        {
+            let receiver = node.receiver();
+            let string_node = arguments.first().or(receiver.as_ref());
+            if let Some(string) = string_node.and_then(Node::as_string_node) {
+                // `constantize` and `const_get` look constants up from the top
+                // level
+                references.extend(self.string_reference(&string, vec![]));
+            }
+        }
+
+        for keywords in arguments
+            .iter()
+            .filter_map(|argument| argument.as_keyword_hash_node())
+        {
+            for element in &keywords.elements() {
+                let Some(assoc) = element.as_assoc_node() else {
+                    continue;
+                };
+                let (Some(key), Some(value)) = (
+                    assoc.key().as_symbol_node(),
+                    assoc.value().as_string_node(),
+                ) else {
+                    continue;
+                };
+                let key = to_string(key.unescaped());
+                if self.string_reference_calls.keywords.contains(&key) {
+                    references.extend(self.string_reference(
+                        &value,
+                        self.current_namespaces.to_owned(),
+                    ));
+                }
+            }
+        }
+
+        references
+    }
+
+    fn string_reference(
+        &self,
+        string: &StringNode,
+        namespace_path: Vec<String>,
+    ) -> Option<UnresolvedReference> {
+        let name = to_string(string.unescaped());
+        if !is_constant_name(&name) {
+            return None;
+        }
+        Some(UnresolvedReference {
+            name,
+            namespace_path,
+            location: self.range(&string.location()),
+            kind: ReferenceKind::String,
+        })
+    }
+}
+
+fn to_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn arguments<'pr>(node: &CallNode<'pr>) -> Vec<Node<'pr>> {
+    node.arguments()
+        .map(|arguments| arguments.arguments().iter().collect())
+        .unwrap_or_default()
+}
+
+// Where a call ends, leaving out its block like `lib-ruby-parser` does
+fn call_end_offset(node: &CallNode) -> usize {
+    let has_block = node
+        .block()
+        .is_some_and(|block| block.as_block_node().is_some());
+    if !has_block {
+        return node.location().end_offset();
+    }
+    node.closing_loc()
+        .or(node.arguments().map(|arguments| arguments.location()))
+        .or(node.message_loc())
+        .map_or(node.location().end_offset(), |location| {
+            location.end_offset()
+        })
+}
+
+// e.g. `Foo::Bar` or `::Foo`, or `None` if a part of the name is not a
+// constant, e.g. in `foo::Bar`
+fn constant_name(node: &Node) -> Option<String> {
+    if let Some(node) = node.as_constant_read_node() {
+        return Some(to_string(node.name().as_slice()));
+    }
+    let node = node.as_constant_path_node()?;
+    constant_path_name(node.parent(), node.name())
+}
+
+fn constant_path_name(
+    parent: Option<Node>,
+    name: Option<ConstantId>,
+) -> Option<String> {
+    let scope = match parent {
+        Some(parent) => constant_name(&parent)?,
+        None => String::new(),
+    };
+    Some(format!("{}::{}", scope, to_string(name?.as_slice())))
+}
+
+// The value of `class_name: "Foo::Bar"` or `class_name: Foo::Bar.name`
+fn class_name_from_keywords(keywords: &KeywordHashNode) -> Option<String> {
+    for element in &keywords.elements() {
+        let Some(assoc) = element.as_assoc_node() else {
+            continue;
+        };
+        let is_class_name = assoc
+            .key()
+            .as_symbol_node()
+            .is_some_and(|key| key.unescaped() == b"class_name");
+        if !is_class_name {
+            continue;
+        }
+
+        let value = assoc.value();
+        if let Some(string) = value.as_string_node() {
+            return Some(to_string(string.unescaped()));
+        }
+        if let Some(call) = value.as_call_node() {
+            if call.name().as_slice() == b"name" {
+                if let Some(name) =
+                    call.receiver().and_then(|r| constant_name(&r))
+                {
+                    return Some(name);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+pub(crate) fn process_from_contents(
+    contents: String,
+    path: &Path,
+    configuration: &Configuration,
+) -> ProcessedFile {
+    let parse_result = ruby_prism::parse(contents.as_bytes());
+    let lookup = LineColLookup::new(&contents);
+
+    let mut collector = ReferenceCollector {
+        references: vec![],
+        definitions: vec![],
+        current_namespaces: vec![],
+        line_col_lookup: lookup,
+        experimental: configuration.experimental_parser,
+        in_superclass: false,
+        superclasses: vec![],
+        behavioral_change_in_namespace: false,
+        is_spec_file: is_spec_file(path),
+        custom_associations: configuration.custom_associations.clone(),
+        inflections: configuration.inflections.clone(),
+        string_reference_calls: configuration.string_reference_calls.clone(),
+    };
+
+    // Prism recovers from syntax errors, so there are references even when
+    // there are parse errors
+    let parse_errors = parse_result
+        .errors()
+        .map(|diagnostic| ParseDiagnostic {
+            message: diagnostic.message().to_owned(),
+            location: collector.range(&diagnostic.location()),
+        })
+        .collect();
+
+    collector.visit(&parse_result.node());
+
+    let (unresolved_references, definitions) = if collector.experimental {
+        (collector.references, collector.definitions)
+    } else {
+        // Like the packwerk parser, the definitions come from the constant
+        // resolver, see zeitwerk_utils
+        (
+            without_local_references(
+                collector.references,
+                &collector.definitions,
+            ),
+            vec![],
+        )
+    };
+
+    ProcessedFile {
+        absolute_path: path.to_owned(),
+        unresolved_references,
+        definitions,
+        sigils: extract_sigils_from_contents(&contents),
+        suppressions: extract_suppressions_from_contents(&contents),
+        parse_errors,
+    }
+}
@@ -1,29 +1,185 @@
-use std::{collections::HashSet, path::Path};
+use std::path::Path;
 
+use lib_ruby_parser::{
+    nodes, traverse::visitor::Visitor, Node, Parser, ParserOptions,
+};
 use regex::Regex;
 
-// Load in config/initializers/inflections.rb
-// For any inflections in there, add them to the acronyms vector
-// An inflection takes the form of "inflect.acronym 'API'", so "API" would be the acronym here
-// This is a bit of a hack, but it's the easiest way to get the inflections loaded in
-// TODO: Figure out a better way to do this
-pub(crate) fn get_acronyms_from_disk(
+use super::inflector_shim::{InflectionRule, Inflections};
+
+// Load in config/initializers/inflections.rb, e.g.
+// ActiveSupport::Inflector.inflections(:en) do |inflect|
+//   inflect.acronym "API"
+//   inflect.irregular "person", "people"
+// end
+// Acronyms, singular, plural, irregular and uncountable rules are read. Human
+// rules (`inflect.human`) don't affect constant names, so they are ignored.
+pub(crate) fn get_inflections_from_disk(
     inflections_path: &Path,
-) -> HashSet<String> {
-    let mut acronyms: HashSet<String> = HashSet::new();
-
-    if inflections_path.exists() {
-        let inflections_file =
-            std::fs::read_to_string(inflections_path).unwrap();
-        let inflections_lines = inflections_file.lines();
-        for line in inflections_lines {
-            if line.contains(".acronym") {
-                let re = Regex::new(r#"['\\"]"#).unwrap();
-                let acronym = re.split(line).nth(1).unwrap();
-                acronyms.insert(acronym.to_string());
+) -> Inflections {
+    match std::fs::read_to_string(inflections_path) {
+        Ok(contents) => get_inflections_from_contents(contents),
+        Err(_) => Inflections::default(),
+    }
+}
+
+fn get_inflections_from_contents(contents: String) -> Inflections {
+    let options = ParserOptions {
+        buffer_name: "".to_string(),
+        ..Default::default()
+    };
+    let mut collector = InflectionCollector {
+        inflections: Inflections::default(),
+    };
+    if let Some(ast) = Parser::new(contents, options).do_parse().ast {
+        collector.visit(&ast);
+    }
+    collector.inflections
+}
+
+struct InflectionCollector {
+    inflections: Inflections,
+}
+
+impl Visitor for InflectionCollector {
+    fn on_send(&mut self, node: &nodes::Send) {
+        let inflections = &mut self.inflections;
+        match (node.method_name.as_str(), node.args.as_slice()) {
+            ("acronym", [word]) => {
+                if let Some(word) = string_value(word) {
+                    inflections.add_acronym(&word);
+                }
+            }
+            ("singular", [rule, replacement]) => {
+                if let (Some(rule), Some(replacement)) =
+                    (inflection_rule(rule), string_value(replacement))
+                {
+                    inflections.add_singular(rule, &replacement);
+                }
+            }
+            ("plural", [rule, replacement]) => {
+                if let (Some(rule), Some(replacement)) =
+                    (inflection_rule(rule), string_value(replacement))
+                {
+                    inflections.add_plural(rule, &replacement);
+                }
+            }
+            ("irregular", [singular, plural]) => {
+                if let (Some(singular), Some(plural)) =
+                    (string_value(singular), string_value(plural))
+                {
+                    inflections.add_irregular(&singular, &plural);
+                }
             }
+            ("uncountable", words) => {
+                // e.g. `inflect.uncountable "fish", "sheep"` or
+                // `inflect.uncountable %w[fish sheep]`
+                for word in words {
+                    let words = match word {
+                        Node::Array(array) => array.elements.as_slice(),
+                        _ => std::slice::from_ref(word),
+                    };
+                    for word in words.iter().filter_map(string_value) {
+                        inflections.add_uncountable(&word);
+                    }
+                }
+            }
+            _ => {}
         }
+
+        lib_ruby_parser::traverse::visitor::visit_send(self, node);
     }
+}
 
-    acronyms
+fn string_value(node: &Node) -> Option<String> {
+    match node {
+        Node::Str(string) => Some(string.value.to_string_lossy()),
+        Node::Sym(symbol) => Some(symbol.name.to_string_lossy()),
+        _ => None,
+    }
+}
+
+// Regexps without interpolation whose syntax the `regex` crate supports
+fn inflection_rule(node: &Node) -> Option<InflectionRule> {
+    let Node::Regexp(regexp) = node else {
+        return string_value(node).map(InflectionRule::Literal);
+    };
+
+    let mut source = String::new();
+    for part in &regexp.parts {
+        let Node::Str(string) = part else {
+            return None;
+        };
+        source.push_str(&string.value.to_string_lossy());
+    }
+
+    let ruby_options = match regexp.options.as_deref() {
+        Some(Node::RegOpt(reg_opt)) => reg_opt.options.clone(),
+        _ => None,
+    };
+    let flags: String = ruby_options
+        .unwrap_or_default()
+        .chars()
+        .filter_map(|option| match option {
+            'i' => Some('i'),
+            'x' => Some('x'),
+            // Ruby's multiline mode lets `.` match newlines
+            'm' => Some('s'),
+            _ => None,
+        })
+        .collect();
+    if !flags.is_empty() {
+        source = format!("(?{}){}", flags, source);
+    }
+
+    Regex::new(&source).ok().map(InflectionRule::Pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::parsing::ruby::inflector_shim::to_class_case;
+
+    #[test]
+    fn test_get_inflections_from_contents() {
+        let contents = r#"
+ActiveSupport::Inflector.inflections(:en) do |inflect|
+  # inflect.acronym "NOPE"
+  inflect.acronym "API"
+  inflect.singular /^(ox)en/i, '\1'
+  inflect.singular "cacti", "cactus"
+  inflect.irregular "person", "people"
+  inflect.irregular "Goose", "geese"
+  inflect.uncountable %w[equipment staff]
+  inflect.uncountable "data"
+  inflect.human /_cnt$/i, '\1_count'
+end
+"#;
+        let inflections = get_inflections_from_contents(contents.to_owned());
+        assert_eq!(
+            inflections.acronyms,
+            [String::from("API")].into_iter().collect()
+        );
+
+        let tests = [
+            ("oxen", "Ox"),
+            ("cacti", "Cactus"),
+            ("people", "Person"),
+            ("sales_people", "SalesPerson"),
+            ("geese", "Goose"),
+            ("equipment", "Equipment"),
+            ("staff", "Staff"),
+            ("data", "Data"),
+            ("api_clients", "APIClient"),
+            ("companies", "Company"),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(
+                to_class_case(input, true, &inflections),
+                expected,
+                "Failed for input: {}",
+                input
+            );
+        }
+    }
 }
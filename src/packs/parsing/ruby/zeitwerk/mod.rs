@@ -6,22 +6,26 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use rayon::prelude::{ParallelBridge, ParallelIterator};
+use rayon::prelude::{IntoParallelIterator, ParallelBridge, ParallelIterator};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::packs::{
     caching::create_cache_dir_idempotently,
     constant_resolver::{
         ConstantDefinition, ConstantResolver, ConstantResolverConfiguration,
     },
-    file_utils::expand_glob,
+    file_utils::{expand_glob, file_content_digest},
     pack::Pack,
-    parsing::ruby::rails_utils::get_acronyms_from_disk,
     PackSet,
 };
 
 use self::constant_resolver::ZeitwerkConstantResolver;
+
+const CONSTANT_RESOLVER_INDEX_FILE: &str = "constant_resolver_index.bin";
+// Bump this whenever `ConstantDefinition` or the way constants are inferred
+// change, so that stale indexes are rebuilt
+const CONSTANT_RESOLVER_INDEX_VERSION: u32 = 2;
 use fs2::FileExt; // Provides file locking methods
 
 use super::inflector_shim;
@@ -30,9 +34,33 @@ pub fn get_zeitwerk_constant_resolver(
     pack_set: &PackSet,
     configuration: &ConstantResolverConfiguration,
 ) -> Box<dyn ConstantResolver + Send + Sync> {
-    let constants = inferred_constants_from_pack_set(pack_set, configuration);
+    let index_key = configuration
+        .cache_enabled
+        .then(|| constant_resolver_index_key(pack_set, configuration));
+    if let Some(index_key) = &index_key {
+        if let Some(index) = get_constant_resolver_index(
+            configuration.cache_directory,
+            index_key,
+        ) {
+            debug!("Using the cached constant resolver index");
+            return Box::new(ZeitwerkConstantResolver {
+                fully_qualified_constant_name_to_constant_definition_map: index,
+            });
+        }
+    }
 
-    ZeitwerkConstantResolver::create(constants)
+    let constants = inferred_constants_from_pack_set(pack_set, configuration);
+    let constant_resolver = ZeitwerkConstantResolver::create(constants);
+
+    if let Some(index_key) = index_key {
+        cache_constant_resolver_index(
+            configuration.cache_directory,
+            index_key,
+            constant_resolver
+                .fully_qualified_constant_name_to_constant_definition_map(),
+        );
+    }
+    constant_resolver
 }
 
 #[derive(Debug)]
@@ -94,10 +122,159 @@ fn get_pack_namespace_settings(pack: &Pack) -> PackNamespaceSettings {
         }) // Default to false and empty set if metadata doesn't exist
 }
 
+// Like Zeitwerk's `collapse` and `ignore`: files in collapsed directories are
+// namespaced as if they were in the parent directory, and ignored files and
+// directories don't define constants
+#[derive(Debug, Default)]
+struct AutoloadDirectories {
+    collapsed: HashSet<PathBuf>,
+    ignored: HashSet<PathBuf>,
+}
+
+// A list of globs in the metadata of a pack, relative to the pack, e.g.
+// metadata:
+//   autoload_collapse:
+//     - app/models/legacy
+fn get_pack_metadata_globs(pack: &Pack, key: &str) -> Vec<PathBuf> {
+    let pack_root = pack.yml.parent().unwrap();
+    pack.client_keys
+        .get("metadata")
+        .and_then(|metadata| metadata.get(key))
+        .and_then(|globs| globs.as_sequence())
+        .map(|globs| {
+            globs
+                .iter()
+                .filter_map(|glob| glob.as_str())
+                .map(|glob| pack_root.join(glob))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The globs of `packwerk.yml`, relative to the root, and of each pack
+fn autoload_globs(
+    pack_set: &PackSet,
+    configuration: &ConstantResolverConfiguration,
+    key: &str,
+) -> Vec<PathBuf> {
+    let configured_globs = if key == "autoload_collapse" {
+        configuration.autoload_collapse
+    } else {
+        configuration.autoload_ignore
+    };
+    let mut globs: Vec<PathBuf> = configured_globs
+        .iter()
+        .map(|glob| configuration.absolute_root.join(glob))
+        .collect();
+    let mut packs: Vec<&Pack> = pack_set.packs.iter().collect();
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    for pack in packs {
+        globs.extend(get_pack_metadata_globs(pack, key));
+    }
+    globs
+}
+
+fn get_autoload_directories(
+    pack_set: &PackSet,
+    configuration: &ConstantResolverConfiguration,
+) -> AutoloadDirectories {
+    let expand = |globs: Vec<PathBuf>| -> HashSet<PathBuf> {
+        globs
+            .iter()
+            .flat_map(|glob| expand_glob(glob.to_str().unwrap()))
+            .collect()
+    };
+    AutoloadDirectories {
+        collapsed: expand(autoload_globs(
+            pack_set,
+            configuration,
+            "autoload_collapse",
+        )),
+        ignored: expand(autoload_globs(
+            pack_set,
+            configuration,
+            "autoload_ignore",
+        )),
+    }
+}
+
 fn inferred_constants_from_pack_set(
     pack_set: &PackSet,
     configuration: &ConstantResolverConfiguration,
 ) -> Vec<ConstantDefinition> {
+    let full_autoload_roots = get_full_autoload_roots(pack_set, configuration);
+    let autoload_directories =
+        get_autoload_directories(pack_set, configuration);
+
+    inferred_constants_from_autoload_paths(
+        configuration,
+        full_autoload_roots,
+        autoload_directories,
+    )
+}
+
+/// The constants inferred from the paths of `files`, whether or not they
+/// still exist, e.g. to know what deleted files used to define. Unlike
+/// `get_zeitwerk_constant_resolver`, this doesn't glob the autoload roots.
+pub(crate) fn inferred_constants_from_files(
+    pack_set: &PackSet,
+    configuration: &ConstantResolverConfiguration,
+    files: &[PathBuf],
+) -> Vec<ConstantDefinition> {
+    let full_autoload_roots = get_full_autoload_roots(pack_set, configuration);
+    let autoload_directories =
+        get_autoload_directories(pack_set, configuration);
+
+    files
+        .iter()
+        .filter(|file| file.extension().is_some_and(|ext| ext == "rb"))
+        .filter(|file| {
+            !file
+                .ancestors()
+                .any(|path| autoload_directories.ignored.contains(path))
+        })
+        .filter_map(|file| {
+            // Like `inferred_constants_from_autoload_paths`, the longest
+            // autoload path containing the file defines its constant
+            let absolute_autoload_path = full_autoload_roots
+                .keys()
+                .filter(|autoload_path| file.starts_with(autoload_path))
+                .max_by_key(|autoload_path| {
+                    autoload_path.components().count()
+                })?;
+            Some(inferred_constant_from_file(
+                file,
+                absolute_autoload_path,
+                configuration.acronyms,
+                &full_autoload_roots[absolute_autoload_path],
+                &autoload_directories.collapsed,
+            ))
+        })
+        .collect()
+}
+
+/// A resolver of `constants` only, e.g. to find the references that may
+/// resolve to some of them
+pub(crate) fn constant_resolver_from_constants(
+    constants: Vec<ConstantDefinition>,
+) -> Box<dyn ConstantResolver + Send + Sync> {
+    ZeitwerkConstantResolver::create(constants)
+}
+
+// The Ruby files in an autoload root, whether or not they are included in
+// `packwerk.yml`
+fn autoload_root_files(absolute_autoload_path: &Path) -> Vec<PathBuf> {
+    let glob_path = absolute_autoload_path.join("**/*.rb");
+    glob::glob(glob_path.to_str().unwrap())
+        .expect("Failed to read glob pattern")
+        .filter_map(Result::ok)
+        .collect()
+}
+
+fn get_full_autoload_roots(
+    pack_set: &PackSet,
+    configuration: &ConstantResolverConfiguration,
+) -> HashMap<PathBuf, String> {
     // build the full list of default autoload roots from the pack set, using the default namespace for each.
     // There is one exception to using the default namespace:
     // Each pack may have metadata that takes this shape:
@@ -125,18 +302,13 @@ fn inferred_constants_from_pack_set(
                 if automatic_pack_namespace
                     && !automatic_pack_namespace_exclusions.contains(&path)
                 {
-                    // Pass an empty set of acronyms as the second argument
-                    // NOTE: This is not the correct implementation – if we want automatic namespacing to work with
-                    // acronym-based pack names, we need to pull from the file, preferably from the cache.
-                    let empty_acronyms = HashSet::new();
-
                     // Camelized pack namespace based on pack name with leading double colon:
                     // e.g. pack name "packs/my_pack" -> "::MyPack"
                     let namespace = format!(
                         "::{}",
                         inflector_shim::camelize(
                             pack.last_name(),
-                            &empty_acronyms,
+                            configuration.acronyms,
                         )
                     );
 
@@ -166,15 +338,22 @@ fn inferred_constants_from_pack_set(
                 });
         });
 
-    inferred_constants_from_autoload_paths(configuration, full_autoload_roots)
+    full_autoload_roots
 }
 
 fn inferred_constants_from_autoload_paths(
     configuration: &ConstantResolverConfiguration,
     full_autoload_roots: HashMap<PathBuf, String>,
+    autoload_directories: AutoloadDirectories,
 ) -> Vec<ConstantDefinition> {
     debug!("Get constant resolver cache");
-    let cache_data = get_constant_resolver_cache(configuration.cache_directory);
+    let cache_key = constant_resolver_cache_key(
+        &full_autoload_roots,
+        &autoload_directories,
+        configuration.acronyms,
+    );
+    let cache_data =
+        get_constant_resolver_cache(configuration.cache_directory, &cache_key);
 
     debug!("Globbing out autoload paths");
     // First, we get a map of each autoload path to the files they map to.
@@ -182,11 +361,13 @@ fn inferred_constants_from_autoload_paths(
         .keys()
         .par_bridge()
         .map(|absolute_autoload_path| {
-            let glob_path = absolute_autoload_path.join("**/*.rb");
-
-            let files = glob::glob(glob_path.to_str().unwrap())
-                .expect("Failed to read glob pattern")
-                .filter_map(Result::ok)
+            let files = autoload_root_files(absolute_autoload_path)
+                .into_iter()
+                .filter(|file| {
+                    !file
+                        .ancestors()
+                        .any(|path| autoload_directories.ignored.contains(path))
+                })
                 .collect::<Vec<PathBuf>>();
 
             (absolute_autoload_path, files)
@@ -220,8 +401,7 @@ fn inferred_constants_from_autoload_paths(
         }
     }
 
-    debug!("Getting acronyms from disk");
-    let acronyms = &get_acronyms_from_disk(configuration.inflections_path);
+    let acronyms = configuration.acronyms;
 
     debug!("Inferring constants from file name (using cache)");
     let constants: Vec<ConstantDefinition> = file_to_longest_path
@@ -245,6 +425,7 @@ fn inferred_constants_from_autoload_paths(
                     absolute_autoload_path,
                     acronyms,
                     default_namespace,
+                    &autoload_directories.collapsed,
                 )
             }
         })
@@ -253,6 +434,7 @@ fn inferred_constants_from_autoload_paths(
     debug!("Caching constant definitions");
     cache_constant_definitions(
         &constants,
+        cache_key,
         configuration.cache_directory,
         !configuration.cache_enabled,
     );
@@ -265,11 +447,24 @@ fn inferred_constant_from_file(
     absolute_autoload_path: &PathBuf,
     acronyms: &HashSet<String>,
     default_namespace: &String,
+    collapsed_directories: &HashSet<PathBuf>,
 ) -> ConstantDefinition {
-    let relative_path =
-        absolute_path.strip_prefix(absolute_autoload_path).unwrap();
-
-    let relative_path = relative_path.with_extension("");
+    let relative_path = absolute_path
+        .strip_prefix(absolute_autoload_path)
+        .unwrap()
+        .with_extension("");
+
+    // Collapsed directories don't add a namespace
+    let components: Vec<_> = relative_path.components().collect();
+    let mut directory = absolute_autoload_path.to_path_buf();
+    let mut relative_path = PathBuf::new();
+    for (index, component) in components.iter().enumerate() {
+        directory.push(component);
+        let is_file = index == components.len() - 1;
+        if is_file || !collapsed_directories.contains(&directory) {
+            relative_path.push(component);
+        }
+    }
 
     let relative_path_str = relative_path.to_str().unwrap();
     let camelized_path = inflector_shim::camelize(relative_path_str, acronyms);
@@ -282,25 +477,157 @@ fn inferred_constant_from_file(
     }
 }
 
+// The constant resolver only depends on the paths of the Ruby files in the
+// autoload roots, how each root is namespaced and the inflections. Listing
+// the autoload roots is much cheaper than inferring constants from every
+// file, so the listing of each root is part of the key, including the files
+// that are excluded in `packwerk.yml`.
+fn constant_resolver_index_key(
+    pack_set: &PackSet,
+    configuration: &ConstantResolverConfiguration,
+) -> String {
+    let mut key_parts: Vec<String> =
+        vec![format!("v{}", CONSTANT_RESOLVER_INDEX_VERSION)];
+
+    // Collapsed and ignored directories without Ruby files are not part of
+    // the listings below, so the globs are
+    for key in ["autoload_collapse", "autoload_ignore"] {
+        key_parts.push(format!(
+            "{} {:?}",
+            key,
+            autoload_globs(pack_set, configuration, key)
+        ));
+    }
+
+    key_parts.push(format!(
+        "inflections {}",
+        file_content_digest(configuration.inflections_path).unwrap_or_default()
+    ));
+
+    let full_autoload_roots = get_full_autoload_roots(pack_set, configuration);
+    let mut autoload_roots: Vec<(&PathBuf, &String)> =
+        full_autoload_roots.iter().collect();
+    autoload_roots.sort();
+    let listings: Vec<String> = autoload_roots
+        .into_par_iter()
+        .map(|(absolute_autoload_path, namespace)| {
+            let mut files = autoload_root_files(absolute_autoload_path);
+            files.sort();
+            let mut listing = format!(
+                "autoload_root {:?} {}",
+                absolute_autoload_path, namespace
+            );
+            for file in files {
+                listing.push('\n');
+                listing.push_str(&file.to_string_lossy());
+            }
+            listing
+        })
+        .collect();
+    key_parts.extend(listings);
+
+    format!("{:x}", md5::compute(key_parts.join("\n")))
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct ConstantResolverIndexCache {
+    key: String,
+    index: HashMap<String, Vec<ConstantDefinition>>,
+}
+
+fn get_constant_resolver_index(
+    cache_dir: &Path,
+    key: &str,
+) -> Option<HashMap<String, Vec<ConstantDefinition>>> {
+    let file =
+        std::fs::File::open(cache_dir.join(CONSTANT_RESOLVER_INDEX_FILE))
+            .ok()?;
+    let cache: ConstantResolverIndexCache =
+        bincode::deserialize_from(std::io::BufReader::new(file)).ok()?;
+    (cache.key == key).then_some(cache.index)
+}
+
+fn cache_constant_resolver_index(
+    cache_dir: &Path,
+    key: String,
+    index: &HashMap<String, Vec<ConstantDefinition>>,
+) {
+    create_cache_dir_idempotently(cache_dir);
+    let cache_file_path = cache_dir.join(CONSTANT_RESOLVER_INDEX_FILE);
+    // Write a temporary file and rename it, so that concurrent runs never
+    // read a partially written index
+    let tmp_path = cache_file_path
+        .with_extension(format!("bin.{}.tmp", std::process::id()));
+    let written = std::fs::File::create(&tmp_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            bincode::serialize_into(
+                &mut writer,
+                &ConstantResolverIndexCache {
+                    key,
+                    index: index.clone(),
+                },
+            )?;
+            writer.flush()?;
+            Ok(())
+        })
+        .and_then(|_| Ok(std::fs::rename(&tmp_path, &cache_file_path)?));
+    if let Err(e) = written {
+        warn!("Failed to cache the constant resolver index: {}", e);
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 struct ConstantResolverCache {
+    // The constants inferred from the paths of files depend on how
+    // directories are autoloaded and on acronyms
+    #[serde(default)]
+    key: String,
     file_definition_map: HashMap<PathBuf, String>,
 }
 
-fn get_constant_resolver_cache(cache_dir: &Path) -> ConstantResolverCache {
+fn constant_resolver_cache_key(
+    full_autoload_roots: &HashMap<PathBuf, String>,
+    autoload_directories: &AutoloadDirectories,
+    acronyms: &HashSet<String>,
+) -> String {
+    let mut autoload_roots: Vec<_> = full_autoload_roots.iter().collect();
+    autoload_roots.sort();
+    let mut collapsed: Vec<_> = autoload_directories.collapsed.iter().collect();
+    collapsed.sort();
+    let mut acronyms: Vec<_> = acronyms.iter().collect();
+    acronyms.sort();
+    format!(
+        "{:x}",
+        md5::compute(format!(
+            "{:?}\n{:?}\n{:?}",
+            autoload_roots, collapsed, acronyms
+        ))
+    )
+}
+
+fn get_constant_resolver_cache(
+    cache_dir: &Path,
+    key: &str,
+) -> ConstantResolverCache {
     let path = cache_dir.join("constant_resolver.json");
     std::fs::File::open(&path)
         .ok()
         .and_then(|file| {
             serde_json::from_reader(std::io::BufReader::new(file)).ok()
         })
+        .filter(|cache: &ConstantResolverCache| cache.key == key)
         .unwrap_or_else(|| ConstantResolverCache {
+            key: key.to_owned(),
             file_definition_map: HashMap::new(),
         })
 }
 
 fn cache_constant_definitions(
     constants: &Vec<ConstantDefinition>,
+    key: String,
     cache_dir: &Path,
     cache_disabled: bool,
 ) {
@@ -317,6 +644,7 @@ fn cache_constant_definitions(
     }
 
     let cache_data_json = serde_json::to_string(&ConstantResolverCache {
+        key,
         file_definition_map,
     })
     .expect("Failed to serialize");
@@ -495,6 +823,21 @@ mod tests {
         teardown();
     }
 
+    #[test]
+    fn constant_in_collapsed_directory() {
+        let autoload_path = PathBuf::from("/app/models");
+        let collapsed: HashSet<PathBuf> =
+            [autoload_path.join("legacy")].into_iter().collect();
+        let constant = inferred_constant_from_file(
+            &autoload_path.join("legacy/billing/invoice.rb"),
+            &autoload_path,
+            &HashSet::new(),
+            &String::from(""),
+            &collapsed,
+        );
+        assert_eq!("::Billing::Invoice", constant.fully_qualified_name);
+    }
+
     #[test]
     fn test_file_map() {
         let absolute_root = &PathBuf::from("tests/fixtures/simple_app")
@@ -594,11 +937,12 @@ mod tests {
             .clone();
         cache_constant_definitions(
             &constants.values().flatten().cloned().collect(),
+            String::from("key"),
             &cache_dir,
             false,
         );
 
-        let cache_data = get_constant_resolver_cache(&cache_dir);
+        let cache_data = get_constant_resolver_cache(&cache_dir, "key");
 
         // ~/workspace/packs - main ! $ tree tests/fixtures/simple_app
         // tests/fixtures/simple_app
@@ -680,6 +1024,7 @@ mod tests {
 
         assert_eq!(
             ConstantResolverCache {
+                key: String::from("key"),
                 file_definition_map: expected_file_definition_map
             },
             cache_data
@@ -721,10 +1066,11 @@ mod tests {
         file.write_all("".as_bytes())
             .expect("Failed to write cache data");
 
-        let cache_data = get_constant_resolver_cache(&cache_dir);
+        let cache_data = get_constant_resolver_cache(&cache_dir, "key");
 
         assert_eq!(
             ConstantResolverCache {
+                key: String::from("key"),
                 file_definition_map: HashMap::new()
             },
             cache_data
@@ -732,4 +1078,52 @@ mod tests {
 
         teardown();
     }
+
+    #[test]
+    fn test_cached_constant_resolver_index() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let root = tmp_dir.path().canonicalize().unwrap();
+        for (relative_path, contents) in [
+            ("packwerk.yml", ""),
+            ("package.yml", ""),
+            ("packs/foo/package.yml", ""),
+            ("packs/foo/app/services/foo.rb", "module Foo\nend\n"),
+        ] {
+            let path = root.join(relative_path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let resolve = |configuration: &configuration::Configuration| {
+            get_zeitwerk_constant_resolver(
+                &configuration.pack_set,
+                &configuration.constant_resolver_configuration(),
+            )
+            .resolve("Foo", &[])
+        };
+
+        let configuration = configuration::get(&root, &0).unwrap();
+        assert!(resolve(&configuration).is_some());
+        assert!(configuration
+            .cache_directory
+            .join(CONSTANT_RESOLVER_INDEX_FILE)
+            .exists());
+
+        // Removing a file from an autoload root invalidates the index, even
+        // if the included files are stale
+        std::fs::remove_dir_all(root.join("packs/foo/app")).unwrap();
+        assert!(resolve(&configuration).is_none());
+
+        // So does adding one that is excluded in `packwerk.yml`
+        std::fs::write(
+            root.join("packwerk.yml"),
+            "exclude:\n- \"**/foo.rb\"\n",
+        )
+        .unwrap();
+        let configuration = configuration::get(&root, &0).unwrap();
+        assert!(resolve(&configuration).is_none());
+        let path = root.join("packs/foo/app/services/foo.rb");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "module Foo\nend\n").unwrap();
+        assert!(resolve(&configuration).is_some());
+    }
 }
@@ -0,0 +1,329 @@
+use crate::packs::parsing::source_map::EmbeddedRuby;
+use crate::packs::parsing::template::{
+    block_end, expression_end, line_index_of, lines, statement_end, string_end,
+    Line, TemplateRuby,
+};
+
+// A line of Ruby code goes on while it ends with a comma or a backslash
+const CONTINUATIONS: [char; 2] = [',', '\\'];
+// Embedded engines whose contents are text, apart from their interpolations
+const TEXT_ENGINES: [&str; 10] = [
+    "javascript",
+    "css",
+    "sass",
+    "scss",
+    "less",
+    "coffee",
+    "markdown",
+    "textile",
+    "rdoc",
+    "erb",
+];
+
+/// Extracts the Ruby code of a Slim template: `-` and `=` lines, attribute
+/// values, `#{}` interpolations and `ruby:` blocks. Comments (`/`) and the
+/// contents of text blocks and other embedded engines are skipped, apart from
+/// their interpolations.
+pub(crate) fn extract_ruby(template: &str) -> EmbeddedRuby {
+    let lines = lines(template);
+    let mut ruby = TemplateRuby::new(template);
+
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.is_blank() {
+            index += 1;
+            continue;
+        }
+
+        let content = &template[line.content_start..line.end];
+        index = if content.starts_with('/') {
+            ruby.close_blocks(line.indent, false);
+            block_end(&lines, index)
+        } else if content.starts_with('|') || content.starts_with('\'') {
+            ruby.close_blocks(line.indent, false);
+            let end = block_end(&lines, index);
+            for text_line in &lines[index..end] {
+                ruby.push_interpolations(
+                    text_line.content_start,
+                    text_line.end,
+                );
+            }
+            end
+        } else if let Some(engine) = embedded_engine(content) {
+            ruby.close_blocks(line.indent, false);
+            let end = block_end(&lines, index);
+            if engine == "ruby" {
+                ruby.push_lines(&lines[index + 1..end]);
+            } else if TEXT_ENGINES.contains(&engine) {
+                for text_line in &lines[index + 1..end] {
+                    ruby.push_interpolations(
+                        text_line.content_start,
+                        text_line.end,
+                    );
+                }
+            }
+            end
+        } else if content.starts_with("doctype") {
+            ruby.close_blocks(line.indent, false);
+            index + 1
+        } else {
+            process_content(
+                &mut ruby,
+                template,
+                &lines,
+                index,
+                line.content_start,
+            )
+        };
+    }
+
+    ruby.finish()
+}
+
+// Processes a line from `start`, returning the index of the next line to
+// process
+fn process_content(
+    ruby: &mut TemplateRuby,
+    template: &str,
+    lines: &[Line],
+    index: usize,
+    start: usize,
+) -> usize {
+    let indent = lines[index].indent;
+    let content = &template[start..lines[index].end];
+
+    if content.starts_with('-') || content.starts_with('=') {
+        let (end, last_index) =
+            statement_end(template, lines, index, &CONTINUATIONS);
+        ruby.push_statement(indent, start + script_prefix_length(content), end);
+        return last_index + 1;
+    }
+
+    ruby.close_blocks(indent, false);
+    if content.starts_with('<') {
+        ruby.push_interpolations(start, lines[index].end);
+        return index + 1;
+    }
+
+    let position = element(ruby, template, start, lines[index].end);
+    let last_index = line_index_of(lines, index, position);
+    let rest = &template[position..lines[last_index].end];
+    let rest_start = position + rest.len() - rest.trim_start().len();
+    let rest = rest.trim();
+    if let Some(child) = rest.strip_prefix(':') {
+        // An inline child element, e.g. `li: a href=url Text`
+        let child_start = lines[last_index].end - child.trim_start().len();
+        if child_start < lines[last_index].end {
+            return process_content(
+                ruby,
+                template,
+                lines,
+                last_index,
+                child_start,
+            );
+        }
+    } else if rest.starts_with('=') {
+        return process_content(ruby, template, lines, last_index, rest_start);
+    } else if !rest.starts_with('/') {
+        ruby.push_interpolations(rest_start, lines[last_index].end);
+    }
+    last_index + 1
+}
+
+// `-`, `=` and `==`, followed by whitespace modifiers for the latter two
+fn script_prefix_length(content: &str) -> usize {
+    if content.starts_with('-') {
+        return 1;
+    }
+    let bytes = content.as_bytes();
+    let mut length = if content.starts_with("==") { 2 } else { 1 };
+    while matches!(bytes.get(length), Some(b'\'' | b'<' | b'>')) {
+        length += 1;
+    }
+    length
+}
+
+// e.g. `javascript:` alone on its line
+fn embedded_engine(content: &str) -> Option<&str> {
+    let name = content.trim_end().strip_suffix(':')?;
+    if name == "ruby" || TEXT_ENGINES.contains(&name) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+// Skips over the tag, classes, ids and attributes of an element starting at
+// `start`, pushing the code of its attribute values. Returns the offset of
+// what follows.
+fn element(
+    ruby: &mut TemplateRuby,
+    template: &str,
+    start: usize,
+    line_end: usize,
+) -> usize {
+    let bytes = template.as_bytes();
+    let mut position = skip_name(bytes, start, line_end);
+    while position < line_end && matches!(bytes[position], b'.' | b'#') {
+        position = skip_name(bytes, position + 1, line_end);
+    }
+
+    if position < line_end && matches!(bytes[position], b'(' | b'[' | b'{') {
+        // Wrapped attributes may span lines and include boolean ones
+        let end = expression_end(template, position + 1, false);
+        attributes(ruby, template, position + 1, end, true);
+        (end + 1).min(bytes.len())
+    } else {
+        attributes(ruby, template, position, line_end, false)
+    }
+}
+
+// Pushes the values of the attributes between `start` and `end`, returning
+// where they stop
+fn attributes(
+    ruby: &mut TemplateRuby,
+    template: &str,
+    start: usize,
+    end: usize,
+    wrapped: bool,
+) -> usize {
+    let bytes = template.as_bytes();
+    let mut position = start;
+    loop {
+        let mut attribute_start = position;
+        while attribute_start < end
+            && bytes[attribute_start].is_ascii_whitespace()
+        {
+            attribute_start += 1;
+        }
+        if attribute_start >= end {
+            return end;
+        }
+
+        if bytes[attribute_start] == b'*' {
+            // Splat attributes, e.g. `*{ data: Foo.data }` or `*attributes`
+            let value_end =
+                expression_end(template, attribute_start + 1, true).min(end);
+            ruby.push_expression(attribute_start + 1, value_end);
+            position = value_end;
+            continue;
+        }
+
+        let name_end = skip_name(bytes, attribute_start, end);
+        if name_end == attribute_start {
+            return position;
+        }
+        if bytes.get(name_end) != Some(&b'=') {
+            if !wrapped {
+                // Text, rather than a boolean attribute
+                return position;
+            }
+            position = name_end;
+            continue;
+        }
+
+        let mut value_start = name_end + 1;
+        if bytes.get(value_start) == Some(&b'=') {
+            value_start += 1;
+        }
+        position = match bytes.get(value_start) {
+            Some(quote @ (b'"' | b'\'')) => {
+                let value_end =
+                    string_end(template, value_start + 1, *quote).min(end);
+                ruby.push_interpolations(value_start, value_end);
+                value_end
+            }
+            _ => {
+                let value_end =
+                    expression_end(template, value_start, true).min(end);
+                ruby.push_expression(value_start, value_end);
+                value_end
+            }
+        };
+    }
+}
+
+fn skip_name(bytes: &[u8], start: usize, end: usize) -> usize {
+    let mut position = start;
+    while position < end
+        && (bytes[position].is_ascii_alphanumeric()
+            || matches!(bytes[position], b'_' | b'-'))
+    {
+        position += 1;
+    }
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::parsing::Range;
+
+    #[test]
+    fn test_extract_ruby() {
+        let template = [
+            "doctype html",
+            "/ Comment mentioning Foo",
+            "  and Bar",
+            "div.card#main class=Classes::Foo data-id=\"#{Ids::Bar}\"",
+            "  h1 = Greeting.new(user,",
+            "    name)",
+            "  - if admin?",
+            "    span Hello #{Admin.name}",
+            "  - else",
+            "    == render Other",
+            "  - items.each do |item|",
+            "    li: a(href=item_path(item) disabled) = item.name",
+            "| Text mentioning Baz",
+            "  and #{Qux}",
+            "ruby:",
+            "  x = Baz.new",
+            "  y = x",
+            "javascript:",
+            "  var a = #{Quux.to_json};",
+        ]
+        .join("\n");
+        let embedded_ruby = extract_ruby(&template);
+        assert_eq!(
+            embedded_ruby.ruby,
+            [
+                "Classes::Foo",
+                "Ids::Bar",
+                "Greeting.new(user,",
+                "    name)",
+                "if admin?",
+                "Admin.name",
+                "else",
+                "render Other",
+                "end",
+                "items.each do |item|",
+                "item_path(item)",
+                "item.name",
+                "end",
+                "Qux",
+                "x = Baz.new",
+                "  y = x",
+                "Quux.to_json",
+            ]
+            .join("\n")
+        );
+
+        let location = |start_row, start_col, end_col| Range {
+            start_row,
+            start_col,
+            end_row: start_row,
+            end_col,
+        };
+        // `Admin` on line 6 of the Ruby
+        assert_eq!(
+            embedded_ruby.map_range(&location(6, 0, 6)),
+            location(8, 17, 23)
+        );
+        // `Ids` on line 2 of the Ruby
+        assert_eq!(
+            embedded_ruby.map_range(&location(2, 0, 4)),
+            location(4, 44, 48)
+        );
+    }
+}
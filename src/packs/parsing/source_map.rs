@@ -0,0 +1,150 @@
+use line_col::LineColLookup;
+
+use super::{ParseDiagnostic, Range, UnresolvedReference};
+
+/// Ruby code extracted from a template, along with where each piece of it
+/// came from, so that references found in the code can be reported at their
+/// position in the template.
+#[derive(Debug, Default)]
+pub(crate) struct EmbeddedRuby {
+    pub ruby: String,
+    segments: Vec<Segment>,
+    // Number of lines in `ruby`
+    rows: usize,
+}
+
+// A piece of code copied verbatim from the template, starting on its own line
+// of the generated Ruby
+#[derive(Debug)]
+struct Segment {
+    generated_row: usize,
+    template_row: usize,
+    template_col: usize,
+}
+
+impl EmbeddedRuby {
+    /// Appends `code`, which starts at byte `offset` of the template, on a new
+    /// line of the generated Ruby
+    pub(crate) fn push(
+        &mut self,
+        code: &str,
+        offset: usize,
+        template_lookup: &LineColLookup,
+    ) {
+        if self.rows > 0 {
+            self.ruby.push('\n');
+        }
+        let (template_row, template_col) = template_lookup.get(offset);
+        self.segments.push(Segment {
+            generated_row: self.rows + 1,
+            template_row,
+            template_col: template_col - 1,
+        });
+        self.ruby.push_str(code);
+        self.rows += code.matches('\n').count() + 1;
+    }
+
+    /// Appends a line that has no counterpart in the template, e.g. the `end`
+    /// closing a block that the template closes by indentation
+    pub(crate) fn push_generated(&mut self, code: &str) {
+        if self.rows > 0 {
+            self.ruby.push('\n');
+        }
+        self.ruby.push_str(code);
+        self.rows += code.matches('\n').count() + 1;
+    }
+
+    /// Maps a range in the generated Ruby back to the template
+    pub(crate) fn map_range(&self, range: &Range) -> Range {
+        let (start_row, start_col) = self.map(range.start_row, range.start_col);
+        let (end_row, end_col) = self.map(range.end_row, range.end_col);
+        Range {
+            start_row,
+            start_col,
+            end_row,
+            end_col,
+        }
+    }
+
+    pub(crate) fn map_references(
+        &self,
+        references: Vec<UnresolvedReference>,
+    ) -> Vec<UnresolvedReference> {
+        references
+            .into_iter()
+            .map(|reference| UnresolvedReference {
+                location: self.map_range(&reference.location),
+                ..reference
+            })
+            .collect()
+    }
+
+    pub(crate) fn map_parse_errors(
+        &self,
+        parse_errors: Vec<ParseDiagnostic>,
+    ) -> Vec<ParseDiagnostic> {
+        parse_errors
+            .into_iter()
+            .map(|parse_error| ParseDiagnostic {
+                location: self.map_range(&parse_error.location),
+                ..parse_error
+            })
+            .collect()
+    }
+
+    fn map(&self, row: usize, col: usize) -> (usize, usize) {
+        let segment_index = self
+            .segments
+            .partition_point(|segment| segment.generated_row <= row);
+        let Some(segment) = segment_index
+            .checked_sub(1)
+            .map(|index| &self.segments[index])
+        else {
+            return (row, col);
+        };
+        if row == segment.generated_row {
+            // Only the first line of a segment is shifted
+            (segment.template_row, segment.template_col + col)
+        } else {
+            (segment.template_row + row - segment.generated_row, col)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_range() {
+        let template = "<p>\n  <%= Foo %> <% Bar\n  Baz %>\n";
+        let lookup = LineColLookup::new(template);
+        let mut embedded_ruby = EmbeddedRuby::default();
+        embedded_ruby.push("Foo", template.find("Foo").unwrap(), &lookup);
+        embedded_ruby.push(
+            "Bar\n  Baz",
+            template.find("Bar").unwrap(),
+            &lookup,
+        );
+        assert_eq!(embedded_ruby.ruby, "Foo\nBar\n  Baz");
+
+        let range = |start_row, start_col, end_row, end_col| Range {
+            start_row,
+            start_col,
+            end_row,
+            end_col,
+        };
+        assert_eq!(
+            embedded_ruby.map_range(&range(1, 0, 1, 4)),
+            range(2, 6, 2, 10)
+        );
+        assert_eq!(
+            embedded_ruby.map_range(&range(2, 0, 2, 4)),
+            range(2, 16, 2, 20)
+        );
+        assert_eq!(
+            embedded_ruby.map_range(&range(3, 2, 3, 6)),
+            range(3, 2, 3, 6)
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use std::path::Path;
+
+use line_col::LineColLookup;
+
+use crate::packs::file_utils::file_read_contents;
+use crate::packs::parsing::process_from_ruby_contents;
+use crate::packs::parsing::ruby::parse_utils::{
+    extract_sigils_from_contents, extract_suppressions_from_contents,
+};
+use crate::packs::{Configuration, ProcessedFile};
+
+use super::source_map::EmbeddedRuby;
+
+const BLOCK_KEYWORDS: [&str; 7] =
+    ["if", "unless", "case", "while", "until", "for", "begin"];
+const BLOCK_CONTINUATION_KEYWORDS: [&str; 5] =
+    ["else", "elsif", "when", "rescue", "ensure"];
+
+/// Collects the references of the Ruby that `extract_ruby` finds in a
+/// template, with the parser selected by the configuration
+pub(crate) fn process_from_path(
+    path: &Path,
+    configuration: &Configuration,
+    extract_ruby: fn(&str) -> EmbeddedRuby,
+) -> anyhow::Result<ProcessedFile> {
+    let contents = file_read_contents(path, configuration)?;
+    Ok(process_from_contents(
+        contents,
+        path,
+        configuration,
+        extract_ruby,
+    ))
+}
+
+pub(crate) fn process_from_contents(
+    contents: String,
+    path: &Path,
+    configuration: &Configuration,
+    extract_ruby: fn(&str) -> EmbeddedRuby,
+) -> ProcessedFile {
+    let embedded_ruby = extract_ruby(&contents);
+    let processed_file = process_from_ruby_contents(
+        embedded_ruby.ruby.clone(),
+        path,
+        configuration,
+    );
+
+    ProcessedFile {
+        absolute_path: path.to_path_buf(),
+        unresolved_references: embedded_ruby
+            .map_references(processed_file.unresolved_references),
+        definitions: vec![],
+        sigils: extract_sigils_from_contents(&contents),
+        suppressions: extract_suppressions_from_contents(&contents),
+        parse_errors: embedded_ruby
+            .map_parse_errors(processed_file.parse_errors),
+    }
+}
+
+/// Byte offsets of a line of a template, without its line break
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Line {
+    pub start: usize,
+    pub content_start: usize,
+    pub end: usize,
+    pub indent: usize,
+}
+
+impl Line {
+    pub(crate) fn is_blank(&self) -> bool {
+        self.content_start == self.end
+    }
+}
+
+pub(crate) fn lines(template: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for line in template.split('\n') {
+        let text = line.strip_suffix('\r').unwrap_or(line);
+        let indent = text.len() - text.trim_start().len();
+        lines.push(Line {
+            start,
+            content_start: start + indent,
+            end: start + text.len(),
+            indent,
+        });
+        start += line.len() + 1;
+    }
+    lines
+}
+
+/// The index after the last line nested under line `index`, i.e. indented
+/// deeper than it
+pub(crate) fn block_end(lines: &[Line], index: usize) -> usize {
+    let indent = lines[index].indent;
+    let mut end = index + 1;
+    for (nested_index, line) in lines.iter().enumerate().skip(index + 1) {
+        if line.is_blank() {
+            continue;
+        }
+        if line.indent <= indent {
+            break;
+        }
+        end = nested_index + 1;
+    }
+    end
+}
+
+/// The index of the line containing byte `offset`, starting the search at
+/// line `index`
+pub(crate) fn line_index_of(
+    lines: &[Line],
+    index: usize,
+    offset: usize,
+) -> usize {
+    let mut line_index = index;
+    while line_index + 1 < lines.len() && lines[line_index + 1].start <= offset
+    {
+        line_index += 1;
+    }
+    line_index
+}
+
+/// The end of the statement on line `index`, which goes on over the next
+/// lines as long as they end with one of `continuations`. Returns the end
+/// offset and the index of the last line of the statement.
+pub(crate) fn statement_end(
+    template: &str,
+    lines: &[Line],
+    index: usize,
+    continuations: &[char],
+) -> (usize, usize) {
+    let mut last_index = index;
+    while last_index + 1 < lines.len()
+        && template[lines[last_index].start..lines[last_index].end]
+            .trim_end()
+            .ends_with(continuations)
+    {
+        last_index += 1;
+    }
+    (lines[last_index].end, last_index)
+}
+
+/// The offset where the Ruby expression starting at `start` ends: at an
+/// unmatched closing bracket, or at whitespace outside of brackets when
+/// `stop_at_whitespace` is set. Strings are skipped over.
+pub(crate) fn expression_end(
+    source: &str,
+    start: usize,
+    stop_at_whitespace: bool,
+) -> usize {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut index = start;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                if depth == 0 {
+                    return index;
+                }
+                depth -= 1;
+            }
+            quote @ (b'"' | b'\'') => {
+                index = string_end(source, index + 1, quote);
+                continue;
+            }
+            byte if stop_at_whitespace
+                && depth == 0
+                && byte.is_ascii_whitespace() =>
+            {
+                return index
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    bytes.len()
+}
+
+/// The offset right after the `quote` closing the string whose contents
+/// start at `start`
+pub(crate) fn string_end(source: &str, start: usize, quote: u8) -> usize {
+    let bytes = source.as_bytes();
+    let mut index = start;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' {
+            index += 2;
+            continue;
+        }
+        if bytes[index] == quote {
+            return index + 1;
+        }
+        index += 1;
+    }
+    bytes.len()
+}
+
+/// Builds the Ruby of a template that nests code by indentation, like HAML
+/// and Slim. A statement opening a block (`- if`, `- items.each do |item|`)
+/// has no `end` in the template, so one is generated once a line is indented
+/// no deeper than the statement.
+pub(crate) struct TemplateRuby<'a> {
+    template: &'a str,
+    lookup: LineColLookup<'a>,
+    embedded_ruby: EmbeddedRuby,
+    // Indentation of the statements whose block is still open
+    open_blocks: Vec<usize>,
+}
+
+impl<'a> TemplateRuby<'a> {
+    pub(crate) fn new(template: &'a str) -> TemplateRuby<'a> {
+        TemplateRuby {
+            template,
+            lookup: LineColLookup::new(template),
+            embedded_ruby: EmbeddedRuby::default(),
+            open_blocks: Vec::new(),
+        }
+    }
+
+    /// Pushes the code between `start` and `end`, if there is any
+    pub(crate) fn push_expression(&mut self, start: usize, end: usize) {
+        let code = &self.template[start..end];
+        let trimmed_code = code.trim();
+        if trimmed_code.is_empty() {
+            return;
+        }
+        let leading_whitespace = code.len() - code.trim_start().len();
+        self.embedded_ruby.push(
+            trimmed_code,
+            start + leading_whitespace,
+            &self.lookup,
+        );
+    }
+
+    /// Pushes the statement of a line indented by `indent`, closing the
+    /// blocks it is not nested in
+    pub(crate) fn push_statement(
+        &mut self,
+        indent: usize,
+        start: usize,
+        end: usize,
+    ) {
+        let code = self.template[start..end].trim();
+        let continues_block =
+            starts_with_keyword(code, &BLOCK_CONTINUATION_KEYWORDS);
+        let opens_block = !continues_block && opens_block(code);
+        self.close_blocks(indent, continues_block);
+        self.push_expression(start, end);
+        if opens_block {
+            self.open_blocks.push(indent);
+        }
+    }
+
+    /// Pushes each line of code between the first and last non-blank lines,
+    /// e.g. the contents of a `:ruby` filter
+    pub(crate) fn push_lines(&mut self, lines: &[Line]) {
+        let first_line = lines.iter().find(|line| !line.is_blank());
+        let last_line = lines.iter().rfind(|line| !line.is_blank());
+        if let (Some(first_line), Some(last_line)) = (first_line, last_line) {
+            self.push_expression(first_line.content_start, last_line.end);
+        }
+    }
+
+    /// Pushes the code of each `#{}` between `start` and `end`
+    pub(crate) fn push_interpolations(&mut self, start: usize, end: usize) {
+        let mut position = start;
+        while let Some(found) = self.template[position..end].find("#{") {
+            let code_start = position + found + 2;
+            let code_end =
+                expression_end(self.template, code_start, false).min(end);
+            self.push_expression(code_start, code_end);
+            position = (code_end + 1).min(end);
+        }
+    }
+
+    /// Closes the blocks opened at `indent` or deeper. The block opened at
+    /// `indent` is kept open for `else` and the like.
+    pub(crate) fn close_blocks(
+        &mut self,
+        indent: usize,
+        keep_same_level: bool,
+    ) {
+        while let Some(&block_indent) = self.open_blocks.last() {
+            if block_indent < indent
+                || (block_indent == indent && keep_same_level)
+            {
+                break;
+            }
+            self.open_blocks.pop();
+            self.embedded_ruby.push_generated("end");
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> EmbeddedRuby {
+        self.close_blocks(0, false);
+        self.embedded_ruby
+    }
+}
+
+fn starts_with_keyword(code: &str, keywords: &[&str]) -> bool {
+    let first_word = code
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default();
+    keywords.contains(&first_word)
+}
+
+// e.g. `if user`, `items.each do |item|` or `form_with model: user do`
+fn opens_block(code: &str) -> bool {
+    if starts_with_keyword(code, &BLOCK_KEYWORDS) {
+        return true;
+    }
+    let mut code = code.trim_end();
+    if let Some(without_closing_pipe) = code.strip_suffix('|') {
+        let Some(opening_pipe) = without_closing_pipe.rfind('|') else {
+            return false;
+        };
+        code = without_closing_pipe[..opening_pipe].trim_end();
+    }
+    code.strip_suffix("do").is_some_and(|before| {
+        !before
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::parsing::Range;
+
+    #[test]
+    fn test_expression_end() {
+        let source = "foo(bar, \"baz)\") qux} quux";
+        assert_eq!(expression_end(source, 0, true), 16);
+        assert_eq!(expression_end(source, 0, false), 20);
+    }
+
+    #[test]
+    fn test_process_from_contents() {
+        let contents = "%ul\n  - if admin?\n    %li= Foo::Bar.name\n";
+        let mut configuration = Configuration::default();
+        for experimental_parser in [false, true] {
+            configuration.experimental_parser = experimental_parser;
+            let processed_file = process_from_contents(
+                contents.to_owned(),
+                Path::new("path/to/file.html.haml"),
+                &configuration,
+                crate::packs::parsing::haml::tokenizer::extract_ruby,
+            );
+            let references: Vec<(String, Range)> = processed_file
+                .unresolved_references
+                .into_iter()
+                .map(|reference| (reference.name, reference.location))
+                .collect();
+            assert_eq!(
+                references,
+                vec![(
+                    String::from("Foo::Bar"),
+                    Range {
+                        start_row: 3,
+                        start_col: 9,
+                        end_row: 3,
+                        end_col: 18,
+                    }
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_ends() {
+        let template = "- if a\n  - b.each do |c|\n    = c\n- else\n  = d\n= e";
+        let lines = lines(template);
+        let mut ruby = TemplateRuby::new(template);
+        for line in &lines {
+            ruby.push_statement(line.indent, line.content_start + 1, line.end);
+        }
+        assert_eq!(
+            ruby.finish().ruby,
+            "if a\nb.each do |c|\nc\nend\nelse\nd\nend\ne"
+        );
+    }
+}
@@ -10,6 +10,12 @@ use serde::{
     Deserialize, Deserializer, Serialize,
 };
 
+use super::caching::CacheBackend;
+use super::checker::external::ExternalChecker;
+use super::checker::layer::LayersDefinition;
+use super::checker::rules::Rule;
+use super::parsing::ruby::RubyParser;
+
 const CONFIG_FILE_NAME: &str = "packwerk.yml";
 const PACKS_FIRST_CONFIG_FILE_NAME: &str = "packs.yml";
 
@@ -44,18 +50,26 @@ pub(crate) struct RawConfiguration {
     #[serde(default = "default_cache_directory")]
     pub cache_directory: String,
 
+    // How cache entries are stored, `per_file`, `packed` or `content_addressed`
+    #[serde(default)]
+    pub cache_backend: CacheBackend,
+
     // Autoload paths used to resolve constants
     #[serde(default)]
     pub autoload_paths: Option<Vec<String>>,
 
-    // Architecture layers
+    // Architecture layers, as an ordered list or a graph
     #[serde(default)]
-    pub layers: Vec<String>,
+    pub layers: LayersDefinition,
 
     // Experimental parser
     #[serde(default)]
     pub experimental_parser: bool,
 
+    // The library Ruby is parsed with, `lib_ruby_parser` or `prism`
+    #[serde(default)]
+    pub ruby_parser: RubyParser,
+
     // Ignored monkey patches
     #[serde(default)]
     pub ignored_definitions: HashMap<String, HashSet<PathBuf>>,
@@ -64,6 +78,16 @@ pub(crate) struct RawConfiguration {
     #[serde(default)]
     pub autoload_roots: HashMap<PathBuf, String>,
 
+    // Globs of directories that don't add a namespace, like Zeitwerk's
+    // `collapse`
+    #[serde(default)]
+    pub autoload_collapse: Vec<String>,
+
+    // Globs of files and directories that don't define constants, like
+    // Zeitwerk's `ignore`
+    #[serde(default)]
+    pub autoload_ignore: Vec<String>,
+
     // Relative path to inflections file
     #[serde(default)]
     pub inflections_path: Option<PathBuf>,
@@ -71,6 +95,35 @@ pub(crate) struct RawConfiguration {
     // Use packs copy
     #[serde(default)]
     pub packs_first_mode: bool,
+
+    // Whether files that fail to parse make `check` fail
+    #[serde(default)]
+    pub fail_on_parse_errors: bool,
+
+    // Whether string literals naming constants are references, e.g.
+    // `"Foo".constantize`
+    #[serde(default)]
+    pub string_references: bool,
+
+    // Methods called on or with a string naming a constant
+    #[serde(default = "default_string_reference_methods")]
+    pub string_reference_methods: Vec<String>,
+
+    // Keyword arguments whose string value names a constant
+    #[serde(default = "default_string_reference_keywords")]
+    pub string_reference_keywords: Vec<String>,
+
+    // The checkers that string references are checked by
+    #[serde(default = "default_string_reference_enforcements")]
+    pub string_reference_enforcements: HashSet<String>,
+
+    // Allowed and denied dependencies between packs matching globs
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    // Checkers run as external processes
+    #[serde(default)]
+    pub external_checkers: Vec<ExternalChecker>,
 }
 
 pub(crate) fn get(
@@ -137,6 +190,8 @@ fn default_include() -> Vec<String> {
         String::from("**/*.rb"),
         String::from("**/*.rake"),
         String::from("**/*.erb"),
+        String::from("**/*.haml"),
+        String::from("**/*.slim"),
     ]
 }
 
@@ -152,6 +207,32 @@ fn default_custom_associations() -> Vec<String> {
     vec![]
 }
 
+fn default_string_reference_methods() -> Vec<String> {
+    vec![
+        String::from("constantize"),
+        String::from("safe_constantize"),
+        String::from("const_get"),
+    ]
+}
+
+fn default_string_reference_keywords() -> Vec<String> {
+    vec![String::from("class_name")]
+}
+
+fn default_string_reference_enforcements() -> HashSet<String> {
+    [
+        "dependency",
+        "folder_privacy",
+        "layer",
+        "privacy",
+        "rule",
+        "visibility",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
 fn default_cache() -> bool {
     true
 }
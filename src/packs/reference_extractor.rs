@@ -7,11 +7,15 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use tracing::debug;
 
 use crate::packs::{
-    get_experimental_constant_resolver, get_zeitwerk_constant_resolver,
-    process_files_with_cache, ProcessedFile,
+    caching::maintenance::RunStats, get_experimental_constant_resolver,
+    get_zeitwerk_constant_resolver,
+    parsing::process_files_with_cache_and_stats, ProcessedFile,
 };
 
-use super::{checker::reference::Reference, Configuration, Sigil};
+use super::{
+    checker::reference::Reference, constant_resolver::ConstantResolver,
+    Configuration, Sigil,
+};
 
 // It might be nice to have this return a simpler type rather than the tuple
 // This method returns everything we need as input into packwerk checking
@@ -21,52 +25,109 @@ pub(crate) fn get_all_references_and_sigils(
     configuration: &Configuration,
     absolute_paths: &HashSet<PathBuf>,
 ) -> anyhow::Result<(Vec<Reference>, HashMap<PathBuf, Vec<Sigil>>)> {
-    let cache = configuration.get_cache();
+    let (constant_resolver, processed_files_to_check) =
+        get_constant_resolver_and_processed_files(
+            configuration,
+            absolute_paths,
+        )?;
 
-    debug!("Getting unresolved references (using cache if possible)");
+    get_references_and_sigils_from_processed_files(
+        configuration,
+        constant_resolver.as_ref(),
+        &processed_files_to_check
+            .iter()
+            .collect::<Vec<&ProcessedFile>>(),
+    )
+}
 
-    let (constant_resolver, processed_files_to_check) = if configuration
-        .experimental_parser
-    {
-        // The experimental parser needs *all* processed files to get definitions
-        let all_processed_files: Vec<ProcessedFile> = process_files_with_cache(
-            &configuration.included_files,
-            cache,
+// Processes `absolute_paths` and builds the constant resolver for the
+// configured parser
+#[allow(clippy::type_complexity)]
+pub(crate) fn get_constant_resolver_and_processed_files(
+    configuration: &Configuration,
+    absolute_paths: &HashSet<PathBuf>,
+) -> anyhow::Result<(Box<dyn ConstantResolver + Send + Sync>, Vec<ProcessedFile>)>
+{
+    let (constant_resolver, processed_files, _run_stats) =
+        get_constant_resolver_processed_files_and_stats(
             configuration,
+            absolute_paths,
         )?;
+    Ok((constant_resolver, processed_files))
+}
 
-        let constant_resolver = get_experimental_constant_resolver(
-            &configuration.absolute_root,
-            &all_processed_files,
-            &configuration.ignored_definitions,
-        );
+// Like `get_constant_resolver_and_processed_files`, also returning the cache
+// hits and misses
+#[allow(clippy::type_complexity)]
+pub(crate) fn get_constant_resolver_processed_files_and_stats(
+    configuration: &Configuration,
+    absolute_paths: &HashSet<PathBuf>,
+) -> anyhow::Result<(
+    Box<dyn ConstantResolver + Send + Sync>,
+    Vec<ProcessedFile>,
+    RunStats,
+)> {
+    let cache = configuration.get_cache();
 
-        let processed_files_to_check = all_processed_files
-            .into_iter()
-            .filter(|processed_file| {
-                absolute_paths.contains(&processed_file.absolute_path)
-            })
-            .collect();
-
-        (constant_resolver, processed_files_to_check)
-    } else {
-        let processed_files: Vec<ProcessedFile> =
-            process_files_with_cache(absolute_paths, cache, configuration)?;
-
-        // The zeitwerk constant resolver doesn't look at processed files to get definitions
-        let constant_resolver = get_zeitwerk_constant_resolver(
-            &configuration.pack_set,
-            &configuration.constant_resolver_configuration(),
-        );
+    debug!("Getting unresolved references (using cache if possible)");
+
+    let (constant_resolver, processed_files_to_check, run_stats) =
+        if configuration.experimental_parser {
+            // The experimental parser needs *all* processed files to get definitions
+            let (all_processed_files, run_stats) =
+                process_files_with_cache_and_stats(
+                    &configuration.included_files,
+                    cache,
+                    configuration,
+                )?;
+
+            let constant_resolver = get_experimental_constant_resolver(
+                &configuration.absolute_root,
+                &all_processed_files,
+                &configuration.ignored_definitions,
+            );
+
+            let processed_files_to_check = all_processed_files
+                .into_iter()
+                .filter(|processed_file| {
+                    absolute_paths.contains(&processed_file.absolute_path)
+                })
+                .collect();
+
+            (constant_resolver, processed_files_to_check, run_stats)
+        } else {
+            let (processed_files, run_stats) =
+                process_files_with_cache_and_stats(
+                    absolute_paths,
+                    cache,
+                    configuration,
+                )?;
 
-        (constant_resolver, processed_files)
-    };
+            // The zeitwerk constant resolver doesn't look at processed files to get definitions
+            let constant_resolver = get_zeitwerk_constant_resolver(
+                &configuration.pack_set,
+                &configuration.constant_resolver_configuration(),
+            );
+
+            (constant_resolver, processed_files, run_stats)
+        };
+
+    Ok((constant_resolver, processed_files_to_check, run_stats))
+}
 
+// Resolves the references of files that have already been processed, e.g.
+// the files `pks server` keeps in memory between requests.
+#[allow(clippy::type_complexity)]
+pub(crate) fn get_references_and_sigils_from_processed_files(
+    configuration: &Configuration,
+    constant_resolver: &(dyn ConstantResolver + Send + Sync),
+    processed_files_to_check: &[&ProcessedFile],
+) -> anyhow::Result<(Vec<Reference>, HashMap<PathBuf, Vec<Sigil>>)> {
     // Now we're going to get all the files with sigils (i.e. processed_files_to_check where property sigils is not empty)
     // And then make a separate map of PathBuf => Sigils
     debug!("Getting sigils");
     let mut path_to_sigils: HashMap<PathBuf, Vec<Sigil>> = HashMap::new();
-    for processed_file in &processed_files_to_check {
+    for processed_file in processed_files_to_check {
         if !processed_file.sigils.is_empty() {
             path_to_sigils.insert(
                 processed_file.absolute_path.to_owned(),
@@ -86,7 +147,7 @@ pub(crate) fn get_all_references_and_sigils(
                 for unresolved_ref in &processed_file.unresolved_references {
                     let mut refs = Reference::from_unresolved_reference(
                         configuration,
-                        constant_resolver.as_ref(),
+                        constant_resolver,
                         unresolved_ref,
                         &processed_file.absolute_path,
                     )?;
@@ -0,0 +1,476 @@
+// `pks server` keeps the configuration, the constant resolver and every
+// processed file in memory, and answers newline-delimited JSON requests over
+// stdio or a Unix socket. A filesystem watcher records changed paths, which
+// are applied right before the next request is answered: edited source files
+// are reprocessed on their own, while changes to configuration files, or
+// files being added or deleted, rebuild the state from scratch.
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixListener,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Context};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use super::{
+    checker::{self, CheckAllResult},
+    configuration,
+    constant_resolver::ConstantResolver,
+    file_utils::{build_glob_set, get_file_type},
+    get_experimental_constant_resolver, get_zeitwerk_constant_resolver,
+    process_files_with_cache,
+    walk_directory::DEFAULT_EXCLUDED_DIRS,
+    Configuration, ProcessedFile,
+};
+
+// Changes to these files can affect any pack, so they trigger a full reload
+const CONFIGURATION_FILE_NAMES: [&str; 4] = [
+    "packwerk.yml",
+    "packs.yml",
+    "package.yml",
+    "package_todo.yml",
+];
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: RequestParams,
+}
+
+#[derive(Deserialize, Default)]
+struct RequestParams {
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Response {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct PendingChanges {
+    paths: HashSet<PathBuf>,
+    // Set when the watcher reports an error (e.g. a dropped event queue), in
+    // which case we can no longer trust `paths` to be complete
+    reload: bool,
+}
+
+pub(crate) struct ServerState {
+    configuration: Configuration,
+    constant_resolver: Box<dyn ConstantResolver + Send + Sync>,
+    processed_files: HashMap<PathBuf, ProcessedFile>,
+    pending_changes: Arc<Mutex<PendingChanges>>,
+}
+
+impl ServerState {
+    pub(crate) fn new(configuration: Configuration) -> anyhow::Result<Self> {
+        debug!("Processing all included files");
+        let processed_files: HashMap<PathBuf, ProcessedFile> =
+            process_files_with_cache(
+                &configuration.included_files,
+                configuration.get_cache(),
+                &configuration,
+            )?
+            .into_iter()
+            .map(|processed_file| {
+                (processed_file.absolute_path.clone(), processed_file)
+            })
+            .collect();
+        let constant_resolver =
+            build_constant_resolver(&configuration, &processed_files);
+
+        Ok(Self {
+            configuration,
+            constant_resolver,
+            processed_files,
+            pending_changes: Arc::default(),
+        })
+    }
+
+    pub(crate) fn check(
+        &mut self,
+        files: Vec<String>,
+    ) -> anyhow::Result<CheckAllResult> {
+        self.apply_pending_changes()?;
+
+        let absolute_paths = self.configuration.intersect_files(files);
+        let processed_files: Vec<&ProcessedFile> = absolute_paths
+            .iter()
+            .filter_map(|path| self.processed_files.get(path))
+            .collect();
+
+        checker::check_processed_files(
+            &self.configuration,
+            self.constant_resolver.as_ref(),
+            &processed_files,
+        )
+    }
+
+    pub(crate) fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    pub(crate) fn constant_resolver(
+        &self,
+    ) -> &(dyn ConstantResolver + Send + Sync) {
+        self.constant_resolver.as_ref()
+    }
+
+    pub(crate) fn processed_file(
+        &self,
+        absolute_path: &Path,
+    ) -> Option<&ProcessedFile> {
+        self.processed_files.get(absolute_path)
+    }
+
+    // For clients that know about changes before the watcher does, e.g. an
+    // editor saving a file
+    pub(crate) fn mark_changed(&self, absolute_path: PathBuf) {
+        self.pending_changes
+            .lock()
+            .unwrap()
+            .paths
+            .insert(absolute_path);
+    }
+
+    pub(crate) fn reload(&mut self) -> anyhow::Result<()> {
+        debug!("Reloading configuration");
+        let mut configuration =
+            configuration::get(&self.configuration.absolute_root, &0)?;
+        carry_over_overrides(&self.configuration, &mut configuration);
+
+        let pending_changes = Arc::clone(&self.pending_changes);
+        *self = Self {
+            pending_changes,
+            ..Self::new(configuration)?
+        };
+        Ok(())
+    }
+
+    fn apply_pending_changes(&mut self) -> anyhow::Result<()> {
+        let PendingChanges { paths, reload } =
+            std::mem::take(&mut *self.pending_changes.lock().unwrap());
+
+        if reload || paths.iter().any(|path| self.requires_reload(path)) {
+            return self.reload();
+        }
+
+        let changed_files: HashSet<PathBuf> = paths
+            .into_iter()
+            .filter(|path| self.configuration.included_files.contains(path))
+            .collect();
+        if changed_files.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Reprocessing {} changed file(s)", changed_files.len());
+        for processed_file in process_files_with_cache(
+            &changed_files,
+            self.configuration.get_cache(),
+            &self.configuration,
+        )? {
+            self.processed_files
+                .insert(processed_file.absolute_path.clone(), processed_file);
+        }
+
+        // The zeitwerk resolver only depends on file paths, which haven't
+        // changed, but the experimental resolver reads definitions from the
+        // processed files themselves.
+        if self.configuration.experimental_parser {
+            self.constant_resolver = build_constant_resolver(
+                &self.configuration,
+                &self.processed_files,
+            );
+        }
+        Ok(())
+    }
+
+    fn requires_reload(&self, path: &Path) -> bool {
+        let is_configuration_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| CONFIGURATION_FILE_NAMES.contains(&name))
+            || path == self.configuration.inflections_path;
+
+        // Only files a new walk would include count, so that e.g. files
+        // written to excluded directories don't trigger reloads
+        let is_added_or_deleted = get_file_type(path).is_some()
+            && path
+                .strip_prefix(&self.configuration.absolute_root)
+                .is_ok_and(|relative_path| {
+                    self.configuration.file_matcher.is_included(relative_path)
+                })
+            && self.configuration.included_files.contains(path)
+                != path.exists();
+
+        is_configuration_file || is_added_or_deleted
+    }
+
+    pub(crate) fn watch(&self) -> anyhow::Result<RecommendedWatcher> {
+        let pending_changes = Arc::clone(&self.pending_changes);
+        let absolute_root = self.configuration.absolute_root.clone();
+        let cache_directory = self.configuration.cache_directory.clone();
+        // Changes inside directories like `.git/` and `node_modules/` are
+        // frequent and never matter
+        let default_excluded_dirs =
+            build_glob_set(&DEFAULT_EXCLUDED_DIRS.map(|dir| dir.to_string()));
+        let is_ignored = move |path: &Path| {
+            path.starts_with(&cache_directory)
+                || path.strip_prefix(&absolute_root).is_ok_and(|relative| {
+                    relative
+                        .ancestors()
+                        .any(|dir| default_excluded_dirs.is_match(dir))
+                })
+        };
+
+        let mut watcher = notify::recommended_watcher(
+            move |event: notify::Result<notify::Event>| {
+                let mut pending_changes = pending_changes.lock().unwrap();
+                match event {
+                    Ok(event) if !event.kind.is_access() => {
+                        pending_changes.paths.extend(
+                            event.paths.into_iter().filter(|p| !is_ignored(p)),
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        debug!("File watcher error, will reload: {}", err);
+                        pending_changes.reload = true;
+                    }
+                }
+            },
+        )?;
+        watcher.watch(
+            &self.configuration.absolute_root,
+            RecursiveMode::Recursive,
+        )?;
+        Ok(watcher)
+    }
+
+    fn handle(&mut self, request: Request) -> anyhow::Result<Value> {
+        match request.method.as_str() {
+            "check" => {
+                let result = self.check(request.params.files)?;
+                Ok(serde_json::from_str(&result.to_json()?)?)
+            }
+            "validate" => {
+                self.apply_pending_changes()?;
+                Ok(serde_json::to_value(checker::validate_structured(
+                    &self.configuration,
+                ))?)
+            }
+            "reload" => {
+                self.reload()?;
+                Ok(Value::Null)
+            }
+            "shutdown" => Ok(Value::Null),
+            method => Err(anyhow!("Unknown method: {}", method)),
+        }
+    }
+}
+
+pub(crate) fn serve(
+    configuration: Configuration,
+    socket: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let state = ServerState::new(configuration)?;
+    // The watcher stops when dropped, so it has to live as long as the server
+    let _watcher = state.watch()?;
+    let state = Arc::new(Mutex::new(state));
+
+    match socket {
+        Some(socket_path) => serve_socket(state, &socket_path),
+        None => {
+            let stdin = std::io::stdin();
+            serve_connection(&state, stdin.lock(), std::io::stdout())?;
+            Ok(())
+        }
+    }
+}
+
+fn serve_socket(
+    state: Arc<Mutex<ServerState>>,
+    socket_path: &Path,
+) -> anyhow::Result<()> {
+    if socket_path.exists() {
+        std::fs::remove_file(socket_path).context(format!(
+            "Failed to remove stale socket {}",
+            socket_path.display()
+        ))?;
+    }
+    let listener = UnixListener::bind(socket_path)
+        .context(format!("Failed to bind {}", socket_path.display()))?;
+    eprintln!("Listening on {}", socket_path.display());
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let state = Arc::clone(&state);
+        let socket_path = socket_path.to_path_buf();
+        std::thread::spawn(move || {
+            let reader = match stream.try_clone() {
+                Ok(reader) => BufReader::new(reader),
+                Err(err) => {
+                    debug!("Failed to read from connection: {}", err);
+                    return;
+                }
+            };
+            match serve_connection(&state, reader, stream) {
+                Ok(true) => {
+                    let _ = std::fs::remove_file(&socket_path);
+                    std::process::exit(0);
+                }
+                Ok(false) => {}
+                Err(err) => debug!("Connection closed: {}", err),
+            }
+        });
+    }
+    Ok(())
+}
+
+// Returns whether a shutdown was requested
+fn serve_connection(
+    state: &Mutex<ServerState>,
+    reader: impl BufRead,
+    mut writer: impl Write,
+) -> anyhow::Result<bool> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, shutdown) = match serde_json::from_str::<Request>(&line)
+        {
+            Ok(request) => {
+                let id = request.id.clone();
+                let shutdown = request.method == "shutdown";
+                let result = state.lock().unwrap().handle(request);
+                (response_for(id, result), shutdown)
+            }
+            Err(err) => (
+                response_for(
+                    Value::Null,
+                    Err(anyhow!("Invalid request: {}", err)),
+                ),
+                false,
+            ),
+        };
+
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+        writer.flush()?;
+        if shutdown {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn response_for(id: Value, result: anyhow::Result<Value>) -> Response {
+    match result {
+        Ok(result) => Response {
+            id,
+            result: Some(result),
+            error: None,
+        },
+        Err(err) => Response {
+            id,
+            result: None,
+            error: Some(format!("{:#}", err)),
+        },
+    }
+}
+
+fn build_constant_resolver(
+    configuration: &Configuration,
+    processed_files: &HashMap<PathBuf, ProcessedFile>,
+) -> Box<dyn ConstantResolver + Send + Sync> {
+    if configuration.experimental_parser {
+        let processed_files: Vec<ProcessedFile> =
+            processed_files.values().cloned().collect();
+        get_experimental_constant_resolver(
+            &configuration.absolute_root,
+            &processed_files,
+            &configuration.ignored_definitions,
+        )
+    } else {
+        get_zeitwerk_constant_resolver(
+            &configuration.pack_set,
+            &configuration.constant_resolver_configuration(),
+        )
+    }
+}
+
+// CLI flags are applied on top of the loaded configuration, so they need to
+// survive a reload
+fn carry_over_overrides(previous: &Configuration, next: &mut Configuration) {
+    next.print_files = previous.print_files;
+    next.experimental_parser = previous.experimental_parser;
+    next.cache_enabled = previous.cache_enabled;
+    next.disable_enforce_dependencies = previous.disable_enforce_dependencies;
+    next.disable_enforce_folder_privacy =
+        previous.disable_enforce_folder_privacy;
+    next.disable_enforce_layers = previous.disable_enforce_layers;
+    next.disable_enforce_privacy = previous.disable_enforce_privacy;
+    next.disable_enforce_visibility = previous.disable_enforce_visibility;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_file(root: &Path, relative_path: &str, contents: &str) {
+        let path = root.join(relative_path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_only_included_files_require_a_reload() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().canonicalize()?;
+        write_file(
+            &root,
+            "packwerk.yml",
+            "exclude:\n  - \"**/generated/**\"\n",
+        );
+        write_file(&root, "package.yml", "");
+        write_file(&root, "packs/foo/package.yml", "");
+        write_file(&root, "packs/foo/app/foo.rb", "module Foo\nend\n");
+        write_file(&root, "packs/foo/generated/old.rb", "");
+
+        let mut configuration = configuration::get(&root, &0)?;
+        configuration.cache_enabled = false;
+        let state = ServerState::new(configuration)?;
+
+        write_file(&root, "packs/foo/app/new.rb", "");
+        write_file(&root, "packs/foo/generated/new.rb", "");
+        write_file(&root, "node_modules/foo/new.rb", "");
+
+        assert!(state.requires_reload(&root.join("packs/foo/app/new.rb")));
+        assert!(state.requires_reload(&root.join("packs/foo/package.yml")));
+        assert!(!state.requires_reload(&root.join("packs/foo/app/foo.rb")));
+        assert!(
+            !state.requires_reload(&root.join("packs/foo/generated/old.rb"))
+        );
+        assert!(
+            !state.requires_reload(&root.join("packs/foo/generated/new.rb"))
+        );
+        assert!(!state.requires_reload(&root.join("node_modules/foo/new.rb")));
+        Ok(())
+    }
+}
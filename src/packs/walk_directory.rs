@@ -1,7 +1,8 @@
+use globset::GlobSet;
 use jwalk::WalkDirGeneric;
 use std::{
     collections::{HashMap, HashSet},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use tracing::debug;
@@ -16,6 +17,65 @@ pub struct WalkDirectoryResult {
     pub owning_package_yml_for_file: HashMap<PathBuf, PathBuf>,
 }
 
+// Directories the walk never descends into, on top of the `exclude` globs
+pub(crate) const DEFAULT_EXCLUDED_DIRS: [&str; 8] = [
+    "node_modules/**/*",
+    "vendor/**/*",
+    "tmp/**/*",
+    ".git/**/*",
+    "public/**/*",
+    "bin/**/*",
+    "log/**/*",
+    "sorbet/**/*",
+];
+
+// Which files the walk includes, according to the `include` and `exclude`
+// globs. Kept on the configuration, so that `pks server` can tell whether a
+// file it is told about would have been found by a new walk.
+#[derive(Debug, Clone)]
+pub(crate) struct FileMatcher {
+    includes: GlobSet,
+    excludes: GlobSet,
+    excluded_dirs: GlobSet,
+}
+
+impl FileMatcher {
+    pub(crate) fn new(raw: &RawConfiguration) -> Self {
+        let mut excluded_dirs: Vec<String> = DEFAULT_EXCLUDED_DIRS
+            .iter()
+            .map(|dir| dir.to_string())
+            .collect();
+        excluded_dirs.extend(raw.exclude.to_owned());
+
+        Self {
+            includes: build_glob_set(&raw.include),
+            excludes: build_glob_set(&raw.exclude),
+            excluded_dirs: build_glob_set(&excluded_dirs),
+        }
+    }
+
+    // Whether the walk skips this directory, and everything inside it
+    pub(crate) fn is_excluded_dir(&self, relative_path: &Path) -> bool {
+        self.excluded_dirs.is_match(relative_path)
+    }
+
+    // Whether a file the walk visits is included
+    fn is_match(&self, relative_path: &Path) -> bool {
+        self.includes.is_match(relative_path)
+            && !self.excludes.is_match(relative_path)
+    }
+
+    // Whether the walk would include this file, including checking that it
+    // isn't inside a directory the walk skips
+    pub(crate) fn is_included(&self, relative_path: &Path) -> bool {
+        self.is_match(relative_path)
+            && !relative_path
+                .ancestors()
+                .skip(1)
+                .any(|dir| self.is_excluded_dir(dir))
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct ProcessReadDirState {
     current_package_yml: PathBuf,
@@ -45,31 +105,12 @@ pub(crate) fn walk_directory(
     let mut owning_package_yml_for_file: HashMap<PathBuf, PathBuf> =
         HashMap::new();
 
-    // Create this vector outside of the closure to avoid reallocating it
-    let default_excluded_dirs = [
-        "node_modules/**/*",
-        "vendor/**/*",
-        "tmp/**/*",
-        ".git/**/*",
-        "public/**/*",
-        "bin/**/*",
-        "log/**/*",
-        "sorbet/**/*",
-    ];
-    let mut all_excluded_dirs: Vec<String> = Vec::new();
-    all_excluded_dirs
-        .extend(default_excluded_dirs.iter().map(|s| s.to_string()));
-
-    let excluded_globs = &raw.exclude;
-    all_excluded_dirs.extend(excluded_globs.to_owned());
-
-    let all_excluded_dirs_set = build_glob_set(&all_excluded_dirs);
-    let excluded_dirs_ref = Arc::new(all_excluded_dirs_set);
+    // Create this outside of the closure to avoid rebuilding it
+    let file_matcher = Arc::new(FileMatcher::new(raw));
+    let file_matcher_ref = file_matcher.clone();
 
     let absolute_root_ref = Arc::new(absolute_root.clone());
 
-    let includes_set = build_glob_set(&raw.include);
-    let excludes_set = build_glob_set(&raw.exclude);
     let package_paths_set = build_glob_set(&raw.package_paths);
 
     // TODO: Pull directory walker into separate module. Allow it to be called with implementations of a trait
@@ -95,7 +136,7 @@ pub(crate) fn walk_directory(
                 // We need to let the compiler know that we are using a reference and not the value itself.
                 // We need to then clone the Arc to get a new reference, which is a new pointer to the value/data
                 // (with an increase to the reference count).
-                let cloned_excluded_dirs = excluded_dirs_ref.clone();
+                let cloned_file_matcher = file_matcher_ref.clone();
                 let cloned_absolute_root = absolute_root_ref.clone();
                 let package_yml = absolute_dirname.join("package.yml");
 
@@ -116,8 +157,7 @@ pub(crate) fn walk_directory(
                         let relative_path = child_absolute_dirname
                             .strip_prefix(cloned_absolute_root.as_ref())
                             .unwrap();
-                        if cloned_excluded_dirs.as_ref().is_match(relative_path)
-                        {
+                        if cloned_file_matcher.is_excluded_dir(relative_path) {
                             child_dir_entry.read_children_path = None;
                         }
                     }
@@ -174,20 +214,10 @@ pub(crate) fn walk_directory(
         }
 
         // This could be one line, but I'm keeping it separate for debugging purposes
-        if includes_set.is_match(&relative_path) {
-            if !excludes_set.is_match(&relative_path) {
-                included_files.insert(absolute_path.clone());
-                owning_package_yml_for_file
-                    .insert(absolute_path, current_package_yml.clone());
-            } else {
-                // println!("file excluded: {}", relative_path.display())
-            }
-        } else {
-            // println!(
-            //     "file not included: {:?}, {:?}",
-            //     relative_path.display(),
-            //     &raw.include
-            // )
+        if file_matcher.is_match(&relative_path) {
+            included_files.insert(absolute_path.clone());
+            owning_package_yml_for_file
+                .insert(absolute_path, current_package_yml.clone());
         }
     }
 
@@ -202,10 +232,11 @@ pub(crate) fn walk_directory(
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     use crate::packs::{
-        raw_configuration::RawConfiguration, walk_directory::walk_directory,
+        raw_configuration::RawConfiguration,
+        walk_directory::{walk_directory, FileMatcher},
     };
 
     #[test]
@@ -235,4 +266,22 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_file_matcher() {
+        let raw_config = RawConfiguration {
+            include: vec!["**/*.rb".to_string()],
+            exclude: vec!["**/generated/**".to_string()],
+            ..RawConfiguration::default()
+        };
+        let file_matcher = FileMatcher::new(&raw_config);
+
+        assert!(file_matcher.is_included(Path::new("packs/foo/foo.rb")));
+        assert!(!file_matcher.is_included(Path::new("packs/foo/foo.yml")));
+        assert!(
+            !file_matcher.is_included(Path::new("packs/foo/generated/foo.rb"))
+        );
+        assert!(!file_matcher.is_included(Path::new("node_modules/foo/foo.rb")));
+        assert!(!file_matcher.is_included(Path::new(".git/hooks/foo.rb")));
+    }
 }
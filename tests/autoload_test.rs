@@ -0,0 +1,52 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use serde_json::Value;
+use std::{error::Error, path::Path, process::Command};
+
+mod common;
+
+// Constants that `packs/foo` depends on without declaring it
+fn dependency_violations(root: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg("check")
+        .arg("--json")
+        .output()?;
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let mut constants: Vec<String> = json["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|violation| {
+            violation["constant_name"].as_str().unwrap().to_owned()
+        })
+        .collect();
+    constants.sort();
+    Ok(constants)
+}
+
+// In app_with_autoload_collapse_and_ignore, `packs/foo` refers to `Widget`,
+// defined in `packs/bar/app/models/legacy`, and `Thing`, defined in
+// `packs/bar/app/models/thing.rb`
+#[test]
+fn test_collapse_and_ignore() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        dependency_violations(Path::new(
+            "tests/fixtures/app_with_autoload_collapse_and_ignore"
+        ))?,
+        vec!["::Widget"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_without_collapse_and_ignore() -> Result<(), Box<dyn Error>> {
+    let app = common::copy_fixture("app_with_autoload_collapse_and_ignore");
+    common::write_file(app.path(), "packwerk.yml", "");
+    common::write_file(app.path(), "packs/bar/package.yml", "");
+    assert_eq!(dependency_violations(app.path())?, vec!["::Thing"]);
+    Ok(())
+}
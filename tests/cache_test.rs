@@ -0,0 +1,111 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+fn app(packwerk_yml: &str) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_cached_files");
+    common::write_file(tmp_dir.path(), "packwerk.yml", packwerk_yml);
+    tmp_dir
+}
+
+fn cache(root: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .arg("cache")
+        .args(args)
+        .assert()
+}
+
+#[test]
+fn test_cache_warm_and_stats() -> Result<(), Box<dyn Error>> {
+    for packwerk_yml in ["", "cache_backend: packed\n"] {
+        let app = app(packwerk_yml);
+        cache(app.path(), &["stats"])
+            .success()
+            .stdout(predicate::str::contains("zeitwerk: 0 entries, 0 B"))
+            .stdout(predicate::str::contains("Last run: no files processed"));
+
+        cache(app.path(), &["warm"])
+            .success()
+            .stdout(predicate::str::contains("Cached 2 files"));
+        cache(app.path(), &["stats"])
+            .success()
+            .stdout(predicate::str::contains("zeitwerk: 2 entries"))
+            .stdout(predicate::str::contains("experimental: 0 entries"))
+            .stdout(predicate::str::contains(
+                "Last run: 0 hits, 2 misses (0.0% hit rate)",
+            ));
+
+        cache(app.path(), &["warm"]).success();
+        cache(app.path(), &["stats"]).success().stdout(
+            predicate::str::contains(
+                "Last run: 2 hits, 0 misses (100.0% hit rate)",
+            ),
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stats_record_full_runs_only() -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    let check = |args: &[&str]| {
+        Command::new(cargo_bin!("packs"))
+            .arg("--project-root")
+            .arg(app.path())
+            .arg("check")
+            .args(args)
+            .assert()
+            .success();
+    };
+
+    check(&["packs/foo/app/services/foo.rb"]);
+    cache(app.path(), &["stats"])
+        .success()
+        .stdout(predicate::str::contains("Last run: no files processed"));
+
+    check(&[]);
+    cache(app.path(), &["stats"])
+        .success()
+        .stdout(predicate::str::contains(
+            "Last run: 1 hits, 1 misses (50.0% hit rate)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_cache_verify() -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    cache(app.path(), &["warm"]).success();
+    cache(app.path(), &["verify"])
+        .success()
+        .stdout(predicate::str::contains(
+            "Verified 2 cache entries (0 sampled files were not cached)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_cache_prune_removes_excluded_files() -> Result<(), Box<dyn Error>> {
+    for packwerk_yml in ["", "cache_backend: packed\n"] {
+        let app = app(packwerk_yml);
+        cache(app.path(), &["warm"]).success();
+        fs::remove_file(app.path().join("packs/bar/app/services/bar.rb"))?;
+
+        cache(app.path(), &["prune"]).success().stdout(
+            predicate::str::contains(
+                "Removed 1 cache entries of files no longer included",
+            ),
+        );
+        cache(app.path(), &["stats"])
+            .success()
+            .stdout(predicate::str::contains("zeitwerk: 1 entries"));
+    }
+    Ok(())
+}
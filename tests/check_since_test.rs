@@ -0,0 +1,197 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["-c", "user.name=pks", "-c", "user.email=pks@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// packs/baz has a dependency violation on `::Foo` that is only reported
+// when baz.rb is in scope
+fn committed_app() -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_unchecked_violation");
+    let root = tmp_dir.path();
+    git(root, &["init", "--quiet"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "--quiet", "-m", "initial"]);
+    tmp_dir
+}
+
+fn check_since_head(root: &Path) -> assert_cmd::assert::Assert {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg("check")
+        .arg("--since")
+        .arg("HEAD")
+        .assert()
+}
+
+#[test]
+fn test_check_since_without_changes() -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+
+    check_since_head(app.path())
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_ignores_unaffected_files() -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/bar/app/services/bar.rb",
+        "# changed\nmodule Bar\nend\n",
+    );
+
+    check_since_head(app.path())
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_includes_files_referencing_changed_definitions(
+) -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/foo/app/services/foo.rb",
+        "# changed\nmodule Foo\n  Bar\nend\n",
+    );
+
+    check_since_head(app.path())
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "`packs/baz/package.yml` does not specify a dependency on `packs/foo`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_includes_packs_with_changed_package_yml(
+) -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/foo/package.yml",
+        "enforce_dependencies: true\n",
+    );
+
+    check_since_head(app.path())
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "`packs/foo/package.yml` does not specify a dependency on `packs/bar`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_includes_files_referencing_deleted_constants(
+) -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/baz/package_todo.yml",
+        "---\npacks/foo:\n  \"::Foo\":\n    violations:\n    - dependency\n    files:\n    - packs/baz/app/services/baz.rb\n",
+    );
+    git(app.path(), &["add", "."]);
+    git(app.path(), &["commit", "--quiet", "-m", "todo"]);
+    fs::remove_file(app.path().join("packs/foo/app/services/foo.rb"))?;
+
+    check_since_head(app.path())
+        .failure()
+        .stdout(predicate::str::contains(
+            "There were stale violations found",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_includes_files_referencing_moved_constants(
+) -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/baz/package.yml",
+        "enforce_dependencies: true\ndependencies:\n- packs/foo\n",
+    );
+    git(
+        app.path(),
+        &["commit", "--quiet", "-am", "baz depends on foo"],
+    );
+    common::write_file(app.path(), "packs/qux/package.yml", "");
+    fs::create_dir_all(app.path().join("packs/qux/app/services"))?;
+    git(
+        app.path(),
+        &[
+            "mv",
+            "packs/foo/app/services/foo.rb",
+            "packs/qux/app/services/foo.rb",
+        ],
+    );
+
+    check_since_head(app.path())
+        .failure()
+        .stdout(predicate::str::contains(
+        "`packs/baz/package.yml` does not specify a dependency on `packs/qux`",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_with_special_characters_in_paths(
+) -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+    common::write_file(
+        app.path(),
+        "packs/baz/app/services/über baz.rb",
+        "module Baz\n  Foo\nend\n",
+    );
+
+    check_since_head(app.path())
+        .failure()
+        .stdout(predicate::str::contains("über baz.rb"));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_since_unknown_revision() -> Result<(), Box<dyn Error>> {
+    let app = committed_app();
+
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("check")
+        .arg("--since")
+        .arg("does-not-exist")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`git diff"));
+
+    Ok(())
+}
@@ -402,6 +402,107 @@ fn test_check_json_output() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_check_sarif_output() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let sarif: Value = serde_json::from_slice(&output)?;
+
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "pks");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    let dep = &results[0];
+    assert_eq!(dep["ruleId"], "dependency");
+    assert_eq!(dep["level"], "warning");
+    let location = &dep["locations"][0]["physicalLocation"];
+    assert_eq!(
+        location["artifactLocation"]["uri"],
+        "packs/foo/app/services/foo.rb"
+    );
+    assert_eq!(location["region"]["startLine"], 3);
+    assert_eq!(location["region"]["startColumn"], 5);
+    assert!(!dep["message"]["text"].as_str().unwrap().contains("\x1b"));
+    assert_eq!(results[1]["ruleId"], "privacy");
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_junit_output() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("--format")
+        .arg("junit")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "<testsuites name=\"pks\" tests=\"2\" failures=\"2\">",
+        ))
+        .stdout(predicate::str::contains(
+            "<testsuite name=\"packs/foo\" tests=\"2\" failures=\"2\">",
+        ))
+        .stdout(predicate::str::contains("<failure type=\"dependency\""));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_checkstyle_output() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("--format")
+        .arg("checkstyle")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "<file name=\"packs/foo/app/services/foo.rb\">",
+        ))
+        .stdout(predicate::str::contains(
+            "<error line=\"3\" column=\"5\" severity=\"warning\"",
+        ))
+        .stdout(predicate::str::contains("source=\"pks.privacy\"/>"));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_check_github_output() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("check")
+        .arg("--format")
+        .arg("github")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "::error file=packs/foo/app/services/foo.rb,line=3,col=5::packs/foo/app/services/foo.rb:3:5%0ADependency violation",
+        ));
+
+    common::teardown();
+    Ok(())
+}
+
 #[test]
 fn test_check_json_no_violations() -> Result<(), Box<dyn Error>> {
     let output = Command::new(cargo_bin!("packs"))
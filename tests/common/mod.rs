@@ -1,4 +1,8 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
 
 //
 // For more information about this file's naming convention, see
@@ -20,6 +24,36 @@ pub fn teardown() {
         });
 }
 
+// Writes `contents` to `relative_path` under `root`, creating its directories
+#[allow(dead_code)]
+pub fn write_file(root: &Path, relative_path: &str, contents: &str) {
+    let path = root.join(relative_path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+// Copies tests/fixtures/`name` into a temporary directory, for tests that
+// change the app
+#[allow(dead_code)]
+pub fn copy_fixture(name: &str) -> TempDir {
+    let tmp_dir = TempDir::new().unwrap();
+    copy_dir(&PathBuf::from("tests/fixtures").join(name), tmp_dir.path());
+    tmp_dir
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let destination = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &destination);
+        } else {
+            fs::copy(entry.path(), destination).unwrap();
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub fn delete_foobar() {
     let directory = PathBuf::from("tests/fixtures/simple_app/packs/foobar");
@@ -0,0 +1,85 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+// A checkout whose cache lives in `cache_directory`, outside of it
+fn app(cache_directory: &Path) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_content_addressed_cache");
+    common::write_file(
+        tmp_dir.path(),
+        "packwerk.yml",
+        &format!(
+            "cache_backend: content_addressed\ncache_directory: {}\n",
+            cache_directory.display()
+        ),
+    );
+    tmp_dir
+}
+
+fn pks(root: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .args(args)
+        .assert()
+}
+
+fn cache_entry_count(cache_directory: &Path) -> usize {
+    fs::read_dir(cache_directory.join("content"))
+        .unwrap()
+        .flat_map(|shard| fs::read_dir(shard.unwrap().path()).unwrap())
+        .count()
+}
+
+#[test]
+fn test_checkouts_share_the_cache() -> Result<(), Box<dyn Error>> {
+    let cache_directory = TempDir::new()?;
+    let first_checkout = app(cache_directory.path());
+    let second_checkout = app(cache_directory.path());
+    // Moved files have the same contents and hit the cache
+    fs::rename(
+        second_checkout.path().join("packs/foo/app/services/foo.rb"),
+        second_checkout
+            .path()
+            .join("packs/foo/app/services/moved.rb"),
+    )?;
+
+    pks(first_checkout.path(), &["check"])
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"));
+    assert_eq!(cache_entry_count(cache_directory.path()), 2);
+
+    pks(second_checkout.path(), &["check"])
+        .failure()
+        .stdout(predicate::str::contains("packs/foo/app/services/moved.rb"))
+        .stdout(predicate::str::contains("`::Bar` belongs to `packs/bar`"));
+    assert_eq!(cache_entry_count(cache_directory.path()), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_prune() -> Result<(), Box<dyn Error>> {
+    let cache_directory = TempDir::new()?;
+    let checkout = app(cache_directory.path());
+    pks(checkout.path(), &["check"]).failure();
+
+    pks(checkout.path(), &["cache", "prune", "--max-age", "1d"])
+        .success()
+        .stdout(predicate::str::contains("Removed 0 cache entries"));
+    pks(checkout.path(), &["cache", "prune", "--max-size", "0"])
+        .success()
+        .stdout(predicate::str::contains("Removed 2 cache entries"));
+    assert_eq!(cache_entry_count(cache_directory.path()), 0);
+
+    pks(checkout.path(), &["cache", "prune"])
+        .failure()
+        .stderr(predicate::str::contains("--max-size"));
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+// In app_with_external_checker, `packs/foo` refers to `Bar`, defined in
+// `packs/bar`, and bin/no_bar keeps its input in `received.jsonl` and flags
+// references to `::Bar` from `packs/foo`
+fn app(foo_package_yml: &str) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_external_checker");
+    common::write_file(
+        tmp_dir.path(),
+        "packs/foo/package.yml",
+        foo_package_yml,
+    );
+    tmp_dir
+}
+
+fn pks(root: &Path, command: &str) -> Command {
+    let mut pks = Command::new(cargo_bin!("packs"));
+    pks.arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg(command);
+    pks
+}
+
+#[test]
+fn test_external_checker_violation() -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "foo.rb\x1b[0m:3:4\nBar is deprecated",
+        ));
+
+    let received = fs::read_to_string(app.path().join("received.jsonl"))?;
+    assert!(received.contains(r#""type":"pack""#));
+    assert!(received.contains(r#""name":"packs/foo""#));
+    assert!(received.contains(
+        r#""relative_referencing_file":"packs/foo/app/services/foo.rb""#
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_external_checker_todo() -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    pks(app.path(), "update").assert().success();
+    let package_todo =
+        fs::read_to_string(app.path().join("packs/foo/package_todo.yml"))?;
+    assert!(package_todo.contains("- no_bar"));
+    pks(app.path(), "check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    common::write_file(
+        app.path(),
+        "packs/foo/app/services/foo.rb",
+        "class Foo\nend\n",
+    );
+    pks(app.path(), "check").assert().failure().stdout(
+        predicate::str::contains("There were stale violations found"),
+    );
+    Ok(())
+}
+
+#[test]
+fn test_external_checker_strict_mode() -> Result<(), Box<dyn Error>> {
+    let app = app("enforce_no_bar: strict\n");
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "packs/foo cannot have no_bar violations on packs/bar because strict mode is enabled",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_external_checker_strict_mode_of_the_defining_pack(
+) -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    common::write_file(
+        app.path(),
+        "packs/bar/package.yml",
+        "enforce_no_bar: strict\n",
+    );
+    // Violations are outgoing by default, so the referencing pack's setting
+    // applies
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains("strict mode is enabled").not());
+
+    let no_bar = fs::read_to_string(app.path().join("bin/no_bar"))?;
+    common::write_file(
+        app.path(),
+        "bin/no_bar",
+        &no_bar.replace(r#""message""#, r#""direction":"incoming","message""#),
+    );
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "packs/foo cannot have no_bar violations on packs/bar because strict mode is enabled",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_failing_external_checker() -> Result<(), Box<dyn Error>> {
+    let app = app("");
+    common::write_file(
+        app.path(),
+        "bin/no_bar",
+        "#!/bin/sh\necho 'no Bar allowed' >&2\nexit 3\n",
+    );
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("External checker `no_bar` failed"))
+        .stderr(predicate::str::contains("no Bar allowed"));
+    Ok(())
+}
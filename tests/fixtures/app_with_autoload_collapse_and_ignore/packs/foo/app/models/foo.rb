@@ -0,0 +1,6 @@
+class Foo
+  def call
+    Widget
+    Thing
+  end
+end
@@ -0,0 +1,7 @@
+class University
+  has_many :campi
+
+  def client
+    APIClient
+  end
+end
@@ -0,0 +1,2 @@
+class Jobs
+end
@@ -0,0 +1,6 @@
+class Worker
+  def call
+    Util
+    Web
+  end
+end
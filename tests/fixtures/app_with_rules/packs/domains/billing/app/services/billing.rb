@@ -0,0 +1,5 @@
+class Billing
+  def call
+    Admin
+  end
+end
@@ -0,0 +1,4 @@
+class Foo
+  # packs:disable-next-line dependency -- reason: legacy billing shim
+  Bar
+end
@@ -0,0 +1,3 @@
+module Baz
+  Foo
+end
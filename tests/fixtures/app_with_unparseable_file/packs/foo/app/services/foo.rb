@@ -0,0 +1,5 @@
+module Foo
+  def self.call
+    [1, 2
+  end
+end
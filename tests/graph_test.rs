@@ -0,0 +1,94 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::error::Error;
+
+// In app_with_pack_graph, packs/foo declares a dependency on
+// packs/platform/auth and has a recorded violation on packs/platform/billing,
+// which depends on packs/utils
+fn graph(args: &[&str]) -> Vec<u8> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_pack_graph")
+        .arg("--no-cache")
+        .arg("graph")
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone()
+}
+
+#[test]
+fn test_graph_json() -> Result<(), Box<dyn Error>> {
+    let graph: Value = serde_json::from_slice(&graph(&["--format", "json"]))?;
+
+    assert_eq!(
+        graph["nodes"][1],
+        json!({"name": "packs/foo", "layer": "product", "owner": "Foo Team"})
+    );
+    assert_eq!(
+        graph["edges"],
+        json!([
+            {"from": "packs/foo", "to": "packs/platform/auth", "kind": "declared"},
+            {"from": "packs/foo", "to": "packs/platform/billing", "kind": "implicit", "violations": {"dependency": 1, "privacy": 1}},
+            {"from": "packs/platform/billing", "to": "packs/utils", "kind": "declared"}
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_graph_collapse_and_focus() -> Result<(), Box<dyn Error>> {
+    let graph: Value = serde_json::from_slice(&graph(&[
+        "--format",
+        "json",
+        "--collapse",
+        "packs/platform",
+        "--focus",
+        "packs/foo",
+    ]))?;
+
+    assert_eq!(
+        graph["nodes"],
+        json!([
+            {"name": "packs/foo", "layer": "product", "owner": "Foo Team"},
+            {"name": "packs/platform", "layer": "platform", "packs": ["packs/platform/auth", "packs/platform/billing"]}
+        ])
+    );
+    assert_eq!(graph["edges"].as_array().unwrap().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_graph_dot_and_mermaid() -> Result<(), Box<dyn Error>> {
+    let dot = String::from_utf8(graph(&[]))?;
+    assert!(dot.starts_with("digraph packs {"));
+    assert!(dot.contains("\"packs/foo\" -> \"packs/platform/auth\";"));
+    assert!(dot.contains("\"packs/foo\" -> \"packs/platform/billing\" [style=dashed, color=red, label=\"dependency: 1, privacy: 1\"];"));
+
+    let mermaid = String::from_utf8(graph(&["--format", "mermaid"]))?;
+    assert!(mermaid.starts_with("flowchart LR"));
+    assert!(mermaid
+        .contains("n1[\"packs/foo<br/>layer: product<br/>owner: Foo Team\"]"));
+    assert!(mermaid.contains("n1 -.->|\"dependency: 1, privacy: 1\"| n3"));
+    Ok(())
+}
+
+#[test]
+fn test_graph_focus_on_unknown_pack() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_pack_graph")
+        .arg("--no-cache")
+        .arg("graph")
+        .arg("--focus")
+        .arg("packs/unknown")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No pack found 'packs/unknown'"));
+    Ok(())
+}
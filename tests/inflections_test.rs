@@ -0,0 +1,50 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use serde_json::Value;
+use std::{error::Error, path::Path, process::Command};
+
+mod common;
+
+// Constants that `packs/foo` depends on without declaring it
+fn dependency_violations(root: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg("check")
+        .arg("--json")
+        .output()?;
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let mut constants: Vec<String> = json["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|violation| {
+            violation["constant_name"].as_str().unwrap().to_owned()
+        })
+        .collect();
+    constants.sort();
+    Ok(constants)
+}
+
+// In app_with_custom_inflections, `packs/foo` refers to `packs/bar` through
+// an association and a constant whose names depend on custom inflections
+#[test]
+fn test_custom_inflections() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        dependency_violations(Path::new(
+            "tests/fixtures/app_with_custom_inflections"
+        ))?,
+        vec!["::APIClient", "::Campus"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_without_custom_inflections() -> Result<(), Box<dyn Error>> {
+    let app = common::copy_fixture("app_with_custom_inflections");
+    common::write_file(app.path(), "config/initializers/inflections.rb", "");
+    assert_eq!(dependency_violations(app.path())?, Vec::<String>::new());
+    Ok(())
+}
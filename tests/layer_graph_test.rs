@@ -0,0 +1,51 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, path::Path, process::Command};
+
+mod common;
+
+// In app_with_layer_graph, `packs/jobs` refers to `Web` from `packs/web` and
+// `Util` from `packs/util`
+fn pks(root: &Path, command: &str) -> Command {
+    let mut pks = Command::new(cargo_bin!("packs"));
+    pks.arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg(command);
+    pks
+}
+
+#[test]
+fn test_check_with_layer_graph() -> Result<(), Box<dyn Error>> {
+    pks(Path::new("tests/fixtures/app_with_layer_graph"), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "worker.rb\x1b[0m:4:4\nLayer violation: `::Web` belongs to `packs/web` (whose layer is `web`) cannot be accessed from `packs/jobs` (whose layer is `jobs`). Layer `jobs` may only depend on `domain`, `utility`",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_validate_layer_graph_with_cycle() -> Result<(), Box<dyn Error>> {
+    pks(Path::new("tests/fixtures/app_with_layer_graph"), "validate")
+        .assert()
+        .success();
+
+    let cyclic = common::copy_fixture("app_with_layer_graph");
+    common::write_file(
+        cyclic.path(),
+        "packwerk.yml",
+        "layers:\n  web: [domain]\n  jobs: [domain]\n  domain: [utility]\n  utility: [jobs]\n",
+    );
+    pks(cyclic.path(), "validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Layers in `packwerk.yml` must not depend on each other in a cycle: `domain` -> `utility` -> `jobs` -> `domain`",
+        ));
+    Ok(())
+}
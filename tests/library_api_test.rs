@@ -0,0 +1,90 @@
+use packs::packs::{
+    check_all, update_package_todos, validate_structured, UpdateOptions,
+};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use std::path::{Path, PathBuf};
+
+mod common;
+
+#[test]
+#[serial]
+fn test_check_all_returns_typed_violations() -> anyhow::Result<()> {
+    let configuration = packs::packs::configuration(
+        PathBuf::from("tests/fixtures/simple_app"),
+        &0,
+    )?;
+
+    let result = check_all(&configuration, vec![])?;
+
+    assert!(result.has_violations());
+    let mut violation_types: Vec<&str> = result
+        .reportable_violations()
+        .iter()
+        .map(|v| v.identifier.violation_type.as_str())
+        .collect();
+    violation_types.sort();
+    assert_eq!(violation_types, vec!["dependency", "privacy"]);
+
+    let violation = result.reportable_violations().iter().next().unwrap();
+    assert_eq!(violation.identifier.file, "packs/foo/app/services/foo.rb");
+    assert_eq!(violation.identifier.constant_name, "::Bar");
+    assert_eq!(violation.source_location.line, 3);
+    assert!(result.stale_violations().is_empty());
+    assert!(result.strict_mode_violations().is_empty());
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_query_pack_set_and_resolve_constants() -> anyhow::Result<()> {
+    let configuration = packs::packs::configuration(
+        PathBuf::from("tests/fixtures/simple_app"),
+        &0,
+    )?;
+
+    let pack = configuration.pack_set.for_pack("packs/foo")?;
+    assert!(pack.dependencies.contains("packs/baz"));
+
+    let constant_resolver = packs::packs::constant_resolver(&configuration)?;
+    let definitions = constant_resolver.resolve("Bar", &["Foo"]).unwrap();
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(definitions[0].fully_qualified_name, "::Foo::Bar");
+
+    assert!(validate_structured(&configuration).is_empty());
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_update_package_todos_returns_stats() -> anyhow::Result<()> {
+    let configuration = packs::packs::configuration(
+        PathBuf::from("tests/fixtures/simple_app"),
+        &0,
+    )?;
+    let options = UpdateOptions {
+        files: vec![],
+        expand_to_pack: false,
+        constant_name: None,
+        violation_type: None,
+        defining_pack_name: None,
+    };
+
+    let result = update_package_todos(&configuration, &options)?;
+
+    let package_todo_yml_filepath =
+        Path::new("tests/fixtures/simple_app/packs/foo/package_todo.yml");
+    let written = package_todo_yml_filepath.exists();
+    std::fs::remove_file(package_todo_yml_filepath)?;
+    assert!(written);
+    assert_eq!(result.stats.files_added, 1);
+    assert_eq!(result.stats.violations_added, 1);
+    assert!(result.strict_mode_violations.is_empty());
+
+    common::teardown();
+    Ok(())
+}
@@ -0,0 +1,256 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use serde_json::{json, Value};
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{ChildStdin, ChildStdout, Command, Stdio},
+};
+
+mod common;
+
+fn send(stdin: &mut ChildStdin, message: Value) {
+    let body = message.to_string();
+    write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    stdin.flush().unwrap();
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> Value {
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        stdout.read_line(&mut header).unwrap();
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length: ") {
+            content_length = length.parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    stdout.read_exact(&mut body).unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+// Skips notifications (e.g. diagnostics) until the response with `id`
+fn receive_response(stdout: &mut BufReader<ChildStdout>, id: u64) -> Value {
+    loop {
+        let message = receive(stdout);
+        if message["id"] == id {
+            return message;
+        }
+    }
+}
+
+#[test]
+fn test_lsp_diagnostics_definition_and_code_actions(
+) -> Result<(), Box<dyn Error>> {
+    let root = PathBuf::from("tests/fixtures/simple_app").canonicalize()?;
+    let foo_uri = format!(
+        "file://{}",
+        root.join("packs/foo/app/services/foo.rb").display()
+    );
+    let bar_uri = format!(
+        "file://{}",
+        root.join("packs/bar/app/services/bar.rb").display()
+    );
+
+    let mut lsp = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(&root)
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = lsp.stdin.take().unwrap();
+    let mut stdout = BufReader::new(lsp.stdout.take().unwrap());
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+    );
+    let initialize = receive_response(&mut stdout, 1);
+    assert_eq!(
+        initialize["result"]["capabilities"]["definitionProvider"],
+        true
+    );
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": {"uri": foo_uri, "languageId": "ruby", "version": 1, "text": ""}
+        }}),
+    );
+    let diagnostics = receive(&mut stdout);
+    assert_eq!(diagnostics["method"], "textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["params"]["uri"], foo_uri);
+    let mut diagnostics = diagnostics["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .clone();
+    diagnostics.sort_by_key(|d| d["code"].as_str().unwrap().to_owned());
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["code"], "dependency");
+    assert_eq!(diagnostics[1]["code"], "privacy");
+    // Neither pack enforces its checks strictly
+    assert_eq!(diagnostics[0]["severity"], 2);
+    assert_eq!(diagnostics[1]["severity"], 2);
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({"start": {"line": 2, "character": 4}, "end": {"line": 2, "character": 9}})
+    );
+    assert!(diagnostics[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Dependency violation: `::Bar` belongs to `packs/bar`"));
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/definition", "params": {
+            "textDocument": {"uri": foo_uri},
+            "position": {"line": 2, "character": 7}
+        }}),
+    );
+    let definition = receive_response(&mut stdout, 2);
+    assert_eq!(definition["result"][0]["uri"], bar_uri);
+    assert_eq!(
+        definition["result"][0]["range"],
+        json!({"start": {"line": 0, "character": 7}, "end": {"line": 0, "character": 10}})
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 3, "method": "textDocument/codeAction", "params": {
+            "textDocument": {"uri": foo_uri},
+            "range": diagnostics[1]["range"],
+            "context": {"diagnostics": diagnostics}
+        }}),
+    );
+    let code_actions = receive_response(&mut stdout, 3);
+    let commands: Vec<Value> = code_actions["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|action| action["command"].clone())
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            json!({
+                "title": "Add `packs/bar` as a dependency of `packs/foo`",
+                "command": "pks.addDependency",
+                "arguments": ["packs/foo", "packs/bar"]
+            }),
+            json!({
+                "title": "Add `::Bar` to the ignored_private_constants of `packs/bar`",
+                "command": "pks.ignorePrivateConstant",
+                "arguments": ["packs/bar", "::Bar"]
+            }),
+            json!({
+                "title": "Move `packs/bar/app/services/bar.rb` into the public folder of `packs/bar`",
+                "command": "pks.moveToPublicFolder",
+                "arguments": ["packs/bar", "packs/bar/app/services/bar.rb"]
+            }),
+        ]
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+    );
+    receive_response(&mut stdout, 4);
+    send(&mut stdin, json!({"jsonrpc": "2.0", "method": "exit"}));
+    assert!(lsp.wait()?.success());
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_lsp_execute_command_updates_package_yml() -> Result<(), Box<dyn Error>>
+{
+    let tmp_dir = tempfile::TempDir::new()?;
+    let root = tmp_dir.path().canonicalize()?;
+    for (path, contents) in [
+        ("packwerk.yml", ""),
+        ("package.yml", ""),
+        ("packs/foo/package.yml", "enforce_dependencies: true\n"),
+        ("packs/bar/package.yml", "enforce_privacy: true\n"),
+        ("packs/foo/app/services/foo.rb", "module Foo\n  Bar\nend\n"),
+        ("packs/bar/app/services/bar.rb", "module Bar\nend\n"),
+    ] {
+        std::fs::create_dir_all(root.join(path).parent().unwrap())?;
+        std::fs::write(root.join(path), contents)?;
+    }
+    let foo_uri = format!(
+        "file://{}",
+        root.join("packs/foo/app/services/foo.rb").display()
+    );
+
+    let mut lsp = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(&root)
+        .arg("--no-cache")
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = lsp.stdin.take().unwrap();
+    let mut stdout = BufReader::new(lsp.stdout.take().unwrap());
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+    );
+    receive_response(&mut stdout, 1);
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+    );
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": {"uri": foo_uri, "languageId": "ruby", "version": 1, "text": ""}
+        }}),
+    );
+    let diagnostics = receive(&mut stdout);
+    assert_eq!(
+        diagnostics["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "workspace/executeCommand", "params": {
+            "command": "pks.addDependency",
+            "arguments": ["packs/foo", "packs/bar"]
+        }}),
+    );
+    let diagnostics = receive(&mut stdout);
+    let diagnostics = diagnostics["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "privacy");
+    receive_response(&mut stdout, 2);
+    assert_eq!(
+        std::fs::read_to_string(root.join("packs/foo/package.yml"))?,
+        "enforce_dependencies: true\ndependencies:\n- packs/bar\n"
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+    );
+    receive_response(&mut stdout, 3);
+    send(&mut stdin, json!({"jsonrpc": "2.0", "method": "exit"}));
+    assert!(lsp.wait()?.success());
+    Ok(())
+}
@@ -0,0 +1,64 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::{error::Error, process::Command};
+
+mod common;
+
+#[test]
+fn test_metrics_json() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("metrics")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let metrics: Value = serde_json::from_slice(&output)?;
+    assert_eq!(metrics.as_array().unwrap().len(), 4);
+    assert_eq!(
+        metrics[3],
+        json!({
+            "pack": "packs/foo",
+            "files": 3,
+            "afferent_coupling": 0,
+            "efferent_coupling": 1,
+            "instability": 1.0,
+            "public_constants": 0,
+            "private_constants": 2,
+            "todo_violations": {}
+        })
+    );
+    assert_eq!(metrics[2]["pack"], "packs/baz");
+    assert_eq!(metrics[2]["afferent_coupling"], 1);
+    assert_eq!(metrics[2]["instability"], 0.0);
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_metrics_csv_with_todo_violations() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/contains_package_todo")
+        .arg("metrics")
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "pack,files,afferent_coupling,efferent_coupling,instability,public_constants,private_constants,todo_dependency\n",
+        ))
+        .stdout(predicate::str::contains("packs/foo,2,0,0,,0,2,2\n"));
+
+    common::teardown();
+    Ok(())
+}
@@ -0,0 +1,47 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+
+mod common;
+
+fn check(root: &Path) -> assert_cmd::assert::Assert {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(root)
+        .arg("check")
+        .assert()
+}
+
+#[test]
+fn test_check_with_packed_cache() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = common::copy_fixture("app_with_packed_cache");
+    let root = tmp_dir.path();
+
+    // The first run fills the cache, the second one reads from it
+    for _ in 0..2 {
+        check(root)
+            .failure()
+            .stdout(predicate::str::contains("1 violation(s) detected:"));
+    }
+    let cache_files: Vec<_> =
+        fs::read_dir(root.join("tmp/cache/packwerk/zeitwerk"))?
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_dir())
+            .flat_map(|dir| fs::read_dir(dir).unwrap())
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+    assert!(cache_files.contains(&"packed_cache.bin".to_owned()));
+
+    common::write_file(
+        root,
+        "packs/foo/package.yml",
+        "enforce_dependencies: true\ndependencies:\n- packs/bar\n",
+    );
+    check(root)
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::{error::Error, process::Command};
+
+mod common;
+
+// app_with_unparseable_file has no violations, but one file of `packs/foo`
+// doesn't parse
+
+#[test]
+fn test_check_reports_parse_errors() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_unparseable_file")
+        .arg("--no-cache")
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 parse error(s) detected, violations in these files may be missing:",
+        ))
+        .stdout(predicate::str::contains("Parse error: unexpected kEND"))
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
+
+#[test]
+fn test_check_reports_parse_errors_as_json() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_unparseable_file")
+        .arg("--no-cache")
+        .arg("check")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: Value = serde_json::from_slice(&output)?;
+    let parse_errors = json["parse_errors"].as_array().unwrap();
+    assert_eq!(parse_errors.len(), 1);
+    assert_eq!(parse_errors[0]["file"], "packs/foo/app/services/foo.rb");
+    assert_eq!(parse_errors[0]["violation_type"], "parse_error");
+    assert_eq!(parse_errors[0]["line"], 4);
+    Ok(())
+}
+
+#[test]
+fn test_check_fails_on_parse_errors() -> Result<(), Box<dyn Error>> {
+    let app = common::copy_fixture("app_with_unparseable_file");
+    common::write_file(
+        app.path(),
+        "packwerk.yml",
+        "fail_on_parse_errors: true\n",
+    );
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("--no-cache")
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 parse error(s) detected"));
+    Ok(())
+}
@@ -0,0 +1,75 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::{error::Error, fs};
+
+mod common;
+
+fn app_with_ruby_parser(ruby_parser: &str) -> tempfile::TempDir {
+    let app = common::copy_fixture("simple_app");
+    let packwerk_yml = app.path().join("packwerk.yml");
+    let config = fs::read_to_string(&packwerk_yml).unwrap();
+    fs::write(
+        &packwerk_yml,
+        format!("{}\nruby_parser: {}\n", config, ruby_parser),
+    )
+    .unwrap();
+    app
+}
+
+#[cfg(feature = "prism")]
+#[test]
+fn test_check_with_prism() -> Result<(), Box<dyn Error>> {
+    let app = app_with_ruby_parser("prism");
+    common::write_file(
+        app.path(),
+        "packs/foo/app/services/foo.rb",
+        "module Foo\n  def self.call(*) = ::Bar.new(*)\nend\n",
+    );
+
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("parse error").not())
+        .stdout(predicate::str::contains("2 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            ":2:21\nDependency violation: `::Bar` belongs to `packs/bar`",
+        ));
+    Ok(())
+}
+
+#[cfg(not(feature = "prism"))]
+#[test]
+fn test_check_with_prism_needs_the_prism_feature() -> Result<(), Box<dyn Error>>
+{
+    let app = app_with_ruby_parser("prism");
+
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("check")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "needs `pks` to be built with the `prism` feature",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_check_with_lib_ruby_parser() -> Result<(), Box<dyn Error>> {
+    let app = app_with_ruby_parser("lib_ruby_parser");
+
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("2 violation(s) detected:"));
+    Ok(())
+}
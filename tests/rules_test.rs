@@ -0,0 +1,107 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+// In app_with_rules, `packs/domains/billing` refers to `Admin`, defined in
+// `packs/apps/admin`, and domains may not depend on apps. This is a copy with
+// `rules` instead.
+fn app_with_rules(rules: &str) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_rules");
+    common::write_file(
+        tmp_dir.path(),
+        "packwerk.yml",
+        &format!("packs:\n  - packs/**/*\nrules:\n{}", rules),
+    );
+    tmp_dir
+}
+
+fn pks(root: &Path, command: &str) -> Command {
+    let mut pks = Command::new(cargo_bin!("packs"));
+    pks.arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg(command);
+    pks
+}
+
+#[test]
+fn test_deny_rule_violation() -> Result<(), Box<dyn Error>> {
+    pks(Path::new("tests/fixtures/app_with_rules"), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "billing.rb\x1b[0m:3:4\nRule violation: `::Admin` belongs to `packs/apps/admin`, which cannot be accessed from `packs/domains/billing`: Domains can't depend on apps",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_allow_rule_violation() -> Result<(), Box<dyn Error>> {
+    let denied = app_with_rules(
+        "  - from: packs/platform/*\n    to: packs/apps/*\n    policy: allow\n",
+    );
+    pks(denied.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "only packs matching `packs/platform/*` may depend on packs matching `packs/apps/*`",
+        ));
+
+    let allowed = app_with_rules(
+        "  - from: packs/domains/*\n    to: packs/apps/*\n    policy: allow\n",
+    );
+    pks(allowed.path(), "check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
+
+#[test]
+fn test_rule_violation_todo() -> Result<(), Box<dyn Error>> {
+    let app = common::copy_fixture("app_with_rules");
+    pks(app.path(), "update").assert().success();
+    let package_todo = fs::read_to_string(
+        app.path().join("packs/domains/billing/package_todo.yml"),
+    )?;
+    assert!(package_todo.contains("- rule"));
+    pks(app.path(), "check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
+
+#[test]
+fn test_rule_violation_strict_mode() -> Result<(), Box<dyn Error>> {
+    let app = common::copy_fixture("app_with_rules");
+    common::write_file(
+        app.path(),
+        "packs/domains/billing/package.yml",
+        "enforce_rules: strict\n",
+    );
+    pks(app.path(), "check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "packs/domains/billing cannot have rule violations on packs/apps/admin because strict mode is enabled",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_rule_glob() -> Result<(), Box<dyn Error>> {
+    let app = app_with_rules(
+        "  - from: packs/[domains\n    to: packs/apps/*\n    policy: deny\n",
+    );
+    pks(app.path(), "check").assert().failure().stderr(
+        predicate::str::contains("Invalid glob in rules: `packs/[domains`"),
+    );
+    Ok(())
+}
@@ -0,0 +1,125 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::Command;
+use serde_json::Value;
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Write},
+    process::Stdio,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+mod common;
+
+fn responses(stdout: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_server_answers_requests_over_stdio() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("server")
+        .write_stdin(
+            "\
+{\"id\": 1, \"method\": \"check\"}
+{\"id\": 2, \"method\": \"check\", \"params\": {\"files\": [\"packs/bar/app/services/bar.rb\"]}}
+{\"id\": 3, \"method\": \"validate\"}
+{\"id\": 4, \"method\": \"unknown\"}
+{\"id\": 5, \"method\": \"shutdown\"}
+",
+        )
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let responses = responses(&output);
+    assert_eq!(responses.len(), 5);
+
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["result"]["status"], "failure");
+    let violations = responses[0]["result"]["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0]["file"], "packs/foo/app/services/foo.rb");
+
+    assert_eq!(responses[1]["result"]["violations"], Value::Array(vec![]));
+    assert_eq!(responses[2]["result"], Value::Array(vec![]));
+    assert_eq!(responses[3]["error"], "Unknown method: unknown");
+    assert_eq!(responses[4]["id"], 5);
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_server_rechecks_changed_files() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = TempDir::new()?;
+    let root = tmp_dir.path().canonicalize()?;
+    common::write_file(&root, "packwerk.yml", "");
+    common::write_file(&root, "package.yml", "enforce_dependencies: true\n");
+    common::write_file(
+        &root,
+        "packs/foo/package.yml",
+        "enforce_dependencies: true\n",
+    );
+    common::write_file(
+        &root,
+        "packs/bar/package.yml",
+        "enforce_dependencies: true\n",
+    );
+    common::write_file(
+        &root,
+        "packs/foo/app/services/foo.rb",
+        "module Foo\nend\n",
+    );
+    common::write_file(
+        &root,
+        "packs/bar/app/services/bar.rb",
+        "module Bar\nend\n",
+    );
+
+    let mut server = std::process::Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(&root)
+        .arg("--no-cache")
+        .arg("server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = server.stdin.take().unwrap();
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+    let mut check = || -> usize {
+        writeln!(stdin, "{{\"id\": 1, \"method\": \"check\"}}").unwrap();
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str::<Value>(&line).unwrap()["result"]["violations"]
+            .as_array()
+            .unwrap()
+            .len()
+    };
+
+    assert_eq!(check(), 0);
+
+    common::write_file(
+        &root,
+        "packs/foo/app/services/foo.rb",
+        "module Foo\n  Bar\nend\n",
+    );
+    let started = Instant::now();
+    let mut violations = check();
+    while violations == 0 && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(50));
+        violations = check();
+    }
+    assert_eq!(violations, 1);
+
+    server.kill()?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+// An app whose `packs/foo` only refers to `Bar` of `packs/bar` through a
+// string
+fn app_with_string_reference(packwerk_yml: &str) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_string_reference");
+    common::write_file(tmp_dir.path(), "packwerk.yml", packwerk_yml);
+    tmp_dir
+}
+
+fn check(app: &TempDir) -> assert_cmd::assert::Assert {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("--no-cache")
+        .arg("check")
+        .assert()
+}
+
+#[test]
+fn test_string_references_are_ignored_by_default() -> Result<(), Box<dyn Error>>
+{
+    let app = app_with_string_reference("");
+    check(&app)
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
+
+#[test]
+fn test_string_references() -> Result<(), Box<dyn Error>> {
+    let app = app_with_string_reference("string_references: true\n");
+    check(&app)
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains("foo.rb\x1b[0m:3:4"))
+        .stdout(predicate::str::contains(
+            "Dependency violation: `::Bar` belongs to `packs/bar`",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_string_references_not_enforced_by_checker() -> Result<(), Box<dyn Error>>
+{
+    let app = app_with_string_reference(
+        "string_references: true\nstring_reference_enforcements:\n  - privacy\n",
+    );
+    check(&app)
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
@@ -0,0 +1,100 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::{error::Error, path::Path, process::Command};
+use tempfile::TempDir;
+
+mod common;
+
+// In app_with_suppressed_violation, `packs/foo` refers to `Bar`, defined in
+// `packs/bar`, without depending on it. This is a copy whose foo.rb has
+// `foo_contents`.
+fn app(foo_contents: &str) -> TempDir {
+    let tmp_dir = common::copy_fixture("app_with_suppressed_violation");
+    common::write_file(
+        tmp_dir.path(),
+        "packs/foo/app/services/foo.rb",
+        foo_contents,
+    );
+    tmp_dir
+}
+
+fn check(root: &Path) -> Command {
+    let mut command = Command::new(cargo_bin!("packs"));
+    command
+        .arg("--project-root")
+        .arg(root)
+        .arg("--no-cache")
+        .arg("check");
+    command
+}
+
+#[test]
+fn test_suppressed_violation() -> Result<(), Box<dyn Error>> {
+    check(Path::new("tests/fixtures/app_with_suppressed_violation"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No violations detected!"));
+    Ok(())
+}
+
+#[test]
+fn test_stale_suppression() -> Result<(), Box<dyn Error>> {
+    let app = app(
+        "class Foo\n  # packs:disable-next-line dependency,privacy -- reason: legacy billing shim\n  Bar\nend\n",
+    );
+    check(app.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 suppression error(s) detected:"))
+        .stdout(predicate::str::contains("foo.rb\x1b[0m:2\n"))
+        .stdout(predicate::str::contains(
+            "Stale suppression: no privacy violation is suppressed on the next line, please remove it",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_suppression_without_reason() -> Result<(), Box<dyn Error>> {
+    let app =
+        app("class Foo\n  # packs:disable-next-line dependency\n  Bar\nend\n");
+    check(app.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 violation(s) detected:"))
+        .stdout(predicate::str::contains(
+            "Suppression of dependency violations is missing a reason",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_list_suppressions() -> Result<(), Box<dyn Error>> {
+    let app = app(
+        "class Foo\n  # packs:disable-next-line dependency,privacy -- legacy billing shim\n  Bar\nend\n",
+    );
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg(app.path())
+        .arg("--no-cache")
+        .arg("list-suppressions")
+        .arg("--format")
+        .arg("json")
+        .output()?;
+
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        json,
+        serde_json::json!([{
+            "file": "packs/foo/app/services/foo.rb",
+            "line": 2,
+            "violation_types": ["dependency", "privacy"],
+            "reason": "legacy billing shim",
+            "status": "stale",
+            "unused_violation_types": ["privacy"],
+        }])
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#[allow(deprecated)]
+use assert_cmd::cargo::cargo_bin;
+use assert_cmd::prelude::*;
+use serde_json::Value;
+use std::{error::Error, process::Command};
+
+// Constant name, line and column of each violation
+type Location = (String, u64, u64);
+
+// In app_with_templates, `packs/foo` depends on `Bar` and `Baz` of
+// `packs/bar` from its views only. These are the violations of `view_path`.
+fn violation_locations(
+    view_path: &str,
+) -> Result<Vec<Location>, Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_templates")
+        .arg("--no-cache")
+        .arg("check")
+        .arg("--json")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: Value = serde_json::from_slice(&output)?;
+    let mut locations: Vec<Location> = json["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|violation| {
+            violation["file"] == format!("packs/foo/{}", view_path).as_str()
+        })
+        .map(|violation| {
+            (
+                violation["constant_name"].as_str().unwrap().to_owned(),
+                violation["line"].as_u64().unwrap(),
+                violation["column"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    locations.sort();
+    Ok(locations)
+}
+
+#[test]
+fn test_check_reports_erb_template_locations() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        violation_locations("app/views/foo.html.erb")?,
+        vec![("::Bar".to_owned(), 4, 6), ("::Baz".to_owned(), 7, 5)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_reports_haml_template_locations() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        violation_locations("app/views/foo.html.haml")?,
+        vec![("::Bar".to_owned(), 5, 9), ("::Baz".to_owned(), 7, 16)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_reports_slim_template_locations() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        violation_locations("app/views/foo.html.slim")?,
+        vec![("::Bar".to_owned(), 5, 9), ("::Baz".to_owned(), 7, 13)]
+    );
+    Ok(())
+}
@@ -2,6 +2,7 @@
 use assert_cmd::cargo::cargo_bin;
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
+use serde_json::Value;
 use std::{error::Error, process::Command};
 
 mod common;
@@ -64,3 +65,66 @@ fn test_validate_with_referencing_unknown_pack() -> Result<(), Box<dyn Error>> {
     common::teardown();
     Ok(())
 }
+
+#[test]
+fn test_validate_sarif_output() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/app_with_layer_violations_in_yml")
+        .arg("validate")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let sarif: Value = serde_json::from_slice(&output)?;
+    let invocation = &sarif["runs"][0]["invocations"][0];
+    assert_eq!(invocation["executionSuccessful"], false);
+    let notifications =
+        invocation["toolExecutionNotifications"].as_array().unwrap();
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0]["level"], "error");
+    assert_eq!(notifications[0]["descriptor"]["id"], "layer");
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_validate_json_and_format_conflict() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("validate")
+        .arg("--json")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    common::teardown();
+    Ok(())
+}
+
+#[test]
+fn test_validate_rejects_unsupported_formats() -> Result<(), Box<dyn Error>> {
+    Command::new(cargo_bin!("packs"))
+        .arg("--project-root")
+        .arg("tests/fixtures/simple_app")
+        .arg("validate")
+        .arg("--format")
+        .arg("junit")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'junit'"))
+        .stderr(predicate::str::contains(
+            "[possible values: text, json, sarif]",
+        ));
+
+    common::teardown();
+    Ok(())
+}